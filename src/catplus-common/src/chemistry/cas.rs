@@ -0,0 +1,31 @@
+//! CAS Registry Numbers, e.g. `67-56-1`.
+use anyhow::{bail, Result};
+
+/// Checks the format of a CAS Registry Number (2 to 7 digits, 2 digits and a check digit,
+/// separated by hyphens) and its check digit: the sum of the other digits, each multiplied by
+/// its position from the right, modulo 10.
+pub fn check_cas_number(cas_number: &str) -> Result<()> {
+    let parts: Vec<&str> = cas_number.split('-').collect();
+    let well_formed = match parts.as_slice() {
+        [first, second, check] => {
+            (2..=7).contains(&first.len())
+                && second.len() == 2
+                && check.len() == 1
+                && parts.iter().all(|part| part.chars().all(|c| c.is_ascii_digit()))
+        }
+        _ => false,
+    };
+    if !well_formed {
+        bail!("'{}' is not a CAS number, expected e.g. 67-56-1", cas_number);
+    }
+
+    let digits: Vec<u32> =
+        parts[..2].concat().chars().filter_map(|c| c.to_digit(10)).rev().collect();
+    let checksum: u32 =
+        digits.iter().enumerate().map(|(position, digit)| digit * (position as u32 + 1)).sum();
+    let check_digit = parts[2].parse::<u32>()?;
+    if checksum % 10 != check_digit {
+        bail!("Invalid check digit in CAS number '{}', expected {}", cas_number, checksum % 10);
+    }
+    Ok(())
+}
@@ -0,0 +1,220 @@
+//! Consistency checks of the identifiers of chemicals: CAS number, InChI, SMILES and formula.
+use crate::{
+    chemistry::{cas::check_cas_number, formula::Formula, inchi::Inchi, smiles::Molecule},
+    models::core::Chemical,
+    units::unit::QuantityKind,
+};
+use serde::Serialize;
+use std::fmt;
+
+/// Relative difference tolerated between the molecular mass of a chemical and the mass of its
+/// formula, for rounding.
+const MASS_TOLERANCE: f64 = 1e-3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The values disagree, e.g. a formula that does not match the SMILES.
+    Warning,
+    /// A value is malformed, e.g. a CAS number with a wrong check digit.
+    Error,
+}
+
+/// A problem found in the identifiers of a chemical.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ChemicalIssue {
+    pub chemical_id: String,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl fmt::Display for ChemicalIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Chemical '{}': {}", self.chemical_id, self.message)
+    }
+}
+
+/// Checks the CAS check digit, the syntax of the InChI, SMILES and molecular formula, and
+/// that the molecular formula agrees with the InChI, the SMILES and the molecular mass.
+///
+/// Empty values are not checked.
+pub fn check_chemical(chemical: &Chemical) -> Vec<ChemicalIssue> {
+    let mut issues = Vec::new();
+    let mut report = |severity, message: String| {
+        issues.push(ChemicalIssue { chemical_id: chemical.chemical_id.clone(), severity, message })
+    };
+
+    if let Some(cas_number) = chemical.cas_number.as_deref().filter(|cas| !cas.is_empty()) {
+        if let Err(error) = check_cas_number(cas_number) {
+            report(Severity::Error, format!("{:#}", error));
+        }
+    }
+
+    let mut parse = |value: &str, parser: &dyn Fn(&str) -> anyhow::Result<Formula>| {
+        if value.trim().is_empty() {
+            return None;
+        }
+        parser(value).map_err(|error| report(Severity::Error, format!("{:#}", error))).ok()
+    };
+    let formula = parse(&chemical.molecular_formula, &|formula| {
+        formula.parse::<Formula>().map_err(|error| {
+            anyhow::anyhow!("Invalid molecular formula '{}': {:#}", formula, error)
+        })
+    });
+    let inchi_formula =
+        parse(&chemical.inchi, &|inchi| inchi.parse::<Inchi>()?.molecular_formula());
+    // SMILES with wildcard atoms have no formula, their syntax is still checked.
+    let smiles_formula = match &chemical.smiles {
+        smiles if smiles.trim().is_empty() => None,
+        smiles => smiles
+            .parse::<Molecule>()
+            .map_err(|error| report(Severity::Error, format!("{:#}", error)))
+            .ok()
+            .and_then(|molecule| molecule.formula()),
+    };
+
+    let Some(formula) = formula else {
+        return issues;
+    };
+    for (source, other) in [("InChI", inchi_formula), ("SMILES", smiles_formula)] {
+        if let Some(other) = other.filter(|other| *other != formula) {
+            report(
+                Severity::Warning,
+                format!(
+                    "Molecular formula {} does not match the formula {} of the {}",
+                    formula, other, source
+                ),
+            );
+        }
+    }
+
+    let molecular_mass = &chemical.molecular_mass;
+    if molecular_mass.unit.quantity_kind() != QuantityKind::MolarMass {
+        report(
+            Severity::Warning,
+            format!("Molecular mass unit '{}' is not a molar mass", molecular_mass.unit.label()),
+        );
+    } else if let Some((mass, _)) = molecular_mass.unit.to_si(molecular_mass.value) {
+        // SI molar masses are in kg/mol.
+        let (mass, expected) = (mass * 1e3, formula.molar_mass());
+        if (mass - expected).abs() > MASS_TOLERANCE * expected {
+            report(
+                Severity::Warning,
+                format!(
+                    "Molecular mass {} {} does not match the mass {:.3} g/mol of formula {}",
+                    molecular_mass.value,
+                    molecular_mass.unit.label(),
+                    expected,
+                    formula
+                ),
+            );
+        }
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{models::core::Observation, units::unit::Unit};
+
+    fn sodium_methoxide() -> Chemical {
+        Chemical {
+            chemical_id: "19".to_string(),
+            chemical_name: "Sodium methoxide".to_string(),
+            cas_number: Some("124-41-4".to_string()),
+            molecular_mass: Observation {
+                value: 54.024,
+                unit: Unit::parse("g/mol").unwrap(),
+                error_margin: None,
+            },
+            smiles: "C[O-].[Na+]".to_string(),
+            swiss_cat_number: None,
+            inchi: "InChI=1S/CH3O.Na/c1-2;/h1H3;/q-1;+1".to_string(),
+            keywords: None,
+            molecular_formula: "CH3NaO".to_string(),
+            density: None,
+        }
+    }
+
+    #[test]
+    fn test_cas_number() {
+        assert!(check_cas_number("67-56-1").is_ok());
+        assert!(check_cas_number("7732-18-5").is_ok());
+        let error = check_cas_number("67-56-2").unwrap_err().to_string();
+        assert!(error.contains("expected 1"), "{}", error);
+        assert!(check_cas_number("67561").is_err());
+        assert!(check_cas_number("1-56-1").is_err());
+    }
+
+    #[test]
+    fn test_smiles_formula() {
+        for (smiles, formula) in [
+            ("CCO", "C2H6O"),
+            ("c1ccccc1", "C6H6"),
+            ("c1ccncc1", "C5H5N"),
+            ("c1ccoc1", "C4H4O"),
+            ("c1ccsc1", "C4H4S"),
+            ("c1cc[nH]c1", "C4H5N"),
+            ("Cn1ccnc1", "C4H6N2"),
+            ("CC(=O)O", "C2H4O2"),
+            ("CS(=O)C", "C2H6OS"),
+            ("C[C@@H](N)C(=O)O", "C3H7NO2"),
+            ("[13CH4]", "CH4"),
+            ("C[O-].[Na+]", "CH3NaO"),
+            ("CN1C=NC2=C1C(=O)NC(=O)N2C", "C7H8N4O2"),
+            ("OC(=O)C1CCCC%10CC1%10", "C8H12O2"),
+        ] {
+            let molecule: Molecule = smiles.parse().unwrap();
+            assert_eq!(molecule.formula().unwrap().to_string(), formula, "{}", smiles);
+        }
+        assert_eq!("C[O-].[Na+]".parse::<Molecule>().unwrap().charge(), 0);
+        assert!("C*".parse::<Molecule>().unwrap().formula().is_none());
+
+        for invalid in ["C1CC", "C(C", "CC)", "C==C", "Xx", "[Xx]", "C[C", "=C", ""] {
+            assert!(invalid.parse::<Molecule>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_formula_and_inchi() {
+        let formula: Formula = "CuSO4.5H2O".parse().unwrap();
+        assert_eq!(formula.to_string(), "CuH10O9S");
+        assert_eq!("Ca(OH)2".parse::<Formula>().unwrap().to_string(), "CaH2O2");
+        assert!((formula.molar_mass() - 249.68).abs() < 0.01);
+        assert!("C2H6Q".parse::<Formula>().is_err());
+
+        let inchi: Inchi = "InChI=1S/C2H4O2/c1-2(3)4/h1H3,(H,3,4)/p-1".parse().unwrap();
+        assert!(inchi.is_standard());
+        assert_eq!(inchi.layer('c'), Some("1-2(3)4"));
+        assert_eq!(inchi.molecular_formula().unwrap().to_string(), "C2H3O2");
+        assert!("InChI=1S/CH4O/c1-2/h2H,1H3".parse::<Inchi>().is_ok());
+        assert!("CH4O/c1-2".parse::<Inchi>().is_err());
+        assert!("InChI=1S/CH4O/c1-2(/h2H,1H3".parse::<Inchi>().is_err());
+        assert!("InChI=1S/CH4O/x1".parse::<Inchi>().is_err());
+    }
+
+    #[test]
+    fn test_check_chemical() {
+        assert_eq!(check_chemical(&sodium_methoxide()), vec![]);
+
+        let chemical = Chemical {
+            cas_number: Some("124-41-5".to_string()),
+            smiles: "C[O-.[Na+]".to_string(),
+            molecular_formula: "C2H3NaO".to_string(),
+            ..sodium_methoxide()
+        };
+        let issues = check_chemical(&chemical);
+        let severities: Vec<Severity> = issues.iter().map(|issue| issue.severity).collect();
+        assert_eq!(
+            severities,
+            [Severity::Error, Severity::Error, Severity::Warning, Severity::Warning],
+            "{:?}",
+            issues
+        );
+        assert!(issues[0].to_string().starts_with("Chemical '19': Invalid check digit"));
+        assert!(issues[2].message.contains("formula CH3NaO of the InChI"));
+        assert!(issues[3].message.contains("Molecular mass 54.024 g/mol"));
+    }
+}
@@ -0,0 +1,265 @@
+//! Molecular formulas, e.g. `C2H6O`, `Ca(OH)2` or `CuSO4.5H2O`.
+use anyhow::{bail, Context, Result};
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+/// Standard atomic weights in g/mol (the mass number of the most stable isotope for
+/// elements without stable isotopes).
+const ELEMENTS: &[(&str, f64)] = &[
+    ("H", 1.008),
+    ("He", 4.0026),
+    ("Li", 6.94),
+    ("Be", 9.0122),
+    ("B", 10.81),
+    ("C", 12.011),
+    ("N", 14.007),
+    ("O", 15.999),
+    ("F", 18.998),
+    ("Ne", 20.180),
+    ("Na", 22.990),
+    ("Mg", 24.305),
+    ("Al", 26.982),
+    ("Si", 28.085),
+    ("P", 30.974),
+    ("S", 32.06),
+    ("Cl", 35.45),
+    ("Ar", 39.948),
+    ("K", 39.098),
+    ("Ca", 40.078),
+    ("Sc", 44.956),
+    ("Ti", 47.867),
+    ("V", 50.942),
+    ("Cr", 51.996),
+    ("Mn", 54.938),
+    ("Fe", 55.845),
+    ("Co", 58.933),
+    ("Ni", 58.693),
+    ("Cu", 63.546),
+    ("Zn", 65.38),
+    ("Ga", 69.723),
+    ("Ge", 72.630),
+    ("As", 74.922),
+    ("Se", 78.971),
+    ("Br", 79.904),
+    ("Kr", 83.798),
+    ("Rb", 85.468),
+    ("Sr", 87.62),
+    ("Y", 88.906),
+    ("Zr", 91.224),
+    ("Nb", 92.906),
+    ("Mo", 95.95),
+    ("Tc", 98.0),
+    ("Ru", 101.07),
+    ("Rh", 102.91),
+    ("Pd", 106.42),
+    ("Ag", 107.87),
+    ("Cd", 112.41),
+    ("In", 114.82),
+    ("Sn", 118.71),
+    ("Sb", 121.76),
+    ("Te", 127.60),
+    ("I", 126.90),
+    ("Xe", 131.29),
+    ("Cs", 132.91),
+    ("Ba", 137.33),
+    ("La", 138.91),
+    ("Ce", 140.12),
+    ("Pr", 140.91),
+    ("Nd", 144.24),
+    ("Pm", 145.0),
+    ("Sm", 150.36),
+    ("Eu", 151.96),
+    ("Gd", 157.25),
+    ("Tb", 158.93),
+    ("Dy", 162.50),
+    ("Ho", 164.93),
+    ("Er", 167.26),
+    ("Tm", 168.93),
+    ("Yb", 173.05),
+    ("Lu", 174.97),
+    ("Hf", 178.49),
+    ("Ta", 180.95),
+    ("W", 183.84),
+    ("Re", 186.21),
+    ("Os", 190.23),
+    ("Ir", 192.22),
+    ("Pt", 195.08),
+    ("Au", 196.97),
+    ("Hg", 200.59),
+    ("Tl", 204.38),
+    ("Pb", 207.2),
+    ("Bi", 208.98),
+    ("Po", 209.0),
+    ("At", 210.0),
+    ("Rn", 222.0),
+    ("Fr", 223.0),
+    ("Ra", 226.0),
+    ("Ac", 227.0),
+    ("Th", 232.04),
+    ("Pa", 231.04),
+    ("U", 238.03),
+    ("Np", 237.0),
+    ("Pu", 244.0),
+    ("Am", 243.0),
+    ("Cm", 247.0),
+    ("Bk", 247.0),
+    ("Cf", 251.0),
+    ("Es", 252.0),
+    ("Fm", 257.0),
+    ("Md", 258.0),
+    ("No", 259.0),
+    ("Lr", 262.0),
+];
+
+/// The symbol of the element `symbol`, if it is one, e.g. `Cl`.
+pub fn element(symbol: &str) -> Option<&'static str> {
+    ELEMENTS.iter().find(|(element, _)| *element == symbol).map(|(element, _)| *element)
+}
+
+/// The number of atoms of each element of a compound.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Formula {
+    counts: BTreeMap<&'static str, u32>,
+}
+
+impl Formula {
+    /// Adds `count` atoms of `element`, a symbol returned by [element].
+    pub fn add(&mut self, element: &'static str, count: u32) {
+        if count > 0 {
+            *self.counts.entry(element).or_default() += count;
+        }
+    }
+
+    /// Adds `count` hydrogens, or removes them if `count` is negative (e.g. for the protons
+    /// removed by an InChI `/p` layer).
+    pub fn add_hydrogens(&mut self, count: i64) {
+        let hydrogens = self.count("H") as i64 + count;
+        self.counts.remove("H");
+        self.add("H", hydrogens.max(0) as u32);
+    }
+
+    pub fn merge(&mut self, other: &Formula) {
+        for (element, count) in &other.counts {
+            self.add(element, *count);
+        }
+    }
+
+    pub fn count(&self, element: &str) -> u32 {
+        self.counts.get(element).copied().unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// The molar mass in g/mol, from the standard atomic weights.
+    pub fn molar_mass(&self) -> f64 {
+        self.counts
+            .iter()
+            .map(|(element, count)| {
+                let (_, weight) = ELEMENTS.iter().find(|(symbol, _)| symbol == element).unwrap();
+                weight * *count as f64
+            })
+            .sum()
+    }
+}
+
+impl FromStr for Formula {
+    type Err = anyhow::Error;
+
+    /// Parses a formula whose components are separated by `.` (e.g. salts or hydrates),
+    /// each with an optional multiplier.
+    fn from_str(formula: &str) -> Result<Self> {
+        let mut parsed = Formula::default();
+        for component in formula.trim().split(['.', '·']) {
+            let digits = component.chars().take_while(|c| c.is_ascii_digit()).count();
+            let multiplier = match digits {
+                0 => 1,
+                _ => component[..digits].parse().context("Invalid multiplier")?,
+            };
+            let chars: Vec<char> = component[digits..].chars().collect();
+            let mut position = 0;
+            let group = parse_group(&chars, &mut position, None)
+                .with_context(|| format!("Invalid formula '{}'", formula))?;
+            if group.is_empty() {
+                bail!("Invalid formula '{}': empty component", formula);
+            }
+            for _ in 0..multiplier {
+                parsed.merge(&group);
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+/// Parses elements and parenthesized groups with their counts until `closing` (or the end).
+fn parse_group(chars: &[char], position: &mut usize, closing: Option<char>) -> Result<Formula> {
+    let mut group = Formula::default();
+    while let Some(&c) = chars.get(*position) {
+        if Some(c) == closing {
+            *position += 1;
+            return Ok(group);
+        }
+        *position += 1;
+        match c {
+            '(' | '[' => {
+                let inner = parse_group(chars, position, Some(if c == '(' { ')' } else { ']' }))?;
+                for _ in 0..parse_count(chars, position)? {
+                    group.merge(&inner);
+                }
+            }
+            'A'..='Z' => {
+                let mut symbol = c.to_string();
+                if let Some(&next) = chars.get(*position).filter(|c| c.is_ascii_lowercase()) {
+                    symbol.push(next);
+                    *position += 1;
+                }
+                let Some(element) = element(&symbol) else {
+                    bail!("unknown element '{}'", symbol);
+                };
+                group.add(element, parse_count(chars, position)?);
+            }
+            c => bail!("unexpected character '{}'", c),
+        }
+    }
+    match closing {
+        Some(closing) => bail!("missing '{}'", closing),
+        None => Ok(group),
+    }
+}
+
+fn parse_count(chars: &[char], position: &mut usize) -> Result<u32> {
+    let start = *position;
+    while chars.get(*position).is_some_and(|c| c.is_ascii_digit()) {
+        *position += 1;
+    }
+    match *position - start {
+        0 => Ok(1),
+        _ => Ok(chars[start..*position].iter().collect::<String>().parse()?),
+    }
+}
+
+/// Writes the formula in Hill order: carbon, hydrogen, then the other elements in
+/// alphabetical order; all alphabetical if there is no carbon.
+impl fmt::Display for Formula {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut elements: Vec<(&str, u32)> = Vec::new();
+        if self.count("C") > 0 {
+            elements.push(("C", self.count("C")));
+            if self.count("H") > 0 {
+                elements.push(("H", self.count("H")));
+            }
+        }
+        for (element, count) in &self.counts {
+            if !elements.iter().any(|(known, _)| known == element) {
+                elements.push((element, *count));
+            }
+        }
+        for (element, count) in elements {
+            match count {
+                1 => write!(f, "{}", element)?,
+                count => write!(f, "{}{}", element, count)?,
+            }
+        }
+        Ok(())
+    }
+}
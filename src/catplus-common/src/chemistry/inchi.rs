@@ -0,0 +1,108 @@
+//! IUPAC International Chemical Identifiers, e.g. `InChI=1S/CH4O/c1-2/h2H,1H3`.
+use crate::chemistry::formula::Formula;
+use anyhow::{bail, Context, Result};
+use std::str::FromStr;
+
+/// Characters allowed in the layers whose syntax is checked, by layer prefix.
+const LAYER_CHARACTERS: &[(char, &str)] = &[
+    ('c', "0123456789()-,;*"),
+    ('h', "0123456789H()-,;*"),
+    ('q', "0123456789+-;*"),
+    ('p', "0123456789+-;*"),
+    ('b', "0123456789+-,;*?"),
+    ('t', "0123456789+-,;*?"),
+    ('m', "01.;*"),
+    ('s', "123"),
+];
+
+/// Prefixes of the layers following the formula: connections, hydrogens, charge, protons,
+/// stereochemistry, isotopes, fixed hydrogens, reconnected metals and transposition.
+const LAYER_PREFIXES: &str = "chqpbtmsifro";
+
+/// An InChI split into its layers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Inchi {
+    /// `1S` for standard InChIs, `1` otherwise.
+    pub version: String,
+    /// The formula layer, with `.` between the components.
+    pub formula: String,
+    /// The other layers in order, with their prefix, e.g. `c1-2`.
+    pub layers: Vec<String>,
+}
+
+impl FromStr for Inchi {
+    type Err = anyhow::Error;
+
+    fn from_str(inchi: &str) -> Result<Self> {
+        let Some(identifier) = inchi.strip_prefix("InChI=") else {
+            bail!("'{}' is not an InChI, it should start with 'InChI='", inchi);
+        };
+        let mut layers = identifier.split('/');
+        let version = layers.next().unwrap_or_default().to_string();
+        if version != "1S" && version != "1" {
+            bail!("Unsupported InChI version '{}' in '{}'", version, inchi);
+        }
+        let formula = layers.next().unwrap_or_default().to_string();
+        formula.parse::<Formula>().with_context(|| format!("Invalid InChI '{}'", inchi))?;
+
+        let layers: Vec<String> = layers.map(str::to_string).collect();
+        for layer in &layers {
+            check_layer(layer).with_context(|| format!("Invalid InChI '{}'", inchi))?;
+        }
+
+        Ok(Inchi { version, formula, layers })
+    }
+}
+
+fn check_layer(layer: &str) -> Result<()> {
+    let mut chars = layer.chars();
+    let prefix = match chars.next() {
+        Some(prefix) if LAYER_PREFIXES.contains(prefix) => prefix,
+        Some(prefix) => bail!("unknown layer '/{}'", prefix),
+        None => bail!("empty layer"),
+    };
+    let content = chars.as_str();
+    if content.chars().any(char::is_whitespace) {
+        bail!("whitespace in layer '/{}'", layer);
+    }
+    if let Some((_, allowed)) = LAYER_CHARACTERS.iter().find(|(known, _)| *known == prefix) {
+        if let Some(c) = content.chars().find(|c| !allowed.contains(*c)) {
+            bail!("unexpected character '{}' in layer '/{}'", c, layer);
+        }
+        let mut depth = 0;
+        for c in content.chars() {
+            match c {
+                '(' => depth += 1,
+                ')' if depth == 0 => bail!("unbalanced parentheses in layer '/{}'", layer),
+                ')' => depth -= 1,
+                _ => {}
+            }
+        }
+        if depth != 0 {
+            bail!("unbalanced parentheses in layer '/{}'", layer);
+        }
+    }
+    Ok(())
+}
+
+impl Inchi {
+    pub fn is_standard(&self) -> bool {
+        self.version == "1S"
+    }
+
+    /// The first layer with `prefix`, without the prefix.
+    pub fn layer(&self, prefix: char) -> Option<&str> {
+        self.layers.iter().find_map(|layer| layer.strip_prefix(prefix))
+    }
+
+    /// The formula of the compound, including the protons added or removed by the `/p` layer.
+    pub fn molecular_formula(&self) -> Result<Formula> {
+        let mut formula: Formula = self.formula.parse()?;
+        if let Some(protons) = self.layer('p') {
+            formula.add_hydrogens(
+                protons.parse().with_context(|| format!("Invalid proton layer '/p{}'", protons))?,
+            );
+        }
+        Ok(formula)
+    }
+}
@@ -0,0 +1,104 @@
+//! InChIKeys, the hashed form of InChIs, e.g. `OKKJLVBELUTLKV-UHFFFAOYSA-N` for methanol.
+use crate::chemistry::inchi::Inchi;
+use sha2::{Digest, Sha256};
+
+/// Layers starting the second block of the key: stereochemistry, isotopes, fixed hydrogens
+/// and reconnected metals. The first block hashes the formula and the layers before them.
+const MINOR_LAYERS: &str = "btmsifr";
+
+/// Minor parts shorter than this are hashed twice over, as in the reference implementation.
+const MINOR_REPEAT_LENGTH: usize = 255;
+
+/// The InChIKey of an InChI: 14 letters hashing its skeleton (formula, connections and
+/// hydrogens), 8 letters hashing its stereochemistry and isotopes, the standard flag (`S` or
+/// `N`), the version (`A`) and the protonation (`N` for neutral, `M` for `/p-1`, `O` for `/p+1`,
+/// ...).
+pub fn inchi_key(inchi: &Inchi) -> String {
+    let mut major = vec![inchi.formula.as_str()];
+    let mut minor = String::new();
+    let mut protons = 0;
+    for layer in &inchi.layers {
+        if !minor.is_empty() || layer.starts_with(|c| MINOR_LAYERS.contains(c)) {
+            minor.push('/');
+            minor.push_str(layer);
+        } else if let Some(count) = layer.strip_prefix('p') {
+            protons = count.parse::<i64>().unwrap_or_default();
+        } else {
+            major.push(layer);
+        }
+    }
+    if minor.len() < MINOR_REPEAT_LENGTH {
+        minor = minor.repeat(2);
+    }
+
+    let major = Sha256::digest(major.join("/").as_bytes());
+    let minor = Sha256::digest(minor.as_bytes());
+    let protonation = match protons {
+        -12..=12 => (b'N' as i64 + protons) as u8 as char,
+        _ => 'A',
+    };
+    format!(
+        "{}{}{}{}{}-{}{}{}{}A-{}",
+        triplet(bits(&major, 0, 14)),
+        triplet(bits(&major, 14, 14)),
+        triplet(bits(&major, 28, 14)),
+        triplet(bits(&major, 42, 14)),
+        doublet(bits(&major, 56, 9)),
+        triplet(bits(&minor, 0, 14)),
+        triplet(bits(&minor, 14, 14)),
+        doublet(bits(&minor, 28, 9)),
+        if inchi.is_standard() { 'S' } else { 'N' },
+        protonation
+    )
+}
+
+/// Reads `count` bits from `start` in `hash`, least significant bits first.
+fn bits(hash: &[u8], start: usize, count: usize) -> usize {
+    (0..count)
+        .map(|bit| ((hash[(start + bit) / 8] >> ((start + bit) % 8)) as usize & 1) << bit)
+        .sum()
+}
+
+/// The `index`th of the 16384 triplets of letters used by InChIKeys: the triplets in
+/// alphabetical order, without those starting with `E` (for exponents) nor `TAA` to `TTV`.
+fn triplet(index: usize) -> String {
+    let index = match index {
+        0..2704 => index,
+        2704..12168 => index + 676,
+        _ => index + 1192,
+    };
+    [index / 676, index / 26 % 26, index % 26]
+        .iter()
+        .map(|letter| (b'A' + *letter as u8) as char)
+        .collect()
+}
+
+/// The `index`th pair of letters in alphabetical order.
+fn doublet(index: usize) -> String {
+    [index / 26, index % 26].iter().map(|letter| (b'A' + *letter as u8) as char).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inchi_key() {
+        for (inchi, key) in [
+            ("InChI=1S/H2O/h1H2", "XLYOFNOQVPJJNP-UHFFFAOYSA-N"),
+            ("InChI=1S/CH4O/c1-2/h2H,1H3", "OKKJLVBELUTLKV-UHFFFAOYSA-N"),
+            ("InChI=1S/C8H18/c1-3-5-7-8-6-4-2/h3-8H2,1-2H3", "TVMXDCGIABBOFY-UHFFFAOYSA-N"),
+            ("InChI=1S/C2H4O2/c1-2(3)4/h1H3,(H,3,4)/p-1", "QTBSBXVTEAMEQO-UHFFFAOYSA-M"),
+            (
+                "InChI=1S/C3H7NO2/c1-2(4)3(5)6/h2H,4H2,1H3,(H,5,6)/t2-/m0/s1",
+                "QNAYBMKLOCPYGJ-REOHCLBHSA-N",
+            ),
+            (
+                "InChI=1S/C3H7NO2/c1-2(4)3(5)6/h2H,4H2,1H3,(H,5,6)/t2-/m1/s1",
+                "QNAYBMKLOCPYGJ-UWTATZPHSA-N",
+            ),
+        ] {
+            assert_eq!(inchi_key(&inchi.parse().unwrap()), key, "{}", inchi);
+        }
+    }
+}
@@ -0,0 +1,6 @@
+pub mod cas;
+pub mod check;
+pub mod formula;
+pub mod inchi;
+pub mod inchikey;
+pub mod smiles;
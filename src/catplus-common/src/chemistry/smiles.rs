@@ -0,0 +1,372 @@
+//! A parser of SMILES strings (see http://opensmiles.org), enough to check their syntax and
+//! to derive the molecular formula, with the implicit hydrogens of the organic subset.
+use crate::chemistry::formula::{element, Formula};
+use anyhow::{bail, Result};
+use std::{collections::HashMap, str::FromStr};
+
+/// Elements of the organic subset, written without brackets, and their normal valences.
+const ORGANIC_SUBSET: &[(&str, &[u32])] = &[
+    ("B", &[3]),
+    ("C", &[4]),
+    ("N", &[3, 5]),
+    ("O", &[2]),
+    ("P", &[3, 5]),
+    ("S", &[2, 4, 6]),
+    ("F", &[1]),
+    ("Cl", &[1]),
+    ("Br", &[1]),
+    ("I", &[1]),
+];
+
+/// Elements that can be written in lowercase as aromatic atoms.
+const AROMATIC: &[&str] = &["B", "C", "N", "O", "P", "S", "Se", "As", "Te"];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Bond {
+    Single,
+    Double,
+    Triple,
+    Quadruple,
+    Aromatic,
+}
+
+impl Bond {
+    /// The valence used by the bond, aromatic bonds are counted as single bonds.
+    fn order(self) -> u32 {
+        match self {
+            Bond::Single | Bond::Aromatic => 1,
+            Bond::Double => 2,
+            Bond::Triple => 3,
+            Bond::Quadruple => 4,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Atom {
+    /// `None` for the `*` wildcard.
+    element: Option<&'static str>,
+    aromatic: bool,
+    /// Hydrogens of a bracket atom, `None` for the organic subset (implicit hydrogens).
+    hydrogens: Option<u32>,
+    charge: i32,
+}
+
+/// The atoms and bonds of a SMILES string.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Molecule {
+    atoms: Vec<Atom>,
+    bonds: Vec<(usize, usize, Bond)>,
+}
+
+impl FromStr for Molecule {
+    type Err = anyhow::Error;
+
+    fn from_str(smiles: &str) -> Result<Self> {
+        let mut parser = Parser { chars: smiles.chars().collect(), position: 0 };
+        parser.parse().map_err(|error| anyhow::anyhow!("Invalid SMILES '{}': {}", smiles, error))
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn parse(&mut self) -> Result<Molecule> {
+        let mut molecule = Molecule::default();
+        let mut previous: Option<usize> = None;
+        let mut branches: Vec<Option<usize>> = Vec::new();
+        let mut bond: Option<Bond> = None;
+        let mut rings: HashMap<u32, (usize, Option<Bond>)> = HashMap::new();
+
+        // A SMILES ends at the first whitespace, which can be followed by a name.
+        while let Some(c) = self.peek().filter(|c| !c.is_whitespace()) {
+            match c {
+                '-' | '=' | '#' | '$' | ':' | '/' | '\\' => {
+                    if bond.is_some() {
+                        bail!("two bonds in a row at position {}", self.position + 1);
+                    }
+                    bond = Some(match c {
+                        '=' => Bond::Double,
+                        '#' => Bond::Triple,
+                        '$' => Bond::Quadruple,
+                        ':' => Bond::Aromatic,
+                        _ => Bond::Single,
+                    });
+                    self.position += 1;
+                }
+                '(' => {
+                    if previous.is_none() {
+                        bail!("branch without atom at position {}", self.position + 1);
+                    }
+                    branches.push(previous);
+                    self.position += 1;
+                }
+                ')' => {
+                    if bond.is_some() {
+                        bail!("bond without atom at position {}", self.position + 1);
+                    }
+                    previous = branches
+                        .pop()
+                        .ok_or_else(|| anyhow::anyhow!("unmatched ')' at {}", self.position + 1))?;
+                    self.position += 1;
+                }
+                '.' => {
+                    if bond.is_some() {
+                        bail!("bond without atom at position {}", self.position + 1);
+                    }
+                    previous = None;
+                    self.position += 1;
+                }
+                '0'..='9' | '%' => {
+                    let Some(atom) = previous else {
+                        bail!("ring closure without atom at position {}", self.position + 1);
+                    };
+                    let ring = self.ring_number()?;
+                    match rings.remove(&ring) {
+                        Some((other, other_bond)) => {
+                            if other == atom {
+                                bail!("ring {} closes on the atom that opens it", ring);
+                            }
+                            let bond = bond.take().or(other_bond).unwrap_or_else(|| {
+                                default_bond(&molecule.atoms[other], &molecule.atoms[atom])
+                            });
+                            molecule.bonds.push((other, atom, bond));
+                        }
+                        None => {
+                            rings.insert(ring, (atom, bond.take()));
+                        }
+                    }
+                }
+                _ => {
+                    let atom = self.atom()?;
+                    let index = molecule.atoms.len();
+                    if let Some(previous) = previous {
+                        let bond = bond
+                            .take()
+                            .unwrap_or_else(|| default_bond(&molecule.atoms[previous], &atom));
+                        molecule.bonds.push((previous, index, bond));
+                    } else if bond.is_some() {
+                        bail!("bond without atom before position {}", self.position);
+                    }
+                    molecule.atoms.push(atom);
+                    previous = Some(index);
+                }
+            }
+        }
+
+        if molecule.atoms.is_empty() {
+            bail!("no atoms");
+        }
+        if bond.is_some() {
+            bail!("bond without atom at the end");
+        }
+        if !branches.is_empty() {
+            bail!("unclosed branch");
+        }
+        if let Some(ring) = rings.keys().min() {
+            bail!("unclosed ring {}", ring);
+        }
+        Ok(molecule)
+    }
+
+    /// A ring closure: a digit, or `%` and two digits.
+    fn ring_number(&mut self) -> Result<u32> {
+        let length = if self.peek() == Some('%') { 3 } else { 1 };
+        let digits: String = self.chars.iter().skip(self.position).take(length).collect();
+        let number = digits.trim_start_matches('%');
+        if number.len() != length.min(2) || !number.chars().all(|c| c.is_ascii_digit()) {
+            bail!("invalid ring closure '{}' at position {}", digits, self.position + 1);
+        }
+        self.position += length;
+        Ok(number.parse()?)
+    }
+
+    fn atom(&mut self) -> Result<Atom> {
+        if self.peek() == Some('[') {
+            self.position += 1;
+            return self.bracket_atom();
+        }
+        if self.peek() == Some('*') {
+            self.position += 1;
+            return Ok(Atom { element: None, aromatic: false, hydrogens: None, charge: 0 });
+        }
+
+        let rest: String = self.chars.iter().skip(self.position).take(2).collect();
+        for (symbol, _) in ORGANIC_SUBSET.iter().rev() {
+            if rest.starts_with(symbol) {
+                self.position += symbol.len();
+                return Ok(Atom {
+                    element: element(symbol),
+                    aromatic: false,
+                    hydrogens: None,
+                    charge: 0,
+                });
+            }
+        }
+        if let Some(symbol) = ["b", "c", "n", "o", "p", "s"].iter().find(|s| rest.starts_with(**s))
+        {
+            self.position += 1;
+            return Ok(Atom {
+                element: element(&symbol.to_uppercase()),
+                aromatic: true,
+                hydrogens: None,
+                charge: 0,
+            });
+        }
+        bail!(
+            "unexpected character '{}' at position {}",
+            self.chars[self.position],
+            self.position + 1
+        )
+    }
+
+    /// An atom in brackets: `[isotope symbol chirality hydrogens charge class]`, e.g. `[13CH4]`,
+    /// `[C@@H]`, `[nH]` or `[Fe+2]`.
+    fn bracket_atom(&mut self) -> Result<Atom> {
+        let start = self.position;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.position += 1;
+        }
+
+        let (element, aromatic) = self.bracket_symbol()?;
+
+        // Chirality, e.g. `@`, `@@`, `@TH1` or `@OH12`.
+        if self.peek() == Some('@') {
+            self.position += 1;
+            if self.peek() == Some('@') {
+                self.position += 1;
+            }
+            while self.peek().is_some_and(|c| c.is_ascii_uppercase() && c != 'H')
+                || self.peek().is_some_and(|c| c.is_ascii_digit())
+            {
+                self.position += 1;
+            }
+        }
+
+        let mut hydrogens = 0;
+        if self.peek() == Some('H') {
+            self.position += 1;
+            hydrogens = self.number().unwrap_or(1);
+        }
+
+        let mut charge = 0;
+        if let Some(sign @ ('+' | '-')) = self.peek() {
+            let sign = if sign == '+' { 1 } else { -1 };
+            self.position += 1;
+            charge = sign * self.number().map(|n| n as i32).unwrap_or(1);
+            while self.peek() == Some(if sign == 1 { '+' } else { '-' }) {
+                self.position += 1;
+                charge += sign;
+            }
+        }
+
+        if self.peek() == Some(':') {
+            self.position += 1;
+            if self.number().is_none() {
+                bail!("missing atom class at position {}", self.position + 1);
+            }
+        }
+
+        if self.peek() != Some(']') {
+            let atom: String = self.chars[start..].iter().take_while(|c| **c != ']').collect();
+            bail!("invalid bracket atom '[{}]'", atom);
+        }
+        self.position += 1;
+        Ok(Atom { element, aromatic, hydrogens: Some(hydrogens), charge })
+    }
+
+    fn bracket_symbol(&mut self) -> Result<(Option<&'static str>, bool)> {
+        let rest: String = self.chars.iter().skip(self.position).take(2).collect();
+        if rest.starts_with('*') {
+            self.position += 1;
+            return Ok((None, false));
+        }
+        for length in [2, 1] {
+            let Some(symbol) = rest.get(..length) else {
+                continue;
+            };
+            if let Some(element) = element(symbol) {
+                self.position += length;
+                return Ok((Some(element), false));
+            }
+            let mut chars = symbol.chars();
+            let capitalized: String =
+                chars.next().map(|c| c.to_ascii_uppercase()).into_iter().chain(chars).collect();
+            if symbol.starts_with(|c: char| c.is_ascii_lowercase())
+                && AROMATIC.contains(&capitalized.as_str())
+            {
+                self.position += length;
+                return Ok((element(&capitalized), true));
+            }
+        }
+        bail!("unknown element at position {}", self.position + 1)
+    }
+
+    fn number(&mut self) -> Option<u32> {
+        let start = self.position;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.position += 1;
+        }
+        self.chars[start..self.position].iter().collect::<String>().parse().ok()
+    }
+}
+
+/// Bonds without symbol are aromatic between aromatic atoms, single otherwise.
+fn default_bond(first: &Atom, second: &Atom) -> Bond {
+    if first.aromatic && second.aromatic {
+        Bond::Aromatic
+    } else {
+        Bond::Single
+    }
+}
+
+impl Molecule {
+    /// The molecular formula, `None` if the molecule has wildcard atoms.
+    pub fn formula(&self) -> Option<Formula> {
+        let mut formula = Formula::default();
+        for (index, atom) in self.atoms.iter().enumerate() {
+            formula.add(atom.element?, 1);
+            formula.add_hydrogens(self.hydrogens(index) as i64);
+        }
+        Some(formula)
+    }
+
+    /// The total charge of the molecule.
+    pub fn charge(&self) -> i32 {
+        self.atoms.iter().map(|atom| atom.charge).sum()
+    }
+
+    /// The hydrogens of an atom: explicit for bracket atoms, implicit for the organic subset,
+    /// to reach the lowest normal valence that is not exceeded by the bonds.
+    fn hydrogens(&self, index: usize) -> u32 {
+        let atom = &self.atoms[index];
+        if let Some(hydrogens) = atom.hydrogens {
+            return hydrogens;
+        }
+        let Some((_, valences)) =
+            ORGANIC_SUBSET.iter().find(|(symbol, _)| Some(*symbol) == atom.element)
+        else {
+            return 0;
+        };
+        let bonds: u32 = self
+            .bonds
+            .iter()
+            .filter(|(first, second, _)| *first == index || *second == index)
+            .map(|(_, _, bond)| bond.order())
+            .sum();
+
+        if atom.aromatic {
+            // An aromatic atom also takes part in the pi system, unless it has no valence
+            // left for it (e.g. the oxygen of furan or a substituted pyrrole nitrogen).
+            return valences[0].saturating_sub(bonds + 1);
+        }
+        valences.iter().find(|valence| **valence >= bonds).map_or(0, |valence| valence - bonds)
+    }
+}
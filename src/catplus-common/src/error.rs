@@ -0,0 +1,421 @@
+//! Errors of the conversion of JSON input, located in the input so that they can be fixed.
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use serde_path_to_error::Segment;
+use std::{cmp::Reverse, collections::HashMap, fmt};
+
+/// Characters of the input line shown on each side of the error in [ConversionError::excerpt].
+const EXCERPT_WIDTH: usize = 40;
+
+/// Where an error occurred in a JSON document.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Location {
+    /// JSON Pointer of the value, e.g. `/Actions/3/hasSample/hasChemical/molecularMass/unit`.
+    pub pointer: String,
+    /// Line of the error, starting at 1, 0 if unknown.
+    pub line: usize,
+    /// Column of the error, starting at 1, 0 if unknown.
+    pub column: usize,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.pointer.as_str() {
+            "" => write!(f, "the document root"),
+            pointer => write!(f, "'{}'", pointer),
+        }?;
+        match self.line {
+            0 => Ok(()),
+            line => write!(f, " (line {}, column {})", line, self.column),
+        }
+    }
+}
+
+/// An item of the input left out by a lenient conversion (see [parse_json_lenient]).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SkippedItem {
+    /// JSON Pointer of the item in the input, e.g. `/Actions/3`.
+    pub pointer: String,
+    pub message: String,
+}
+
+impl fmt::Display for SkippedItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Skipped '{}': {}", self.pointer, self.message)
+    }
+}
+
+/// Why a conversion failed.
+#[derive(Debug)]
+pub enum ConversionError {
+    /// The input is not valid JSON.
+    Syntax { message: String, line: usize, column: usize },
+    /// The JSON does not have the expected shape, e.g. a missing field or a value of the wrong
+    /// type.
+    Schema { message: String, location: Location },
+    /// A unit that is not a valid expression or has no known QUDT IRI.
+    UnknownUnit { unit: String, message: String, location: Location },
+    /// An `actionName` that is not one of the supported actions.
+    UnknownAction { action: String, message: String, location: Location },
+    /// The data was read but its graph could not be built, e.g. invalid chemical identifiers.
+    Graph(anyhow::Error),
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConversionError::Syntax { message, line, column } => {
+                write!(f, "Invalid JSON at line {}, column {}: {}", line, column, message)
+            }
+            ConversionError::Schema { message, location } => {
+                write!(f, "Unexpected JSON at {}: {}", location, message)
+            }
+            ConversionError::UnknownUnit { message, location, .. } => {
+                write!(f, "{} at {}", message, location)
+            }
+            ConversionError::UnknownAction { action, message, location } => {
+                write!(f, "Unknown action '{}' at {}: {}", action, location, message)
+            }
+            ConversionError::Graph(error) => write!(f, "{:#}", error),
+        }
+    }
+}
+
+impl std::error::Error for ConversionError {}
+
+impl ConversionError {
+    /// The location of the error in the input, `None` for graph-building errors.
+    pub fn location(&self) -> Option<Location> {
+        match self {
+            ConversionError::Syntax { line, column, .. } => {
+                Some(Location { pointer: String::new(), line: *line, column: *column })
+            }
+            ConversionError::Schema { location, .. }
+            | ConversionError::UnknownUnit { location, .. }
+            | ConversionError::UnknownAction { location, .. } => Some(location.clone()),
+            ConversionError::Graph(_) => None,
+        }
+    }
+
+    /// The line of `source` where the error occurred, with a caret under its column, e.g.
+    ///
+    /// ```text
+    ///   12 |     "unit": "furlong"
+    ///      |                     ^
+    /// ```
+    ///
+    /// Long lines (e.g. of minified JSON) are cut around the column.
+    pub fn excerpt(&self, source: &str) -> Option<String> {
+        let location = self.location()?;
+        let line: Vec<char> = source.lines().nth(location.line.checked_sub(1)?)?.chars().collect();
+        let column = location.column.saturating_sub(1).min(line.len());
+        let start = column.saturating_sub(EXCERPT_WIDTH);
+        let end = (column + EXCERPT_WIDTH).min(line.len());
+        let text: String = line[start..end].iter().collect();
+        let number = location.line.to_string();
+        Some(format!(
+            "{} | {}\n{} | {}^",
+            number,
+            text,
+            " ".repeat(number.len()),
+            " ".repeat(column - start)
+        ))
+    }
+}
+
+/// Deserializes a JSON document, with errors located by their JSON Pointer and their line and
+/// column in `json`.
+pub fn parse_json<T: DeserializeOwned>(json: &str) -> Result<T, ConversionError> {
+    let deserializer = &mut serde_json::Deserializer::from_str(json);
+    serde_path_to_error::deserialize(deserializer)
+        .map_err(|error| json_error(error, || serde_json::from_str::<Value>(json).ok()))
+}
+
+/// Turns a `serde_json` error, located by `serde_path_to_error`, into a [ConversionError].
+/// `document` returns the parsed input if it is available, to name the unknown unit or action
+/// at the location of the error.
+pub fn json_error(
+    error: serde_path_to_error::Error<serde_json::Error>,
+    document: impl FnOnce() -> Option<Value>,
+) -> ConversionError {
+    let pointer = json_pointer(error.path());
+    let error = error.into_inner();
+    let (line, column) = (error.line(), error.column());
+    // The message of serde_json errors ends with their position, which is reported separately.
+    let message = error.to_string();
+    let message = message
+        .strip_suffix(&format!(" at line {} column {}", line, column))
+        .unwrap_or(&message)
+        .to_string();
+    if error.is_syntax() || error.is_eof() || error.is_io() {
+        return ConversionError::Syntax { message, line, column };
+    }
+
+    located_error(document().as_ref(), message, Location { pointer, line, column })
+}
+
+/// Classifies a deserialization error from the value at its location in `document`.
+fn located_error(document: Option<&Value>, message: String, location: Location) -> ConversionError {
+    let value = document
+        .and_then(|document| document.pointer(&location.pointer)?.as_str().map(str::to_string));
+    let field = location.pointer.rsplit('/').next().unwrap_or_default();
+    match (field, value) {
+        ("unit", Some(unit)) => ConversionError::UnknownUnit { unit, message, location },
+        ("actionName", Some(action)) => {
+            ConversionError::UnknownAction { action, message, location }
+        }
+        _ => ConversionError::Schema { message, location },
+    }
+}
+
+/// An array of records that [parse_json_lenient] leaves out one by one.
+pub struct RecordArray {
+    /// JSON Pointer of the arrays, `*` matching any index, e.g. `/Actions/*/hasWell`.
+    pub pointer: &'static str,
+    /// Deserializes a single record on its own.
+    pub check: fn(&Value) -> Result<(), serde_path_to_error::Error<serde_json::Error>>,
+}
+
+impl RecordArray {
+    /// The arrays at `pointer`, whose records are deserialized as `R`.
+    pub fn of<R: DeserializeOwned>(pointer: &'static str) -> Self {
+        RecordArray {
+            pointer,
+            check: |record| serde_path_to_error::deserialize::<_, R>(record).map(|_| ()),
+        }
+    }
+}
+
+/// Inputs that can be converted without the records that cannot be deserialized.
+pub trait Records: DeserializeOwned {
+    /// The arrays of records (e.g. actions, samples, measurement documents or peaks).
+    fn records() -> Vec<RecordArray>;
+}
+
+/// Deserializes a JSON document, leaving out the records that cannot be deserialized instead of
+/// failing: each element of the arrays of [Records::records] is deserialized on its own, inner
+/// arrays first, and the document is deserialized once without the failing ones.
+///
+/// Errors outside of any record still fail, without line and column since the document may have
+/// been modified.
+///
+/// # Returns
+/// The data and the skipped records, located in the original document.
+pub fn parse_json_lenient<T: Records>(
+    json: &str,
+) -> Result<(T, Vec<SkippedItem>), ConversionError> {
+    let Ok(mut document) = serde_json::from_str::<Value>(json) else {
+        return parse_json(json).map(|data| (data, Vec::new()));
+    };
+    let original = document.clone();
+    // Indices of the records removed from each array, by pointer of the array in the original.
+    let mut removed: HashMap<String, Vec<usize>> = HashMap::new();
+    let mut skipped = Vec::new();
+    let mut records = T::records();
+    // The arrays of a record are checked before it, while the indices are still the original ones.
+    records.sort_by_key(|records| Reverse(records.pointer.matches('/').count()));
+    for records in records {
+        for array in matching_arrays(&document, records.pointer) {
+            let Some(Value::Array(elements)) = document.pointer_mut(&array) else {
+                continue;
+            };
+            let mut index = 0;
+            elements.retain(|element| {
+                index += 1;
+                let Err(error) = (records.check)(element) else {
+                    return true;
+                };
+                removed.entry(array.clone()).or_default().push(index - 1);
+                skipped.push(SkippedItem {
+                    pointer: format!("{}/{}", array, index - 1),
+                    message: error.into_inner().to_string(),
+                });
+                false
+            });
+        }
+    }
+
+    serde_path_to_error::deserialize::<_, T>(&document).map(|data| (data, skipped)).map_err(
+        |error| {
+            let pointer = original_pointer(&document, &json_pointer(error.path()), &removed);
+            let message = error.into_inner().to_string();
+            located_error(Some(&original), message, Location { pointer, line: 0, column: 0 })
+        },
+    )
+}
+
+/// The pointers of the values of `document` matched by `pattern`, whose `*` segments match any
+/// index of an array.
+fn matching_arrays(document: &Value, pattern: &str) -> Vec<String> {
+    pattern.split('/').skip(1).fold(vec![String::new()], |pointers, segment| {
+        pointers
+            .into_iter()
+            .flat_map(|pointer| match (segment, document.pointer(&pointer)) {
+                ("*", Some(Value::Array(elements))) => {
+                    (0..elements.len()).map(|index| format!("{}/{}", pointer, index)).collect()
+                }
+                ("*", _) => Vec::new(),
+                (segment, _) => vec![format!("{}/{}", pointer, segment)],
+            })
+            .collect()
+    })
+}
+
+/// Translates a pointer of `document` to the original document, before the records in
+/// `removed` were removed from it.
+fn original_pointer(
+    document: &Value,
+    pointer: &str,
+    removed: &HashMap<String, Vec<usize>>,
+) -> String {
+    let (mut current, mut original) = (String::new(), String::new());
+    for segment in pointer.split('/').skip(1) {
+        let index = segment.parse::<usize>().ok();
+        match (document.pointer(&current), index) {
+            (Some(Value::Array(_)), Some(mut index)) => {
+                for removed in removed.get(&original).into_iter().flatten() {
+                    if *removed <= index {
+                        index += 1;
+                    }
+                }
+                original.push_str(&format!("/{}", index));
+            }
+            _ => original.push_str(&format!("/{}", segment)),
+        }
+        current.push_str(&format!("/{}", segment));
+    }
+    original
+}
+
+/// The JSON Pointer (RFC 6901) of a path, e.g. `/Actions/0/actionName`.
+fn json_pointer(path: &serde_path_to_error::Path) -> String {
+    path.iter()
+        .filter_map(|segment| match segment {
+            Segment::Seq { index } => Some(index.to_string()),
+            Segment::Map { key } => Some(key.replace('~', "~0").replace('/', "~1")),
+            Segment::Enum { variant } => Some(variant.clone()),
+            Segment::Unknown => None,
+        })
+        .map(|segment| format!("/{}", segment))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::synth::SynthBatch;
+
+    const BATCH: &str = r#"{
+        "batchID": "23",
+        "Actions": [
+            {
+                "actionName": "ACTION",
+                "speedShaker": { "value": 152, "unit": "UNIT" },
+                "startTime": "2024-07-25T12:03:31",
+                "endingTime": "2024-07-25T12:04:05",
+                "methodName": "set_temperature",
+                "equipmentName": "Chemspeed SWING XL",
+                "subEquipmentName": "heater"
+            }
+        ]
+    }"#;
+
+    fn parse(action: &str, unit: &str) -> Result<SynthBatch, ConversionError> {
+        parse_json(&BATCH.replace("ACTION", action).replace("UNIT", unit))
+    }
+
+    #[test]
+    fn test_located_errors() {
+        assert!(parse("setTemperatureAction", "rpm").is_ok());
+
+        let error = parse("setTemperatureAction", "furlong").unwrap_err();
+        let ConversionError::UnknownUnit { unit, location, .. } = &error else {
+            panic!("{:?}", error);
+        };
+        assert_eq!(unit, "furlong");
+        assert_eq!(location.pointer, "/Actions/0/speedShaker/unit");
+        assert_eq!(location.line, 6);
+        assert!(error.to_string().starts_with("Invalid unit 'furlong'"), "{}", error);
+
+        let error = parse("boilAction", "rpm").unwrap_err();
+        assert!(
+            matches!(&error, ConversionError::UnknownAction { action, .. } if action == "boilAction"),
+            "{:?}",
+            error
+        );
+
+        let json = BATCH.replace("ACTION", "setTemperatureAction").replace("\"UNIT\"", "12");
+        let error = parse_json::<SynthBatch>(&json).unwrap_err();
+        let ConversionError::Schema { location, .. } = &error else {
+            panic!("{:?}", error);
+        };
+        assert_eq!(location.pointer, "/Actions/0/speedShaker/unit");
+
+        let json = BATCH.replace("\"batchID\": \"23\",", "\"batchID\": \"23\"");
+        let error = parse_json::<SynthBatch>(&json).unwrap_err();
+        assert!(matches!(error, ConversionError::Syntax { line: 3, .. }), "{:?}", error);
+        assert_eq!(error.excerpt(&json).unwrap(), "3 |         \"Actions\": [\n  |         ^");
+    }
+
+    #[test]
+    fn test_parse_json_lenient() {
+        let action = |name: &str, unit: &str| {
+            format!(
+                r#"{{
+                    "actionName": "{}",
+                    "speedShaker": {{ "value": 152, "unit": "{}" }},
+                    "startTime": "2024-07-25T12:03:31",
+                    "endingTime": "2024-07-25T12:04:05",
+                    "methodName": "set_temperature",
+                    "equipmentName": "Chemspeed SWING XL",
+                    "subEquipmentName": "heater"
+                }}"#,
+                name, unit
+            )
+        };
+        let json = format!(
+            r#"{{ "batchID": "23", "Actions": [{}, {}, {}, {}] }}"#,
+            action("setTemperatureAction", "rpm"),
+            action("boilAction", "rpm"),
+            action("setTemperatureAction", "furlong"),
+            action("shakeAction", "rpm"),
+        );
+        assert!(parse_json::<SynthBatch>(&json).is_err());
+
+        let (batch, skipped) = parse_json_lenient::<SynthBatch>(&json).unwrap();
+        assert_eq!(batch.actions.unwrap().len(), 2);
+        let pointers: Vec<&str> = skipped.iter().map(|item| item.pointer.as_str()).collect();
+        assert_eq!(pointers, ["/Actions/1", "/Actions/2"]);
+        assert!(skipped[1].to_string().starts_with("Skipped '/Actions/2': Invalid unit 'furlong'"));
+
+        // A record left out of a kept record is located in the original document.
+        let well = |unit: &str| {
+            format!(
+                r#"{{ "position": "A1", "containerID": "1", "quantity": {{ "value": 1, "unit": "{}" }} }}"#,
+                unit
+            )
+        };
+        let wells = format!(r#""hasWell": [{}, {}, {}],"#, well("mg"), well("furlong"), well("mg"));
+        let nested = json.replacen(r#""speedShaker""#, &format!(r#"{} "speedShaker""#, wells), 4);
+        let (batch, skipped) = parse_json_lenient::<SynthBatch>(&nested).unwrap();
+        let actions = batch.actions.unwrap();
+        assert_eq!(actions[1].has_well.as_ref().unwrap().len(), 2);
+        let pointers: Vec<&str> = skipped.iter().map(|item| item.pointer.as_str()).collect();
+        assert_eq!(
+            pointers,
+            [
+                "/Actions/0/hasWell/1",
+                "/Actions/1/hasWell/1",
+                "/Actions/2/hasWell/1",
+                "/Actions/3/hasWell/1",
+                "/Actions/1",
+                "/Actions/2"
+            ]
+        );
+
+        // Errors outside of the records still fail, located in the original document.
+        let json = json.replace(r#""batchID": "23""#, r#""batchID": 23"#);
+        let error = parse_json_lenient::<SynthBatch>(&json).unwrap_err();
+        assert_eq!(error.location().unwrap().pointer, "/batchID");
+    }
+}
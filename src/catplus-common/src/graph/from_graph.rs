@@ -0,0 +1,183 @@
+use crate::graph::{insert_into::Flatten, namespaces::schema};
+use anyhow::{bail, Context, Result};
+use sophia::{api::ns::rdf, inmem::graph::LightGraph};
+use sophia_api::{
+    prelude::*,
+    term::{SimpleTerm, TermKind},
+};
+
+/// Derives [FromGraph] from the same `#[rdf(...)]` attributes as [InsertIntoGraph](super::insert_into::InsertIntoGraph).
+pub use catplus_derive::FromGraph;
+
+/// FromGraph provides a trait to rebuild a type from the triples inserted by
+/// [InsertIntoGraph](super::insert_into::InsertIntoGraph), i.e. the inverse conversion.
+///
+/// RDF graphs are unordered: the elements of a [Vec] are sorted by the `schema:position`
+/// of their nodes (see [insert_position](super::insert_into::insert_position)). Elements
+/// without a position come last, in the order in which their nodes appear in the graph.
+///
+/// Fields without an `#[rdf(...)]` mapping are not part of the graph, and are rebuilt
+/// with their [Default] value.
+pub trait FromGraph: Sized {
+    /// Rebuilds `Self` from the node `iri` of `graph`.
+    fn from_graph(graph: &LightGraph, iri: &SimpleTerm) -> Result<Self>;
+
+    /// Rebuilds `Self` from the node linked to `source` with `pred`
+    /// (from the node to `source` if `reverse` is set).
+    fn from_links(
+        graph: &LightGraph,
+        source: &SimpleTerm,
+        pred: &SimpleTerm,
+        reverse: bool,
+    ) -> Result<Self> {
+        match linked_nodes(graph, source, pred, reverse)?.as_slice() {
+            [node] => Self::from_graph(graph, node).with_context(|| {
+                format!("Failed to read {} of {}", display(pred), display(source))
+            }),
+            [] => bail!("Missing {} of {}", display(pred), display(source)),
+            _ => bail!("Expected a single {} of {}", display(pred), display(source)),
+        }
+    }
+
+    /// Rebuilds `Self` from the (unique) node of its type in `graph`.
+    fn from_root(_graph: &LightGraph) -> Result<Self> {
+        bail!("{} has no RDF type to look up in the graph", std::any::type_name::<Self>())
+    }
+}
+
+/// Rebuilds a `#[rdf(flatten)]` field from the node `iri` of the struct holding it.
+pub fn from_flattened<T: Flatten + FromGraph>(graph: &LightGraph, iri: &SimpleTerm) -> Result<T> {
+    T::from_graph(graph, iri)
+}
+
+/// Default implementation for [Option<T>]: `None` if there is no linked node.
+impl<T> FromGraph for Option<T>
+where
+    T: FromGraph,
+{
+    fn from_graph(graph: &LightGraph, iri: &SimpleTerm) -> Result<Self> {
+        T::from_graph(graph, iri).map(Some)
+    }
+
+    fn from_links(
+        graph: &LightGraph,
+        source: &SimpleTerm,
+        pred: &SimpleTerm,
+        reverse: bool,
+    ) -> Result<Self> {
+        if linked_nodes(graph, source, pred, reverse)?.is_empty() {
+            return Ok(None);
+        }
+        T::from_links(graph, source, pred, reverse).map(Some)
+    }
+}
+
+impl<T> FromGraph for Vec<T>
+where
+    T: FromGraph,
+{
+    fn from_graph(graph: &LightGraph, iri: &SimpleTerm) -> Result<Self> {
+        Ok(vec![T::from_graph(graph, iri)?])
+    }
+
+    fn from_links(
+        graph: &LightGraph,
+        source: &SimpleTerm,
+        pred: &SimpleTerm,
+        reverse: bool,
+    ) -> Result<Self> {
+        let mut nodes = linked_nodes(graph, source, pred, reverse)?;
+        nodes.sort_by_cached_key(|node| position(graph, node).unwrap_or(usize::MAX));
+        nodes
+            .iter()
+            .map(|node| {
+                T::from_graph(graph, node).with_context(|| {
+                    format!("Failed to read {} of {}", display(pred), display(source))
+                })
+            })
+            .collect()
+    }
+}
+
+impl FromGraph for String {
+    fn from_graph(_graph: &LightGraph, iri: &SimpleTerm) -> Result<Self> {
+        match iri.lexical_form() {
+            Some(value) => Ok(value.to_string()),
+            None => bail!("Expected a literal, found {}", display(iri)),
+        }
+    }
+}
+
+impl FromGraph for f64 {
+    fn from_graph(graph: &LightGraph, iri: &SimpleTerm) -> Result<Self> {
+        let value = String::from_graph(graph, iri)?;
+        value.parse().with_context(|| format!("Expected a number, found '{}'", value))
+    }
+}
+
+/// Returns the IRI of a node, used by types mapped to IRIs (e.g. units).
+pub fn iri_of(iri: &SimpleTerm) -> Result<String> {
+    match iri.iri() {
+        Some(value) => Ok(value.as_str().to_string()),
+        None => bail!("Expected an IRI, found {}", display(iri)),
+    }
+}
+
+/// Returns the unique node of type `rdf_type` in `graph`.
+pub fn root_node(graph: &LightGraph, rdf_type: &SimpleTerm) -> Result<SimpleTerm<'static>> {
+    let mut roots = Vec::new();
+    for triple in graph.triples_matching(Any, [rdf::type_], [rdf_type]) {
+        let [subject, _, _] = triple?;
+        roots.push(subject.into_term::<SimpleTerm<'static>>());
+    }
+    match roots.as_slice() {
+        [root] => Ok(root.clone()),
+        [] => bail!("No node of type {} in the graph", display(rdf_type)),
+        _ => bail!("Several nodes of type {} in the graph", display(rdf_type)),
+    }
+}
+
+/// Nodes linked to `source` with `pred`, in the order in which they appear in the graph.
+fn linked_nodes(
+    graph: &LightGraph,
+    source: &SimpleTerm,
+    pred: &SimpleTerm,
+    reverse: bool,
+) -> Result<Vec<SimpleTerm<'static>>> {
+    let mut nodes = Vec::new();
+    if reverse {
+        for triple in graph.triples_matching(Any, [pred], [source]) {
+            let [subject, _, _] = triple?;
+            nodes.push(subject.into_term());
+        }
+    } else {
+        for triple in graph.triples_matching([source], [pred], Any) {
+            let [_, _, object] = triple?;
+            nodes.push(object.into_term());
+        }
+    }
+    Ok(nodes)
+}
+
+/// Position of `node` in its array, the smallest one if the node is shared by several arrays.
+fn position(graph: &LightGraph, node: &SimpleTerm) -> Option<usize> {
+    graph
+        .triples_matching([node], [schema::position], Any)
+        .filter_map(|triple| triple.ok()?[2].lexical_form()?.parse().ok())
+        .min()
+}
+
+fn display(term: &SimpleTerm) -> String {
+    match term.kind() {
+        TermKind::Iri => {
+            format!("<{}>", term.iri().map(|i| i.as_str().to_string()).unwrap_or_default())
+        }
+        TermKind::Literal => {
+            format!("{:?}", term.lexical_form().map(|l| l.to_string()).unwrap_or_default())
+        }
+        TermKind::BlankNode => {
+            format!("_:{}", term.bnode_id().map(|b| b.as_str().to_string()).unwrap_or_default())
+        }
+        _ => format!("{:?}", term),
+    }
+}
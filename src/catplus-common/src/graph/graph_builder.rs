@@ -1,13 +1,46 @@
-use crate::rdf::rdf_serializers::{serialize_graph_to_jsonld, serialize_graph_to_turtle};
+use crate::{
+    chemistry::{
+        check::{check_chemical, ChemicalIssue},
+        inchi::Inchi,
+        inchikey::inchi_key,
+    },
+    graph::from_graph::FromGraph,
+    models::{
+        core::{Chemical, QuantityValue},
+        provenance::ConversionActivity,
+    },
+    rdf::rdf_serializers::{
+        serialize_dataset_to_nquads, serialize_dataset_to_trig, serialize_graph_to_jsonld,
+        serialize_graph_to_nquads, serialize_graph_to_ntriples, serialize_graph_to_rdfxml,
+        serialize_graph_to_trig, serialize_graph_to_turtle,
+    },
+    units::unit::Unit,
+};
 use anyhow::{Context, Result};
-use sophia::inmem::graph::LightGraph;
+use sophia::{
+    api::ns::rdf,
+    inmem::{dataset::LightDataset, graph::LightGraph},
+};
 use sophia_api::{prelude::*, term::SimpleTerm};
 
-use super::insert_into::InsertIntoGraph;
+use super::{
+    insert_into::InsertIntoGraph,
+    merging::{merge_chemical_nodes, ChemicalKey},
+    minting::{deterministic_iris, IriMinting},
+    namespaces::{allores, cat, obo, prov, purl, qudt},
+};
 
 /// An RDF Graph
 pub struct GraphBuilder {
     pub graph: LightGraph,
+    /// IRI of the named graph holding the triples in quad serializations (TriG, N-Quads),
+    /// the default graph is used if `None`.
+    pub graph_name: Option<String>,
+    /// Problems found while building the graph that do not prevent its conversion,
+    /// e.g. chemical identifiers that disagree (see [GraphBuilder::check_chemicals]).
+    pub warnings: Vec<String>,
+    /// Number of input items left out by a lenient conversion.
+    pub skipped_items: usize,
 }
 
 /// Builds an RDF graph of Synthesis data for the cat+ ontology.
@@ -19,7 +52,46 @@ pub struct GraphBuilder {
 /// * serialize_to_turtle: serializes the graph to a turtle output
 impl GraphBuilder {
     pub fn new() -> Self {
-        Self { graph: LightGraph::new() }
+        Self { graph: LightGraph::new(), graph_name: None, warnings: Vec::new(), skipped_items: 0 }
+    }
+
+    /// Puts the triples of the graph in the named graph `iri` when serialized as quads,
+    /// so that each conversion can be loaded into (and dropped from) a store separately.
+    pub fn set_graph_name(&mut self, iri: &str) -> Result<()> {
+        Iri::new(iri).with_context(|| format!("Invalid graph name '{}'", iri))?;
+        self.graph_name = Some(iri.to_string());
+        Ok(())
+    }
+
+    /// Returns the identifier of the batch described by the graph, if there is exactly one.
+    pub fn batch_id(&self) -> Result<Option<String>> {
+        let mut batch_ids = Vec::new();
+        for triple in self.graph.triples_matching(Any, [rdf::type_], [cat::Batch]) {
+            let [batch, _, _] = triple?;
+            for triple in self.graph.triples_matching([batch], [purl::identifier], Any) {
+                let [_, _, id] = triple?;
+                if let Some(id) = id.lexical_form() {
+                    batch_ids.push(id.to_string());
+                }
+            }
+        }
+        batch_ids.sort();
+        batch_ids.dedup();
+        Ok(match batch_ids.as_slice() {
+            [batch_id] => Some(batch_id.clone()),
+            _ => None,
+        })
+    }
+
+    /// Returns the graph as a dataset, in the named graph if one is set.
+    pub fn dataset(&self) -> Result<LightDataset> {
+        let graph_name = self.graph_name.as_deref().map(IriRef::new).transpose()?;
+        let mut dataset = LightDataset::new();
+        for triple in self.graph.triples() {
+            let [subject, predicate, object] = triple?;
+            dataset.insert(subject, predicate, object, graph_name.as_ref())?;
+        }
+        Ok(dataset)
     }
 
     /// Inserts a new object into the graph as a collection of triples.
@@ -29,10 +101,143 @@ impl GraphBuilder {
         Ok(())
     }
 
+    /// Inserts a `prov:Activity` describing the conversion, and links the top-level documents
+    /// of the graph (batches, campaigns and measurement documents) to it with `prov:wasGeneratedBy`.
+    pub fn insert_provenance(&mut self, activity: &ConversionActivity) -> Result<()> {
+        let documents = self.top_level_documents()?;
+
+        let activity_iri = activity.get_uri();
+        activity.insert_into(&mut self.graph, activity_iri.clone())?;
+        for document in documents {
+            self.graph.insert(document, prov::wasGeneratedBy, activity_iri.clone())?;
+        }
+
+        Ok(())
+    }
+
+    /// Records the number of input items left out by a lenient conversion on the top-level
+    /// documents of the graph, with `cat:skippedItems`.
+    pub fn insert_skipped_items(&mut self, count: usize) -> Result<()> {
+        self.skipped_items = count;
+        for document in self.top_level_documents()? {
+            self.graph.insert(document, cat::skippedItems, count as isize)?;
+        }
+        Ok(())
+    }
+
+    /// The batches, campaigns and measurement documents of the graph.
+    fn top_level_documents(&self) -> Result<Vec<SimpleTerm<'static>>> {
+        let mut documents = Vec::new();
+        for document_type in [cat::Batch, cat::Campaign, allores::AFR_0002375] {
+            for triple in self.graph.triples_matching(Any, [rdf::type_], [document_type]) {
+                let [document, _, _] = triple?;
+                documents.push(document.into_term::<SimpleTerm<'static>>());
+            }
+        }
+        Ok(documents)
+    }
+
+    /// Adds the value of each observation and measurement converted to the SI unit of its
+    /// quantity kind (e.g. °C to K, min to s), as a `qudt:QuantityValue` linked with
+    /// `cat:normalizedValue`. Values in units without an SI counterpart are left as is.
+    pub fn normalize_units(&mut self) -> Result<()> {
+        let mut normalized = Vec::new();
+        for node_type in [cat::Observation, cat::Measurement] {
+            for triple in self.graph.triples_matching(Any, [rdf::type_], [node_type]) {
+                let [node, _, _] = triple?;
+                let object = |predicate| {
+                    self.graph
+                        .triples_matching([node], [predicate], Any)
+                        .filter_map(Result::ok)
+                        .map(|[_, _, o]| o.clone())
+                        .next()
+                };
+                let value = object(qudt::value)
+                    .and_then(|value| value.lexical_form().and_then(|v| v.parse::<f64>().ok()));
+                let unit = object(qudt::unit).and_then(|unit| Unit::from_term(&unit));
+                if let Some(quantity) = value
+                    .zip(unit)
+                    .and_then(|(value, unit)| QuantityValue::normalized(value, &unit))
+                {
+                    normalized.push((node.into_term::<SimpleTerm<'static>>(), quantity));
+                }
+            }
+        }
+
+        for (node, quantity) in normalized {
+            let quantity_iri = quantity.get_uri();
+            quantity.insert_into(&mut self.graph, quantity_iri.clone())?;
+            self.graph.insert(node, cat::normalizedValue, quantity_iri)?;
+        }
+
+        Ok(())
+    }
+
+    /// Checks the identifiers of each chemical of the graph (see [check_chemical]).
+    pub fn check_chemicals(&self) -> Result<Vec<ChemicalIssue>> {
+        let mut issues = Vec::new();
+        for triple in self.graph.triples_matching(Any, [rdf::type_], [obo::CHEBI_25367]) {
+            let [node, _, _] = triple?;
+            let chemical = Chemical::from_graph(&self.graph, node)?;
+            issues.extend(check_chemical(&chemical));
+        }
+        Ok(issues)
+    }
+
+    /// Adds the InChIKey computed from the InChI of each chemical of the graph, with
+    /// `cat:inchiKey`, to deduplicate chemicals and join them with external registries (e.g.
+    /// PubChem or ChEBI). Chemicals without a valid InChI are left as is.
+    pub fn insert_inchi_keys(&mut self) -> Result<()> {
+        let mut keys = Vec::new();
+        for triple in self.graph.triples_matching(Any, [rdf::type_], [obo::CHEBI_25367]) {
+            let [node, _, _] = triple?;
+            for triple in self.graph.triples_matching([node], [allores::AFR_0002296], Any) {
+                let [_, _, inchi] = triple?;
+                if let Some(inchi) = inchi.lexical_form().and_then(|i| i.parse::<Inchi>().ok()) {
+                    keys.push((node.into_term::<SimpleTerm<'static>>(), inchi_key(&inchi)));
+                }
+            }
+        }
+
+        for (node, key) in keys {
+            self.graph.insert(node, cat::inchiKey, key.as_str())?;
+        }
+
+        Ok(())
+    }
+
+    /// Merges the chemicals that share the same `key` into a single node with an IRI under
+    /// `prefix`, also used for that chemical in other graphs (see [merge_chemical_nodes]).
+    /// Chemicals merged with different descriptions are reported as warnings.
+    pub fn merge_chemicals(&mut self, key: ChemicalKey, prefix: &str) -> Result<()> {
+        let (graph, warnings) = merge_chemical_nodes(&self.graph, key, prefix)?;
+        self.graph = graph;
+        self.warnings.extend(warnings);
+        Ok(())
+    }
+
     /// Materializes blank nodes in the graph by replacing them with URIs.
     /// If a prefix is given, it will be used for all materialized blank nodes.
     /// Otherwise, the empty string is used as the prefix.
     pub fn materialize_blank_nodes(&mut self, prefix: Option<&str>) -> Result<()> {
+        let prefix = prefix.unwrap_or_default();
+        self.replace_blank_nodes(|id| format!("{}{}", prefix, id))
+    }
+
+    /// Replaces all blank nodes in the graph with IRIs under `prefix`, minted with the given strategy.
+    pub fn mint_iris(&mut self, prefix: &str, minting: IriMinting) -> Result<()> {
+        match minting {
+            IriMinting::Random => self.materialize_blank_nodes(Some(prefix)),
+            IriMinting::Deterministic => {
+                let iris = deterministic_iris(&self.graph, prefix)
+                    .context("Failed to compute deterministic IRIs")?;
+                self.replace_blank_nodes(|id| iris[id].clone())
+            }
+        }
+    }
+
+    /// Replaces each blank node with the IRI returned by `iri_of` for its identifier.
+    fn replace_blank_nodes(&mut self, iri_of: impl Fn(&str) -> String) -> Result<()> {
         let mut materialized_graph = LightGraph::new();
 
         for triple in self.graph.triples_matching(Any, Any, Any) {
@@ -40,10 +245,7 @@ impl GraphBuilder {
 
             // If the subject is a blank node, replace it with a URI
             let new_subject = match subject {
-                SimpleTerm::BlankNode(s) => {
-                    let new_iri = format!("{}{}", prefix.unwrap_or_default(), s.as_str());
-                    IriRef::new(new_iri.to_owned())
-                }
+                SimpleTerm::BlankNode(s) => IriRef::new(iri_of(s.as_str())),
                 SimpleTerm::Iri(s) => IriRef::new(s.as_str().to_owned()),
                 _ => panic!("Unexpected subject type"),
             }?;
@@ -52,7 +254,7 @@ impl GraphBuilder {
             // In any other case, we just clone it.
             match object {
                 SimpleTerm::BlankNode(o) => {
-                    let new_o = format!("{}{}", prefix.unwrap_or_default(), o.as_str());
+                    let new_o = iri_of(o.as_str());
                     materialized_graph.insert(
                         new_subject,
                         predicate.clone(),
@@ -90,4 +292,32 @@ impl GraphBuilder {
     pub fn serialize_to_jsonld(&self) -> Result<String> {
         serialize_graph_to_jsonld(&self.graph).context("Failed to serialize graph to JSON-LD")
     }
+
+    /// Get the N-Triples serialization of the RDF graph
+    pub fn serialize_to_ntriples(&self) -> Result<String> {
+        serialize_graph_to_ntriples(&self.graph).context("Failed to serialize graph to N-Triples")
+    }
+
+    /// Get the N-Quads serialization of the RDF graph, in the named graph if one is set
+    pub fn serialize_to_nquads(&self) -> Result<String> {
+        match self.graph_name {
+            Some(_) => serialize_dataset_to_nquads(&self.dataset()?),
+            None => serialize_graph_to_nquads(&self.graph),
+        }
+        .context("Failed to serialize graph to N-Quads")
+    }
+
+    /// Get the TriG serialization of the RDF graph, in the named graph if one is set
+    pub fn serialize_to_trig(&self) -> Result<String> {
+        match self.graph_name {
+            Some(_) => serialize_dataset_to_trig(&self.dataset()?),
+            None => serialize_graph_to_trig(&self.graph),
+        }
+        .context("Failed to serialize graph to TriG")
+    }
+
+    /// Get the RDF/XML serialization of the RDF graph
+    pub fn serialize_to_rdfxml(&self) -> Result<String> {
+        serialize_graph_to_rdfxml(&self.graph).context("Failed to serialize graph to RDF/XML")
+    }
 }
@@ -1,10 +1,28 @@
 use sophia::inmem::graph::LightGraph;
 use sophia_api::{
     graph::MutableGraph,
+    ns::NsTerm,
     term::{SimpleTerm, Term},
 };
 
-use crate::graph::utils::generate_bnode_term;
+use crate::graph::{namespaces::schema, utils::generate_bnode_term};
+use std::fmt;
+
+/// Derives [InsertIntoGraph] from `#[rdf(...)]` attributes, see [catplus_derive].
+///
+/// Only derived structs can be flattened into the node (see [Flatten]), literals are attached
+/// with a predicate:
+///
+/// ```compile_fail
+/// use catplus_common::graph::insert_into::InsertIntoGraph;
+///
+/// #[derive(InsertIntoGraph)]
+/// struct Plate {
+///     #[rdf(flatten)]
+///     container_id: String,
+/// }
+/// ```
+pub use catplus_derive::InsertIntoGraph;
 
 /// Used in [InsertIntoGraph::attach_and_insert].
 #[derive(Clone)]
@@ -29,9 +47,44 @@ pub trait InsertIntoGraph {
         self.insert_into(graph, iri)
     }
 
+    /// Same as [InsertIntoGraph::attach_into], but the additional triple goes from `&self`
+    /// to the existing node.
+    fn attach_reverse_into(&self, graph: &mut LightGraph, attach: Link) -> anyhow::Result<()> {
+        let iri = attach.target_iri.unwrap_or_else(|| self.get_uri());
+        _ = graph.insert(&iri, &attach.pred, &attach.source_iri);
+
+        self.insert_into(graph, iri)
+    }
+
     fn get_uri(&self) -> SimpleTerm<'static> {
         generate_bnode_term()
     }
+
+    /// Whether `&self` is attached as a node of its own, rather than as a value
+    /// (a literal or an external IRI such as a unit).
+    fn is_node(&self) -> bool {
+        true
+    }
+}
+
+/// Types whose triples can be inserted on the node of the struct holding them, with
+/// `#[rdf(flatten)]`. Implemented by `#[derive(InsertIntoGraph)]`: literals (strings,
+/// numbers, units...) have no triples of their own and cannot be flattened.
+pub trait Flatten: InsertIntoGraph {}
+
+/// Inserts a `#[rdf(flatten)]` field on the node `iri` of the struct holding it.
+pub fn insert_flattened<T: Flatten>(
+    value: &T,
+    graph: &mut LightGraph,
+    iri: SimpleTerm,
+) -> anyhow::Result<()> {
+    value.insert_into(graph, iri)
+}
+
+/// Records the position of the node `iri` in its array with `schema:position`,
+/// which [FromGraph](super::from_graph::FromGraph) uses to restore the order of the elements.
+pub fn insert_position(graph: &mut LightGraph, iri: &SimpleTerm, index: usize) {
+    _ = graph.insert(iri, schema::position, index);
 }
 
 /// Default implementation for [Option<T>].
@@ -52,8 +105,21 @@ where
         }
         Ok(())
     }
+
+    fn attach_reverse_into(&self, graph: &mut LightGraph, attach: Link) -> anyhow::Result<()> {
+        if let Some(v) = self {
+            v.attach_reverse_into(graph, attach)?
+        }
+        Ok(())
+    }
+
+    fn is_node(&self) -> bool {
+        self.as_ref().is_none_or(T::is_node)
+    }
 }
 
+/// Implementation for [Vec<T>]: each element is attached in turn, and element nodes
+/// are given their index in the array (see [insert_position]).
 impl<T> InsertIntoGraph for Vec<T>
 where
     T: InsertIntoGraph,
@@ -66,17 +132,42 @@ where
     }
 
     fn attach_into(&self, graph: &mut LightGraph, attach: Link) -> anyhow::Result<()> {
-        for item in self {
-            item.attach_into(graph, attach.clone())?;
+        for (index, item) in self.iter().enumerate() {
+            let attach = positioned(graph, &attach, item, index);
+            item.attach_into(graph, attach)?;
         }
         Ok(())
     }
+
+    fn attach_reverse_into(&self, graph: &mut LightGraph, attach: Link) -> anyhow::Result<()> {
+        for (index, item) in self.iter().enumerate() {
+            let attach = positioned(graph, &attach, item, index);
+            item.attach_reverse_into(graph, attach)?;
+        }
+        Ok(())
+    }
+}
+
+/// The link of the `index`-th element of an array, whose node is minted here to record its
+/// position.
+fn positioned<'a, 'b>(
+    graph: &mut LightGraph,
+    attach: &Link<'a, 'b, '_>,
+    item: &impl InsertIntoGraph,
+    index: usize,
+) -> Link<'a, 'b, 'static> {
+    if !item.is_node() {
+        return Link { target_iri: None, ..attach.clone() };
+    }
+    let iri = item.get_uri();
+    insert_position(graph, &iri, index);
+    Link { target_iri: Some(iri), ..attach.clone() }
 }
 
 /// Default implementation for [SimpleTerm].
 impl<'a> InsertIntoGraph for SimpleTerm<'a> {
     fn insert_into(&self, _graph: &mut LightGraph, _iri: SimpleTerm) -> anyhow::Result<()> {
-        unimplemented!("cannot insert {:?} into graph, use `attach_and_insert`", &self)
+        Err(literal_error(self))
     }
 
     fn attach_into(&self, graph: &mut LightGraph, attach: Link) -> anyhow::Result<()> {
@@ -86,4 +177,98 @@ impl<'a> InsertIntoGraph for SimpleTerm<'a> {
 
         Ok(())
     }
+
+    fn is_node(&self) -> bool {
+        false
+    }
+}
+
+/// Error of [InsertIntoGraph::insert_into] for literals, which have no node of their own and
+/// can only be attached to one.
+pub(crate) fn literal_error(value: &dyn fmt::Debug) -> anyhow::Error {
+    anyhow::anyhow!(
+        "Cannot insert the literal {:?} as a node, it must be attached with a predicate",
+        value
+    )
+}
+
+/// Implementation for plain literals: [String] and [f64] are attached as literal values.
+macro_rules! impl_insert_literal {
+    ($($t:ty),*) => {
+        $(
+            impl InsertIntoGraph for $t {
+                fn insert_into(&self, _graph: &mut LightGraph, _iri: SimpleTerm) -> anyhow::Result<()> {
+                    Err(literal_error(self))
+                }
+
+                fn attach_into(&self, graph: &mut LightGraph, attach: Link) -> anyhow::Result<()> {
+                    self.as_simple().attach_into(graph, attach)
+                }
+
+                fn is_node(&self) -> bool {
+                    false
+                }
+            }
+        )*
+    };
+}
+
+impl_insert_literal!(String, f64);
+
+/// A string attached as a literal with an explicit datatype,
+/// e.g. `xsd:dateTime` (see `#[rdf(datatype = ...)]`).
+pub struct TypedLiteral<'a, T> {
+    pub value: &'a T,
+    pub datatype: NsTerm<'static>,
+}
+
+impl InsertIntoGraph for TypedLiteral<'_, String> {
+    fn insert_into(&self, _graph: &mut LightGraph, _iri: SimpleTerm) -> anyhow::Result<()> {
+        Err(literal_error(self.value))
+    }
+
+    fn attach_into(&self, graph: &mut LightGraph, attach: Link) -> anyhow::Result<()> {
+        (self.value.as_str() * self.datatype).as_simple().attach_into(graph, attach)
+    }
+
+    fn is_node(&self) -> bool {
+        false
+    }
+}
+
+impl InsertIntoGraph for TypedLiteral<'_, Option<String>> {
+    fn insert_into(&self, _graph: &mut LightGraph, _iri: SimpleTerm) -> anyhow::Result<()> {
+        Err(literal_error(self.value))
+    }
+
+    fn attach_into(&self, graph: &mut LightGraph, attach: Link) -> anyhow::Result<()> {
+        match self.value {
+            Some(value) => {
+                TypedLiteral { value, datatype: self.datatype }.attach_into(graph, attach)
+            }
+            None => Ok(()),
+        }
+    }
+
+    fn is_node(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::units::unit::Unit;
+    use sophia_api::graph::Graph;
+
+    #[test]
+    fn test_literals_are_not_nodes() {
+        let mut graph = LightGraph::new();
+        let iri = generate_bnode_term();
+        let error = "23".to_string().insert_into(&mut graph, iri.clone()).unwrap_err();
+        assert!(error.to_string().contains("\"23\""), "{}", error);
+        assert!(1.5.insert_into(&mut graph, iri.clone()).is_err());
+        assert!(Unit::parse("mg").unwrap().insert_into(&mut graph, iri).is_err());
+        assert_eq!(graph.triples().count(), 0);
+    }
 }
@@ -0,0 +1,268 @@
+use crate::graph::{
+    minting::encode_segment,
+    namespaces::{cat, obo, purl, schema},
+};
+use serde::{Deserialize, Serialize};
+use sophia::{api::ns::rdf, inmem::graph::LightGraph};
+use sophia_api::{
+    ns::NsTerm,
+    prelude::*,
+    term::{SimpleTerm, TermKind},
+};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+/// Value identifying the chemicals that describe the same compound.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+pub enum ChemicalKey {
+    /// The `chemicalID` of the chemical.
+    #[default]
+    Id,
+    /// The InChI of the chemical, compared through its InChIKey (`cat:inchiKey`).
+    Inchi,
+}
+
+impl ChemicalKey {
+    fn predicate(self) -> NsTerm<'static> {
+        match self {
+            ChemicalKey::Id => purl::identifier,
+            ChemicalKey::Inchi => cat::inchiKey,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            ChemicalKey::Id => "chemicalID",
+            ChemicalKey::Inchi => "InChIKey",
+        }
+    }
+
+    /// Path of the IRIs of the merged chemicals, under the resource prefix.
+    fn path(self) -> &'static str {
+        match self {
+            ChemicalKey::Id => "chemical",
+            ChemicalKey::Inchi => "chemical/inchikey",
+        }
+    }
+}
+
+type Node = SimpleTerm<'static>;
+
+/// Literal values describing a node, with the path of predicates leading to them.
+pub type Description = BTreeSet<(String, String)>;
+
+/// Merges the chemicals of `graph` that share the same `key` into a single node, named
+/// `<prefix>chemical/<chemicalID>` or `<prefix>chemical/inchikey/<InChIKey>`, so that the same
+/// compound gets the same IRI in every graph converted with the same prefix.
+///
+/// The description of the first chemical is kept, those of the others (and the blank nodes
+/// only they refer to, e.g. their molecular mass) are dropped and their references redirected
+/// to the merged node. The blank nodes only the merged node refers to are named after it and
+/// their predicate, e.g. `<prefix>chemical/134/AFR_0002294` for its molecular mass, so that
+/// graphs describing the same chemical can be loaded together without repeating its description.
+/// Chemicals without a key, or with several values for it, are left as is.
+///
+/// # Returns
+/// The merged graph, and a warning for each key whose chemicals have different descriptions.
+pub fn merge_chemical_nodes(
+    graph: &LightGraph,
+    key: ChemicalKey,
+    prefix: &str,
+) -> anyhow::Result<(LightGraph, Vec<String>)> {
+    let mut merged: HashMap<Node, Node> = HashMap::new();
+    let mut kept: HashMap<String, Node> = HashMap::new();
+    let mut duplicates: Vec<(String, Node)> = Vec::new();
+    for triple in graph.triples_matching(Any, [rdf::type_], [obo::CHEBI_25367]) {
+        let [chemical, _, _] = triple?;
+        let chemical: Node = chemical.into_term();
+        let mut values = BTreeSet::new();
+        for triple in graph.triples_matching([&chemical], [key.predicate()], Any) {
+            let [_, _, value] = triple?;
+            if let Some(value) = value.lexical_form() {
+                values.insert(value.to_string());
+            }
+        }
+        let mut values = values.into_iter();
+        let (Some(value), None) = (values.next(), values.next()) else {
+            continue;
+        };
+
+        let iri = format!("{}{}/{}", prefix, key.path(), encode_segment(&value));
+        merged.insert(chemical.clone(), IriRef::new_unchecked(iri).into_term());
+        match kept.get(&value) {
+            Some(_) => duplicates.push((value, chemical)),
+            None => {
+                kept.insert(value, chemical);
+            }
+        }
+    }
+
+    let mut conflicts = BTreeSet::new();
+    let mut dropped: HashSet<Node> = HashSet::new();
+    for (value, duplicate) in &duplicates {
+        if description(graph, duplicate)? != description(graph, &kept[value])? {
+            conflicts.insert(value);
+        }
+        collect_dropped(graph, duplicate, &mut dropped)?;
+    }
+    let warnings = conflicts
+        .into_iter()
+        .map(|value| {
+            format!(
+                "Chemicals with {} '{}' have different descriptions, only the first one is kept",
+                key.name(),
+                value
+            )
+        })
+        .collect();
+
+    for chemical in kept.values() {
+        let iri = merged[chemical].iri().expect("Merged chemicals are IRIs").as_str().to_string();
+        name_children(graph, chemical, &iri, &mut merged)?;
+    }
+
+    let mut merged_graph = LightGraph::new();
+    for triple in graph.triples() {
+        let [subject, predicate, object] = triple?;
+        let (subject, object): (Node, Node) = (subject.into_term(), object.into_term());
+        if dropped.contains(&subject) {
+            continue;
+        }
+        merged_graph.insert(
+            merged.get(&subject).unwrap_or(&subject),
+            predicate,
+            merged.get(&object).unwrap_or(&object),
+        )?;
+    }
+
+    Ok((merged_graph, warnings))
+}
+
+/// The literal values describing `node` and the blank nodes only it refers to, with the path
+/// of predicates leading to them, except positions in arrays.
+fn description(graph: &LightGraph, node: &Node) -> anyhow::Result<Description> {
+    let mut description = Description::new();
+    describe(graph, node, "", &mut |child| child.kind() == TermKind::BlankNode, &mut description)?;
+    Ok(description)
+}
+
+/// Adds the literal values of `node` to `description`, under `path`, and those of the nodes
+/// only it refers to for which `owned` holds.
+fn describe(
+    graph: &LightGraph,
+    node: &Node,
+    path: &str,
+    owned: &mut impl FnMut(&Node) -> bool,
+    description: &mut Description,
+) -> anyhow::Result<()> {
+    for triple in graph.triples_matching([node], Any, Any) {
+        let [_, predicate, object] = triple?;
+        let Some(predicate) = predicate.iri().filter(|p| !Term::eq(p, schema::position)) else {
+            continue;
+        };
+        let path = format!("{}/{}", path, predicate.as_str());
+        let object: Node = object.into_term();
+        match object.lexical_form().map(|value| value.to_string()) {
+            Some(value) => {
+                description.insert((path, value));
+            }
+            None if owned(&object) && graph.triples_matching(Any, Any, [&object]).count() == 1 => {
+                describe(graph, &object, &path, owned, description)?;
+            }
+            None => {}
+        }
+    }
+    Ok(())
+}
+
+/// Names the blank nodes that only `node` refers to after `iri` and their predicate, e.g.
+/// `<iri>/AFR_0002294`, numbered if several share the same predicate, and recursively
+/// their own blank nodes.
+fn name_children(
+    graph: &LightGraph,
+    node: &Node,
+    iri: &str,
+    names: &mut HashMap<Node, Node>,
+) -> anyhow::Result<()> {
+    let mut children: BTreeMap<String, Vec<(Description, Node)>> = BTreeMap::new();
+    for triple in graph.triples_matching([node], Any, Any) {
+        let [_, predicate, object] = triple?;
+        let object: Node = object.into_term();
+        if object.kind() != TermKind::BlankNode
+            || names.contains_key(&object)
+            || graph.triples_matching(Any, Any, [&object]).count() != 1
+        {
+            continue;
+        }
+        let name = predicate.iri().map_or(String::new(), |predicate| {
+            predicate.as_str().rsplit(['#', '/']).next().unwrap_or_default().to_string()
+        });
+        let description = description(graph, &object)?;
+        children.entry(encode_segment(&name)).or_default().push((description, object));
+    }
+
+    for (name, mut nodes) in children {
+        // Numbered in the order of their descriptions, which does not depend on the input.
+        nodes.sort_by(|a, b| a.0.cmp(&b.0));
+        let numbered = nodes.len() > 1;
+        for (index, (_, child)) in nodes.into_iter().enumerate() {
+            let child_iri = match numbered {
+                true => format!("{}/{}-{}", iri, name, index + 1),
+                false => format!("{}/{}", iri, name),
+            };
+            names.insert(child.clone(), IriRef::new_unchecked(child_iri.clone()).into_term());
+            name_children(graph, &child, &child_iri, names)?;
+        }
+    }
+    Ok(())
+}
+
+/// The description of each merged chemical of `graph` (see [merge_chemical_nodes]), by IRI:
+/// its literal values and those of the nodes named after it, with the path of predicates
+/// leading to them, to compare the descriptions of a chemical merged in different graphs.
+pub fn merged_chemical_descriptions(
+    graph: &LightGraph,
+    key: ChemicalKey,
+    prefix: &str,
+) -> anyhow::Result<BTreeMap<String, Description>> {
+    let chemicals_prefix = format!("{}{}/", prefix, key.path());
+    let mut descriptions = BTreeMap::new();
+    for triple in graph.triples_matching(Any, [rdf::type_], [obo::CHEBI_25367]) {
+        let [chemical, _, _] = triple?;
+        let Some(iri) = chemical.iri().map(|iri| iri.as_str().to_string()) else {
+            continue;
+        };
+        if !iri.starts_with(&chemicals_prefix) {
+            continue;
+        }
+        let children = format!("{}/", iri);
+        let mut owned = |node: &Node| node.iri().is_some_and(|i| i.as_str().starts_with(&children));
+        let mut description = Description::new();
+        describe(graph, &chemical.into_term(), "", &mut owned, &mut description)?;
+        descriptions.insert(iri, description);
+    }
+    Ok(descriptions)
+}
+
+/// Adds `node` and the blank nodes reachable from it to `dropped`, except those that are also
+/// referred to from elsewhere.
+fn collect_dropped(
+    graph: &LightGraph,
+    node: &Node,
+    dropped: &mut HashSet<Node>,
+) -> anyhow::Result<()> {
+    if !dropped.insert(node.clone()) {
+        return Ok(());
+    }
+    for triple in graph.triples_matching([node], Any, Any) {
+        let [_, _, object] = triple?;
+        if object.kind() != TermKind::BlankNode {
+            continue;
+        }
+        let object: Node = object.into_term();
+        let parents = graph.triples_matching(Any, Any, [&object]).count();
+        if parents == 1 {
+            collect_dropped(graph, &object, dropped)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,293 @@
+use crate::graph::namespaces::{allores, cat, obo, purl, schema};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sophia::{api::ns::rdf, inmem::graph::LightGraph};
+use sophia_api::{
+    ns::NsTerm,
+    prelude::*,
+    term::{SimpleTerm, TermKind},
+};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Strategy used to turn blank nodes into IRIs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+pub enum IriMinting {
+    /// Reuse the random identifier of each blank node.
+    #[default]
+    Random,
+    /// Derive IRIs from natural keys (e.g. `batchID`) or from the content of the node,
+    /// so that converting the same input twice yields the same IRIs.
+    Deterministic,
+}
+
+type Node = SimpleTerm<'static>;
+
+/// Natural keys, by node type: the key is built from the values of the given predicate paths.
+fn natural_keys() -> Vec<(Node, &'static str, Vec<Vec<Node>>)> {
+    let term = |t: NsTerm<'static>| -> Node { t.into_term() };
+    vec![
+        (term(cat::Batch), "batch", vec![vec![term(purl::identifier)]]),
+        (term(cat::Campaign), "campaign", vec![vec![term(schema::name)]]),
+        (term(obo::CHEBI_25367), "chemical", vec![vec![term(purl::identifier)]]),
+        (term(cat::Plate), "plate", vec![vec![term(cat::containerID)]]),
+        (
+            term(cat::Well),
+            "well",
+            vec![
+                vec![term(cat::hasPlate), term(cat::containerID)],
+                vec![term(allores::AFR_0002240)],
+            ],
+        ),
+        (
+            term(allores::AFR_0002375),
+            "measurement",
+            vec![
+                vec![term(allores::AFR_0002529), term(allores::AFR_0002535)],
+                vec![term(allores::AFR_0001121)],
+            ],
+        ),
+    ]
+}
+
+/// Computes deterministic IRIs for all blank nodes of a graph.
+///
+/// Nodes with a natural key (see [natural_keys]) get a readable IRI such as
+/// `<prefix>batch/23`. All other nodes get an IRI derived from a hash of the IRI
+/// of their parent, the predicate linking them, and their own content. Identical siblings,
+/// e.g. two identical actions of a batch, are told apart with a counter suffix (`-2`, `-3`,
+/// ...): they cannot be distinguished, so the minted graph is the same whatever their order.
+pub struct DeterministicMinter {
+    prefix: String,
+    /// Outgoing edges of each blank node, as (predicate, object) pairs.
+    edges: HashMap<Node, Vec<(Node, Node)>>,
+    /// Incoming edges of each blank node, as (subject, predicate) pairs.
+    parents: HashMap<Node, Vec<(Node, Node)>>,
+    content_hashes: HashMap<Node, String>,
+    iris: HashMap<Node, String>,
+    /// Number of nodes minted with each hashed IRI.
+    hashed_iris: HashMap<String, usize>,
+}
+
+impl DeterministicMinter {
+    pub fn new(graph: &LightGraph, prefix: &str) -> anyhow::Result<Self> {
+        let mut edges: HashMap<Node, Vec<(Node, Node)>> = HashMap::new();
+        let mut parents: HashMap<Node, Vec<(Node, Node)>> = HashMap::new();
+
+        for triple in graph.triples() {
+            let [s, p, o] = triple?;
+            let (s, p, o): (Node, Node, Node) = (s.into_term(), p.into_term(), o.into_term());
+            if s.kind() == TermKind::BlankNode {
+                edges.entry(s.clone()).or_default().push((p.clone(), o.clone()));
+            }
+            if o.kind() == TermKind::BlankNode {
+                parents.entry(o).or_default().push((s, p));
+            }
+        }
+
+        Ok(DeterministicMinter {
+            prefix: prefix.to_string(),
+            edges,
+            parents,
+            content_hashes: HashMap::new(),
+            iris: HashMap::new(),
+            hashed_iris: HashMap::new(),
+        })
+    }
+
+    /// Returns the IRI of a blank node.
+    pub fn iri(&mut self, node: &Node) -> String {
+        self.mint(node, &mut HashSet::new())
+    }
+
+    fn mint(&mut self, node: &Node, visiting: &mut HashSet<Node>) -> String {
+        if node.kind() != TermKind::BlankNode {
+            return repr(node);
+        }
+        if let Some(iri) = self.iris.get(node) {
+            return iri.clone();
+        }
+
+        let content = self.content_hash(node, &mut HashSet::new());
+        let kind = self.type_name(node);
+
+        let iri = match self.natural_key(node) {
+            Some((name, key)) => format!("{}{}/{}", self.prefix, name, key),
+            None => {
+                // The parent is chosen independently of minting order: smallest predicate,
+                // then smallest parent content.
+                let mut candidates: Vec<(Node, Node)> =
+                    self.parents.get(node).cloned().unwrap_or_default();
+                candidates.retain(|(parent, _)| !visiting.contains(parent));
+                let parent = candidates
+                    .into_iter()
+                    .map(|(parent, pred)| {
+                        let parent_content = self.content_hash(&parent, &mut HashSet::new());
+                        (repr(&pred), parent_content, parent)
+                    })
+                    .min_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+                let seed = match parent {
+                    Some((pred, _, parent)) => {
+                        visiting.insert(node.clone());
+                        let parent_iri = self.mint(&parent, visiting);
+                        visiting.remove(node);
+                        format!("{} {} {}", parent_iri, pred, content)
+                    }
+                    None => content,
+                };
+                let iri = format!("{}{}/{}", self.prefix, kind, hash(&seed));
+                let count = self.hashed_iris.entry(iri.clone()).or_default();
+                *count += 1;
+                match *count {
+                    1 => iri,
+                    count => format!("{}-{}", iri, count),
+                }
+            }
+        };
+
+        self.iris.insert(node.clone(), iri.clone());
+        iri
+    }
+
+    /// Hash of the node and of all blank nodes reachable from it.
+    fn content_hash(&mut self, node: &Node, visiting: &mut HashSet<Node>) -> String {
+        if let Some(hash) = self.content_hashes.get(node) {
+            return hash.clone();
+        }
+        if !visiting.insert(node.clone()) {
+            return "_:cycle".to_string();
+        }
+
+        let mut lines: Vec<String> = self
+            .edges
+            .get(node)
+            .cloned()
+            .unwrap_or_default()
+            .iter()
+            .map(|(p, o)| {
+                let object = match o.kind() {
+                    TermKind::BlankNode => self.content_hash(o, visiting),
+                    _ => repr(o),
+                };
+                format!("{} {}", repr(p), object)
+            })
+            .collect();
+        lines.sort();
+
+        visiting.remove(node);
+        let content = hash(&lines.join("\n"));
+        self.content_hashes.insert(node.clone(), content.clone());
+        content
+    }
+
+    fn values(&self, node: &Node, path: &[Node]) -> Vec<Node> {
+        path.iter().fold(vec![node.clone()], |nodes, pred| {
+            nodes
+                .iter()
+                .flat_map(|n| self.edges.get(n).cloned().unwrap_or_default())
+                .filter(|(p, _)| p == pred)
+                .map(|(_, o)| o)
+                .collect()
+        })
+    }
+
+    fn types(&self, node: &Node) -> Vec<Node> {
+        self.values(node, &[rdf::type_.into_term()])
+    }
+
+    /// Lower-cased local name of the first type of the node, used as IRI segment.
+    fn type_name(&self, node: &Node) -> String {
+        let mut types: Vec<String> = self.types(node).iter().map(repr).collect();
+        types.sort();
+        types
+            .first()
+            .map(|t| {
+                t.trim_end_matches('>').rsplit(['#', '/']).next().unwrap_or_default().to_lowercase()
+            })
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| "resource".to_string())
+    }
+
+    fn natural_key(&self, node: &Node) -> Option<(&'static str, String)> {
+        let types = self.types(node);
+        for (class, name, paths) in natural_keys() {
+            if !types.contains(&class) {
+                continue;
+            }
+            let mut segments = Vec::new();
+            for path in &paths {
+                let mut values: Vec<String> =
+                    self.values(node, path).iter().filter_map(lexical).collect();
+                values.sort();
+                // Keys must be unambiguous, otherwise we fall back to content hashing.
+                if values.len() != 1 {
+                    return None;
+                }
+                segments.push(encode_segment(&values[0]));
+            }
+            return Some((name, segments.join("/")));
+        }
+        None
+    }
+}
+
+/// Canonical string representation of a term, used for hashing and sorting.
+fn repr(term: &Node) -> String {
+    match term.kind() {
+        TermKind::Iri => {
+            format!("<{}>", term.iri().map(|i| i.as_str().to_string()).unwrap_or_default())
+        }
+        TermKind::Literal => {
+            let datatype = term.datatype().map(|d| d.as_str().to_string()).unwrap_or_default();
+            let language = term.language_tag().map(|l| l.as_str().to_string()).unwrap_or_default();
+            format!("{:?}^^<{}>@{}", lexical(term).unwrap_or_default(), datatype, language)
+        }
+        _ => format!("{:?}", term),
+    }
+}
+
+fn lexical(term: &Node) -> Option<String> {
+    term.lexical_form().map(|l| l.to_string())
+}
+
+fn hash(content: &str) -> String {
+    let digest = Sha256::digest(content.as_bytes());
+    // 128 bits are more than enough to avoid collisions within a dataset.
+    format!("{:x}", digest)[..32].to_string()
+}
+
+/// Percent-encodes everything but unreserved characters, so that keys are valid IRI segments.
+pub(crate) fn encode_segment(value: &str) -> String {
+    let encoded: String = value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect();
+    match encoded.as_str() {
+        "" => "_".to_string(),
+        _ => encoded,
+    }
+}
+
+/// Maps all blank node identifiers of `graph` to deterministic IRIs.
+pub fn deterministic_iris(
+    graph: &LightGraph,
+    prefix: &str,
+) -> anyhow::Result<BTreeMap<String, String>> {
+    let mut minter = DeterministicMinter::new(graph, prefix)?;
+    let mut nodes: Vec<Node> = minter.edges.keys().chain(minter.parents.keys()).cloned().collect();
+    nodes.sort_by_key(repr);
+    nodes.dedup();
+
+    let mut iris = BTreeMap::new();
+    for node in nodes {
+        if let Some(id) = node.bnode_id() {
+            let iri = minter.iri(&node);
+            iris.insert(id.as_str().to_string(), iri);
+        }
+    }
+    Ok(iris)
+}
@@ -1,5 +1,8 @@
+pub mod from_graph;
 pub mod graph_builder;
 pub mod insert_into;
+pub mod merging;
+pub mod minting;
 pub mod namespaces;
 pub mod prefix_map;
 pub mod utils;
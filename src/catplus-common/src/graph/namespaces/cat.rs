@@ -37,11 +37,14 @@ namespace! {
     hasPlate,
     hasSample,
     hasWell,
+    inchiKey,
     InjectionDocument,
+    inputType,
     internalBarCode,
     measure,
     measuredQuantity,
     Measurement,
+    normalizedValue,
     Objective,
     Observation,
     optimizationType,
@@ -59,6 +62,7 @@ namespace! {
     SetTemperatureAction,
     SetVacuumAction,
     ShakeAction,
+    skippedItems,
     speedInRPM,
     speedTumbleStirrerShape,
     subEquipmentName,
@@ -69,7 +73,8 @@ namespace! {
     ThreeDimensionalUltravioletSpectrumDataCube,
     vacuum,
     vialShape,
-    Well
+    Well,
+    zarrPath
 }
 lazy_static! {
     pub static ref ns: Namespace<&'static str> = Namespace::new(PREFIX.as_str()).unwrap();
@@ -4,6 +4,7 @@ use sophia_api::namespace;
 namespace! {
     "http://qudt.org/schema/qudt/",
     quantity,
+    QuantityValue,
     unit,
     value
 }
@@ -4,8 +4,13 @@ use sophia_api::namespace;
 namespace! {
     "https://schema.org/",
     description,
+    encodingFormat,
     keywords,
-    name
+    name,
+    position,
+    sha256,
+    softwareVersion,
+    version
 }
 lazy_static! {
     pub static ref ns: Namespace<&'static str> = Namespace::new(PREFIX.as_str()).unwrap();
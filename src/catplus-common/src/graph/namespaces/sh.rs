@@ -0,0 +1,105 @@
+use lazy_static::lazy_static;
+use sophia::api::ns::Namespace;
+use sophia_api::namespace;
+// `in` is a reserved keyword in rust, therefore it is mapped to `in_`
+namespace! {
+    "http://www.w3.org/ns/shacl#",
+    alternativePath,
+    and,
+    BlankNode,
+    BlankNodeOrIRI,
+    BlankNodeOrLiteral,
+    class,
+    closed,
+    conforms,
+    datatype,
+    deactivated,
+    disjoint,
+    equals,
+    flags,
+    focusNode,
+    hasValue,
+    ignoredProperties,
+    Info,
+    inversePath,
+    IRI,
+    IRIOrLiteral,
+    languageIn,
+    lessThan,
+    lessThanOrEquals,
+    Literal,
+    maxCount,
+    maxExclusive,
+    maxInclusive,
+    maxLength,
+    message,
+    minCount,
+    minExclusive,
+    minInclusive,
+    minLength,
+    node,
+    nodeKind,
+    NodeShape,
+    not,
+    oneOrMorePath,
+    or,
+    path,
+    pattern,
+    property,
+    PropertyShape,
+    qualifiedMaxCount,
+    qualifiedMinCount,
+    qualifiedValueShape,
+    result,
+    resultMessage,
+    resultPath,
+    resultSeverity,
+    severity,
+    sourceConstraintComponent,
+    sourceShape,
+    targetClass,
+    targetNode,
+    targetObjectsOf,
+    targetSubjectsOf,
+    uniqueLang,
+    ValidationReport,
+    ValidationResult,
+    value,
+    Violation,
+    Warning,
+    xone,
+    zeroOrMorePath,
+    zeroOrOnePath,
+    AndConstraintComponent,
+    ClassConstraintComponent,
+    ClosedConstraintComponent,
+    DatatypeConstraintComponent,
+    DisjointConstraintComponent,
+    EqualsConstraintComponent,
+    HasValueConstraintComponent,
+    InConstraintComponent,
+    LanguageInConstraintComponent,
+    LessThanConstraintComponent,
+    LessThanOrEqualsConstraintComponent,
+    MaxCountConstraintComponent,
+    MaxExclusiveConstraintComponent,
+    MaxInclusiveConstraintComponent,
+    MaxLengthConstraintComponent,
+    MinCountConstraintComponent,
+    MinExclusiveConstraintComponent,
+    MinInclusiveConstraintComponent,
+    MinLengthConstraintComponent,
+    NodeConstraintComponent,
+    NodeKindConstraintComponent,
+    NotConstraintComponent,
+    OrConstraintComponent,
+    PatternConstraintComponent,
+    QualifiedMaxCountConstraintComponent,
+    QualifiedMinCountConstraintComponent,
+    UniqueLangConstraintComponent,
+    XoneConstraintComponent;
+    in_, "in"
+}
+lazy_static! {
+    pub static ref ns: Namespace<&'static str> = Namespace::new(PREFIX.as_str()).unwrap();
+}
@@ -1,6 +1,6 @@
 use crate::graph::namespaces::{
-    allocom, allodc, allohdf, allohdfcube, alloproc, alloqual, allores, allorole, cat, obo, purl,
-    qb, qudt, qudtext, schema, unit,
+    allocom, allodc, allohdf, allohdfcube, alloproc, alloqual, allores, allorole, cat, obo, prov,
+    purl, qb, qudt, qudtext, schema, sh, unit,
 };
 use sophia_api::{prefix::Prefix, prelude::Iri};
 
@@ -57,7 +57,9 @@ pub fn generate_prefix_map() -> Vec<(Prefix<Box<str>>, Iri<Box<str>>)> {
                 alloqual,
                 allodc,
                 purl,
-                obo
+                obo,
+                prov,
+                sh
             )
             .into_iter(),
         )
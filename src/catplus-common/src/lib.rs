@@ -1,3 +1,10 @@
+// Lets the derive macros of `catplus-derive` refer to `::catplus_common` within this crate.
+extern crate self as catplus_common;
+
+pub mod chemistry;
+pub mod error;
 pub mod graph;
 pub mod models;
 pub mod rdf;
+pub mod units;
+pub mod zarr;
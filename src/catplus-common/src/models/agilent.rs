@@ -1,19 +1,27 @@
 use crate::{
+    error::{RecordArray, Records},
     graph::{
-        insert_into::{InsertIntoGraph, Link},
+        from_graph::FromGraph,
+        insert_into::InsertIntoGraph,
         namespaces::{allodc, allores, allorole, cat, obo, qb, qudt},
     },
-    models::{core::PeakList, enums::Unit},
+    models::core::{Peak, PeakList},
+    units::unit::Unit,
+    zarr::{
+        nd_array::NdArray,
+        store::{node_name, ZarrStore},
+        write_into::{write_data_cube, WriteIntoZarr},
+    },
 };
 
-use serde::{Deserialize, Serialize};
+use serde::{de, Deserialize, Deserializer, Serialize};
 use sophia::{
-    api::ns::{rdf, rdfs, xsd},
+    api::ns::{rdfs, xsd},
     inmem::graph::LightGraph,
 };
-use sophia_api::term::{SimpleTerm, Term};
+use sophia_api::term::SimpleTerm;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 pub struct LiquidChromatographyAggregateDocumentWrapper {
     #[serde(rename = "liquid chromatography aggregate document")]
     pub liquid_chromatography_aggregate_document: LiquidChromatographyAggregateDocument,
@@ -25,461 +33,385 @@ impl InsertIntoGraph for LiquidChromatographyAggregateDocumentWrapper {
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+impl FromGraph for LiquidChromatographyAggregateDocumentWrapper {
+    fn from_graph(graph: &LightGraph, iri: &SimpleTerm) -> anyhow::Result<Self> {
+        Ok(LiquidChromatographyAggregateDocumentWrapper {
+            liquid_chromatography_aggregate_document:
+                LiquidChromatographyAggregateDocument::from_graph(graph, iri)?,
+        })
+    }
+
+    fn from_root(graph: &LightGraph) -> anyhow::Result<Self> {
+        Ok(LiquidChromatographyAggregateDocumentWrapper {
+            liquid_chromatography_aggregate_document:
+                LiquidChromatographyAggregateDocument::from_root(graph)?,
+        })
+    }
+}
+
+impl Records for LiquidChromatographyAggregateDocumentWrapper {
+    fn records() -> Vec<RecordArray> {
+        vec![
+            RecordArray::of::<LiquidChromatographyDocument>(concat!(
+                "/liquid chromatography aggregate document",
+                "/liquid chromatography document"
+            )),
+            RecordArray::of::<MeasurementDocument>(concat!(
+                "/liquid chromatography aggregate document",
+                "/liquid chromatography document/*",
+                "/measurement aggregate document/measurement document"
+            )),
+            RecordArray::of::<Peak>(concat!(
+                "/liquid chromatography aggregate document",
+                "/liquid chromatography document/*",
+                "/measurement aggregate document/measurement document/*",
+                "/processed data document/peak list/peak"
+            )),
+        ]
+    }
+}
+
+impl WriteIntoZarr for LiquidChromatographyAggregateDocumentWrapper {
+    fn write_into_zarr(&mut self, store: &ZarrStore, path: &str) -> anyhow::Result<()> {
+        self.liquid_chromatography_aggregate_document.write_into_zarr(store, path)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, InsertIntoGraph, FromGraph)]
+#[rdf(type = "allores::AFR_0002524")]
 pub struct LiquidChromatographyAggregateDocument {
     #[serde(rename = "liquid chromatography document")]
+    #[rdf(pred = "cat::hasLiquidChromatography")]
     pub liquid_chromatography_document: Option<Vec<LiquidChromatographyDocument>>,
     #[serde(rename = "device system document")]
+    #[rdf(pred = "allores::AFR_0002526")]
     pub device_system_document: Option<DeviceSystemDocument>,
 }
 
-impl InsertIntoGraph for LiquidChromatographyAggregateDocument {
-    fn insert_into(&self, graph: &mut LightGraph, iri: SimpleTerm) -> anyhow::Result<()> {
-        for (pred, value) in [
-            (rdf::type_, &allores::AFR_0002524.as_simple() as &dyn InsertIntoGraph),
-            (cat::hasLiquidChromatography, &self.liquid_chromatography_document),
-            (allores::AFR_0002526, &self.device_system_document),
-        ] {
-            value.attach_into(
-                graph,
-                Link { source_iri: iri.clone(), pred: pred.as_simple(), target_iri: None },
-            )?;
+impl WriteIntoZarr for LiquidChromatographyAggregateDocument {
+    fn write_into_zarr(&mut self, store: &ZarrStore, path: &str) -> anyhow::Result<()> {
+        for (index, document) in
+            self.liquid_chromatography_document.iter_mut().flatten().enumerate()
+        {
+            let document_path = format!("{}/liquid_chromatography_{}", path, index);
+            store.create_group(&document_path, Default::default())?;
+            document.write_into_zarr(store, &document_path)?;
         }
         Ok(())
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, InsertIntoGraph, FromGraph)]
+#[rdf(type = "allores::AFR_0002525")]
 pub struct LiquidChromatographyDocument {
+    #[rdf(pred = "allores::AFR_0001116")]
     pub analyst: String,
+    // NOTE: measurement_aggregate_document is not materialized in the ontology -> we attach
+    // measurement_document directly to LiquidChromatographyDocument
     #[serde(rename = "measurement aggregate document")]
+    #[rdf(flatten)]
     pub measurement_aggregate_document: MeasurementAggregateDocument,
 }
 
-impl InsertIntoGraph for LiquidChromatographyDocument {
-    fn insert_into(&self, graph: &mut LightGraph, iri: SimpleTerm) -> anyhow::Result<()> {
-        for (pred, value) in [
-            (rdf::type_, &allores::AFR_0002525.as_simple() as &dyn InsertIntoGraph),
-            (allores::AFR_0001116, &self.analyst.as_simple()),
-        ] {
-            value.attach_into(
-                graph,
-                Link { source_iri: iri.clone(), pred: pred.as_simple(), target_iri: None },
-            )?;
+impl WriteIntoZarr for LiquidChromatographyDocument {
+    fn write_into_zarr(&mut self, store: &ZarrStore, path: &str) -> anyhow::Result<()> {
+        // NOTE: measurement_aggregate_document is not materialized in the store either
+        for (index, document) in
+            self.measurement_aggregate_document.measurement_documents.iter_mut().enumerate()
+        {
+            let document_path =
+                format!("{}/{}_{}", path, index, node_name(&document.measurement_identifier));
+            store.create_group(&document_path, Default::default())?;
+            document.write_into_zarr(store, &document_path)?;
         }
-        // NOTE: measurement_aggregate_document is not materliazed in the ontology -> we will attach measurement_document directly to LiquidChromatigraphyDocument
-        let _ = &self.measurement_aggregate_document.insert_into(graph, iri)?;
         Ok(())
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, InsertIntoGraph, FromGraph)]
 pub struct MeasurementAggregateDocument {
     #[serde(rename = "measurement document")]
+    #[rdf(pred = "allores::AFR_0002374")]
     pub measurement_documents: Vec<MeasurementDocument>,
 }
 
-impl InsertIntoGraph for MeasurementAggregateDocument {
-    fn insert_into(&self, graph: &mut LightGraph, iri: SimpleTerm) -> anyhow::Result<()> {
-        // NOTE: measurement_aggregate_document is not materliazed in the ontology -> we will attach measurement_document directly to LiquidChromatigraphyDocument
-        let _ = &self.measurement_documents.attach_into(
-            graph,
-            Link {
-                source_iri: iri.clone(),
-                pred: allores::AFR_0002374.as_simple(),
-                target_iri: None,
-            },
-        )?;
-        Ok(())
-    }
-}
-
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, InsertIntoGraph, FromGraph)]
+#[rdf(type = "allores::AFR_0002375")]
 pub struct MeasurementDocument {
     #[serde(rename = "measurement identifier")]
+    #[rdf(pred = "allores::AFR_0001121")]
     pub measurement_identifier: String,
     // TO-DO: needs further definition to be integrated
     // #[serde(rename = "chromatography column document")]
     // pub chromatography_column_document: ChromatographyColumnDocument,
     #[serde(rename = "device control aggregate document")]
+    #[rdf(pred = "allores::AFR_0002526")]
     pub device_control_aggregate_document: DeviceSystemDocument,
     #[serde(rename = "sample document")]
+    #[rdf(pred = "allores::AFR_0002083")]
     pub sample_document: SampleDocument,
     #[serde(rename = "injection document")]
+    #[rdf(pred = "allores::AFR_0002529")]
     pub injection_document: InjectionDocument,
     #[serde(rename = "detection type")]
+    #[rdf(pred = "allores::AFR_0002534")]
     pub detection_type: String,
     #[serde(rename = "chromatogram data cube")]
+    #[rdf(pred = "allores::AFR_0002550")]
     pub chromatogram_data_cube: Option<ChromatogramDataCube>,
     #[serde(rename = "three-dimensional ultraviolet spectrum data cube")]
+    #[rdf(pred = "allores::AFR_0002551")]
     pub three_dimensional_ultraviolet_spectrum_data_cube:
         Option<ThreeDimensionalUltravioletSpectrumDataCube>,
     #[serde(rename = "three-dimensional mass spectrum data cube")]
+    #[rdf(pred = "allores::AFR_0002878")]
     pub three_three_dimensional_mass_spectrum_data_cube:
         Option<ThreeDimensionalMassSpectrumDataCube>,
     #[serde(rename = "processed data document")]
+    #[rdf(pred = "allores::AFR_0002659")]
     pub processed_data_document: Option<ProcessedDataDocument>,
 }
 
-impl InsertIntoGraph for MeasurementDocument {
-    fn insert_into(&self, graph: &mut LightGraph, iri: SimpleTerm) -> anyhow::Result<()> {
-        for (pred, value) in [
-            (rdf::type_, &allores::AFR_0002375.as_simple() as &dyn InsertIntoGraph),
-            (allores::AFR_0001121, &self.measurement_identifier.as_simple()),
-            // TO-DO: needs further definition to be integrated
-            // (allores::AFR_0002607, &self.chromatography_column_document),
-            (allores::AFR_0002526, &self.device_control_aggregate_document),
-            (allores::AFR_0002083, &self.sample_document),
-            (allores::AFR_0002529, &self.injection_document),
-            (allores::AFR_0002534, &self.detection_type.as_simple()),
-            (allores::AFR_0002550, &self.chromatogram_data_cube),
-            (allores::AFR_0002551, &self.three_dimensional_ultraviolet_spectrum_data_cube),
-            (allores::AFR_0002878, &self.three_three_dimensional_mass_spectrum_data_cube),
-            (allores::AFR_0002659, &self.processed_data_document),
-        ] {
-            value.attach_into(
-                graph,
-                Link { source_iri: iri.clone(), pred: pred.as_simple(), target_iri: None },
-            )?;
-        }
+impl WriteIntoZarr for MeasurementDocument {
+    fn write_into_zarr(&mut self, store: &ZarrStore, path: &str) -> anyhow::Result<()> {
+        self.chromatogram_data_cube
+            .write_into_zarr(store, &format!("{}/chromatogram_data_cube", path))?;
+        self.three_dimensional_ultraviolet_spectrum_data_cube.write_into_zarr(
+            store,
+            &format!("{}/three_dimensional_ultraviolet_spectrum_data_cube", path),
+        )?;
+        self.three_three_dimensional_mass_spectrum_data_cube.write_into_zarr(
+            store,
+            &format!("{}/three_dimensional_mass_spectrum_data_cube", path),
+        )?;
         Ok(())
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, InsertIntoGraph, FromGraph)]
+#[rdf(type = "cat::ChromatographyColumnDocument")]
 pub struct ChromatographyColumnDocument {}
 
-impl InsertIntoGraph for ChromatographyColumnDocument {
-    fn insert_into(&self, graph: &mut LightGraph, iri: SimpleTerm) -> anyhow::Result<()> {
-        for (pred, value) in
-            [(rdf::type_, &cat::ChromatographyColumnDocument.as_simple() as &dyn InsertIntoGraph)]
-        {
-            value.attach_into(
-                graph,
-                Link { source_iri: iri.clone(), pred: pred.as_simple(), target_iri: None },
-            )?;
-        }
-        Ok(())
-    }
-}
-
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, InsertIntoGraph, FromGraph)]
+#[rdf(type = "cat::DeviceSystemDocument")]
 pub struct DeviceSystemDocument {
     #[serde(alias = "device document", alias = "device control document")]
+    #[rdf(pred = "allores::AFR_0002722")]
     pub device_document: Vec<DeviceDocument>,
     #[serde(rename = "asset management identifier")]
+    #[rdf(pred = "allores::AFR_0001976")]
     pub asset_management_identifier: Option<String>,
 }
 
-impl InsertIntoGraph for DeviceSystemDocument {
-    fn insert_into(&self, graph: &mut LightGraph, iri: SimpleTerm) -> anyhow::Result<()> {
-        for (pred, value) in [
-            (rdf::type_, &cat::DeviceSystemDocument.as_simple() as &dyn InsertIntoGraph),
-            (allores::AFR_0002722, &self.device_document),
-            (
-                allores::AFR_0001976,
-                &self.asset_management_identifier.as_ref().clone().map(|s| s.as_simple()),
-            ),
-        ] {
-            value.attach_into(
-                graph,
-                Link { source_iri: iri.clone(), pred: pred.as_simple(), target_iri: None },
-            )?;
-        }
-        Ok(())
-    }
-}
-
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, InsertIntoGraph, FromGraph)]
+#[rdf(type = "allores::AFR_0002567")]
 pub struct DeviceDocument {
     #[serde(rename = "device identifier")]
+    #[rdf(pred = "allores::AFR_0002018")]
     pub device_identifier: String,
     #[serde(rename = "device type")]
+    #[rdf(pred = "allores::AFR_0002568")]
     pub device_type: String,
     #[serde(rename = "product manufacturer")]
+    #[rdf(pred = "allores::AFR_0001258")]
     pub product_manufacturer: String,
     #[serde(rename = "equipment serial number")]
+    #[rdf(pred = "allores::AFR_0001119")]
     pub equipment_serial_number: String,
     #[serde(rename = "model number")]
+    #[rdf(pred = "obo::IAO_0000017")]
     pub model_number: String,
     #[serde(rename = "firmware version")]
+    #[rdf(pred = "allores::AFR_0001259")]
     pub firmware_version: String,
     #[serde(rename = "detection type")]
+    #[rdf(pred = "allores::AFR_0002534")]
     pub detection_type: Option<String>,
+    /// Not part of the graph, which records the order of the documents with `schema:position`.
     #[serde(rename = "@index")]
+    #[rdf(skip)]
     pub index: Option<i64>,
 }
 
-impl InsertIntoGraph for DeviceDocument {
-    fn insert_into(&self, graph: &mut LightGraph, iri: SimpleTerm) -> anyhow::Result<()> {
-        for (pred, value) in [
-            (rdf::type_, &allores::AFR_0002567.as_simple() as &dyn InsertIntoGraph),
-            (allores::AFR_0002018, &self.device_identifier.as_simple()),
-            (allores::AFR_0002568, &self.device_type.as_simple()),
-            (allores::AFR_0001258, &self.product_manufacturer.as_simple()),
-            (allores::AFR_0001119, &self.equipment_serial_number.as_simple()),
-            (obo::IAO_0000017, &self.model_number.as_simple()),
-            (allores::AFR_0001259, &self.firmware_version.as_simple()),
-            (allores::AFR_0002534, &self.detection_type.as_ref().clone().map(|s| s.as_simple())),
-            // TO-DO issue with unpacking the index
-            //(allohdfcube::Index, &self.index)
-        ] {
-            value.attach_into(
-                graph,
-                Link { source_iri: iri.clone(), pred: pred.as_simple(), target_iri: None },
-            )?;
-        }
-        Ok(())
-    }
-}
-
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, InsertIntoGraph, FromGraph)]
+#[rdf(type = "cat::ProcessedDataDocument")]
 pub struct ProcessedDataDocument {
     #[serde(rename = "peak list")]
+    #[rdf(pred = "allores::AFR_0000432")]
     pub peak_list: PeakList,
 }
 
-impl InsertIntoGraph for ProcessedDataDocument {
-    fn insert_into(&self, graph: &mut LightGraph, iri: SimpleTerm) -> anyhow::Result<()> {
-        for (pred, value) in [
-            (rdf::type_, &cat::ProcessedDataDocument.as_simple() as &dyn InsertIntoGraph),
-            (allores::AFR_0000432, &self.peak_list),
-        ] {
-            value.attach_into(
-                graph,
-                Link { source_iri: iri.clone(), pred: pred.as_simple(), target_iri: None },
-            )?;
-        }
-        Ok(())
-    }
-}
-
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, InsertIntoGraph, FromGraph)]
+#[rdf(type = "cat::SampleDocument")]
 pub struct SampleDocument {
     #[serde(rename = "sample identifier")]
+    #[rdf(pred = "allores::AFR_0001118")]
     pub sample_identifier: String,
     #[serde(rename = "written name")]
+    #[rdf(pred = "obo::IAO_0000590")]
     pub written_name: String,
 }
 
-impl InsertIntoGraph for SampleDocument {
-    fn insert_into(&self, graph: &mut LightGraph, iri: SimpleTerm) -> anyhow::Result<()> {
-        for (pred, value) in [
-            (rdf::type_, &cat::SampleDocument.as_simple() as &dyn InsertIntoGraph),
-            (allores::AFR_0001118, &self.sample_identifier.as_simple()),
-            (obo::IAO_0000590, &self.written_name.as_simple()),
-        ] {
-            value.attach_into(
-                graph,
-                Link { source_iri: iri.clone(), pred: pred.as_simple(), target_iri: None },
-            )?;
-        }
-        Ok(())
-    }
-}
-
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, InsertIntoGraph, FromGraph)]
+#[rdf(type = "cat::InjectionDocument")]
 pub struct InjectionDocument {
     #[serde(rename = "autosampler injection volume setting (chromatography)")]
+    #[rdf(pred = "allores::AFR_0001267")]
     pub autosampler_injection: AutosamplerInjectionVolumeSetting,
     #[serde(rename = "injection identifier")]
+    #[rdf(pred = "allores::AFR_0002535")]
     pub injection_identifier: String,
     #[serde(rename = "injection time")]
+    #[rdf(pred = "allores::AFR_0002536", datatype = "xsd::dateTime")]
     pub injection_time: String,
 }
 
-impl InsertIntoGraph for InjectionDocument {
-    fn insert_into(&self, graph: &mut LightGraph, iri: SimpleTerm) -> anyhow::Result<()> {
-        for (pred, value) in [
-            (rdf::type_, &cat::InjectionDocument.as_simple() as &dyn InsertIntoGraph),
-            (allores::AFR_0001267, &self.autosampler_injection),
-            (allores::AFR_0002535, &self.injection_identifier.as_simple()),
-            (allores::AFR_0002536, &(self.injection_time.as_str() * xsd::dateTime).as_simple()),
-        ] {
-            value.attach_into(
-                graph,
-                Link { source_iri: iri.clone(), pred: pred.as_simple(), target_iri: None },
-            )?;
-        }
-        Ok(())
-    }
-}
-
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, InsertIntoGraph, FromGraph)]
+#[rdf(type = "cat::ChromatogramDataCube")]
 pub struct ChromatogramDataCube {
+    #[rdf(pred = "obo::IAO_0000009")]
     pub label: Option<String>,
     #[serde(rename = "cube-structure")]
+    #[rdf(pred = "qb::structure")]
     pub cube_structure: CubeStructure,
+    #[rdf(pred = "allores::AFR_0000917")]
     pub identifier: Option<String>,
-}
-
-impl InsertIntoGraph for ChromatogramDataCube {
-    fn insert_into(&self, graph: &mut LightGraph, iri: SimpleTerm) -> anyhow::Result<()> {
-        for (pred, value) in [
-            (rdf::type_, &cat::ChromatogramDataCube.as_simple() as &dyn InsertIntoGraph),
-            (obo::IAO_0000009, &self.label.as_ref().clone().map(|s| s.as_simple())),
-            (qb::structure, &self.cube_structure),
-            (allores::AFR_0000917, &self.identifier.as_ref().clone().map(|s| s.as_simple())),
-        ] {
-            value.attach_into(
-                graph,
-                Link { source_iri: iri.clone(), pred: pred.as_simple(), target_iri: None },
-            )?;
+    /// Written to the Zarr store rather than to the graph, so not read back from it.
+    #[rdf(skip)]
+    pub data: Option<CubeData>,
+    /// Path of the cube in the Zarr store, set once its data has been written.
+    #[serde(skip)]
+    #[rdf(pred = "cat::zarrPath")]
+    pub zarr_path: Option<String>,
+}
+
+impl WriteIntoZarr for ChromatogramDataCube {
+    fn write_into_zarr(&mut self, store: &ZarrStore, path: &str) -> anyhow::Result<()> {
+        if let Some(data) = &self.data {
+            write_data_cube(store, path, &self.cube_structure, data)?;
+            self.zarr_path = Some(path.to_string());
         }
         Ok(())
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, InsertIntoGraph, FromGraph)]
+#[rdf(type = "cat::ThreeDimensionalUltravioletSpectrumDataCube")]
 pub struct ThreeDimensionalUltravioletSpectrumDataCube {
+    #[rdf(pred = "obo::IAO_0000009")]
     pub label: String,
     #[serde(rename = "cube-structure")]
+    #[rdf(pred = "qb::structure")]
     pub cube_structure: CubeStructure,
+    #[rdf(pred = "allores::AFR_0000917")]
     pub identifier: String,
-}
-
-impl InsertIntoGraph for ThreeDimensionalUltravioletSpectrumDataCube {
-    fn insert_into(&self, graph: &mut LightGraph, iri: SimpleTerm) -> anyhow::Result<()> {
-        for (pred, value) in [
-            (
-                rdf::type_,
-                &cat::ThreeDimensionalUltravioletSpectrumDataCube.as_simple()
-                    as &dyn InsertIntoGraph,
-            ),
-            (obo::IAO_0000009, &self.label.as_simple()),
-            (qb::structure, &self.cube_structure),
-            (allores::AFR_0000917, &self.identifier.as_simple()),
-        ] {
-            value.attach_into(
-                graph,
-                Link { source_iri: iri.clone(), pred: pred.as_simple(), target_iri: None },
-            )?;
+    /// Written to the Zarr store rather than to the graph, so not read back from it.
+    #[rdf(skip)]
+    pub data: Option<CubeData>,
+    /// Path of the cube in the Zarr store, set once its data has been written.
+    #[serde(skip)]
+    #[rdf(pred = "cat::zarrPath")]
+    pub zarr_path: Option<String>,
+}
+
+impl WriteIntoZarr for ThreeDimensionalUltravioletSpectrumDataCube {
+    fn write_into_zarr(&mut self, store: &ZarrStore, path: &str) -> anyhow::Result<()> {
+        if let Some(data) = &self.data {
+            write_data_cube(store, path, &self.cube_structure, data)?;
+            self.zarr_path = Some(path.to_string());
         }
         Ok(())
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, InsertIntoGraph, FromGraph)]
+#[rdf(type = "cat::ThreeDimensionalMassSpectrumDataCube")]
 pub struct ThreeDimensionalMassSpectrumDataCube {
+    #[rdf(pred = "obo::IAO_0000009")]
     pub label: String,
     #[serde(rename = "cube-structure")]
+    #[rdf(pred = "qb::structure")]
     pub cube_structure: CubeStructure,
+    #[rdf(pred = "allores::AFR_0000917")]
     pub identifier: String,
-}
-
-impl InsertIntoGraph for ThreeDimensionalMassSpectrumDataCube {
-    fn insert_into(&self, graph: &mut LightGraph, iri: SimpleTerm) -> anyhow::Result<()> {
-        for (pred, value) in [
-            (
-                rdf::type_,
-                &cat::ThreeDimensionalMassSpectrumDataCube.as_simple() as &dyn InsertIntoGraph,
-            ),
-            (obo::IAO_0000009, &self.label.as_simple()),
-            (qb::structure, &self.cube_structure),
-            (allores::AFR_0000917, &self.identifier.as_simple()),
-        ] {
-            value.attach_into(
-                graph,
-                Link { source_iri: iri.clone(), pred: pred.as_simple(), target_iri: None },
-            )?;
+    /// Written to the Zarr store rather than to the graph, so not read back from it.
+    #[rdf(skip)]
+    pub data: Option<CubeData>,
+    /// Path of the cube in the Zarr store, set once its data has been written.
+    #[serde(skip)]
+    #[rdf(pred = "cat::zarrPath")]
+    pub zarr_path: Option<String>,
+}
+
+impl WriteIntoZarr for ThreeDimensionalMassSpectrumDataCube {
+    fn write_into_zarr(&mut self, store: &ZarrStore, path: &str) -> anyhow::Result<()> {
+        if let Some(data) = &self.data {
+            write_data_cube(store, path, &self.cube_structure, data)?;
+            self.zarr_path = Some(path.to_string());
         }
         Ok(())
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, InsertIntoGraph, FromGraph)]
+#[rdf(type = "cat::AutosamplerInjectionVolumeSetting")]
 pub struct AutosamplerInjectionVolumeSetting {
+    #[rdf(pred = "qudt::value")]
     pub value: f64,
+    #[rdf(pred = "qudt::unit")]
     pub unit: Unit,
 }
 
-impl InsertIntoGraph for AutosamplerInjectionVolumeSetting {
-    fn insert_into(&self, graph: &mut LightGraph, iri: SimpleTerm) -> anyhow::Result<()> {
-        for (pred, value) in [
-            (
-                rdf::type_,
-                &cat::AutosamplerInjectionVolumeSetting.as_simple() as &dyn InsertIntoGraph,
-            ),
-            (qudt::value, &self.value.as_simple()),
-            (qudt::unit, &self.unit.iri().as_simple()),
-        ] {
-            value.attach_into(
-                graph,
-                Link { source_iri: iri.clone(), pred: pred.as_simple(), target_iri: None },
-            )?;
-        }
-        Ok(())
-    }
+/// The values of a data cube, in the order of the measures and dimensions of its [CubeStructure].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CubeData {
+    pub measures: Vec<NdArray>,
+    pub dimensions: Vec<NdArray>,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, InsertIntoGraph, FromGraph)]
+#[rdf(type = "cat::CubeStructure")]
 pub struct CubeStructure {
+    #[rdf(pred = "cat::measure")]
     pub measures: Vec<Measure>,
+    #[rdf(pred = "cat::dimension")]
     pub dimensions: Vec<Dimension>,
 }
 
-impl InsertIntoGraph for CubeStructure {
-    fn insert_into(&self, graph: &mut LightGraph, iri: SimpleTerm) -> anyhow::Result<()> {
-        for (pred, value) in [
-            (rdf::type_, &cat::CubeStructure.as_simple() as &dyn InsertIntoGraph),
-            (cat::measure, &self.measures),
-            (cat::dimension, &self.dimensions),
-        ] {
-            value.attach_into(
-                graph,
-                Link { source_iri: iri.clone(), pred: pred.as_simple(), target_iri: None },
-            )?;
-        }
-        Ok(())
-    }
-}
-
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, InsertIntoGraph, FromGraph)]
+#[rdf(type = "allorole::AFRL_0000157")]
 pub struct Measure {
     #[serde(rename = "@componentDatatype")]
+    #[rdf(pred = "allodc::componentDataType")]
     pub component_data_type: String, //subject to change
+    #[rdf(pred = "rdfs::label")]
     pub concept: String,
+    #[rdf(pred = "qudt::unit")]
     pub unit: Unit,
 }
 
-impl InsertIntoGraph for Measure {
-    fn insert_into(&self, graph: &mut LightGraph, iri: SimpleTerm) -> anyhow::Result<()> {
-        for (pred, value) in [
-            (rdf::type_, &allorole::AFRL_0000157.as_simple() as &dyn InsertIntoGraph),
-            (allodc::componentDataType, &self.component_data_type.as_simple()),
-            (rdfs::label, &self.concept.as_simple()),
-            (qudt::unit, &self.unit.iri().as_simple()),
-        ] {
-            value.attach_into(
-                graph,
-                Link { source_iri: iri.clone(), pred: pred.as_simple(), target_iri: None },
-            )?;
-        }
-        Ok(())
-    }
-}
-
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, InsertIntoGraph, FromGraph)]
+#[rdf(type = "cat::Dimension")]
 pub struct Dimension {
     #[serde(rename = "@componentDatatype")]
+    #[rdf(pred = "allodc::componentDataType")]
     pub component_data_type: String, //subject to change
+    #[rdf(pred = "rdfs::label")]
     pub concept: String,
+    #[serde(deserialize_with = "deserialize_dimension_unit")]
+    #[rdf(pred = "qudt::unit")]
     pub unit: Unit,
 }
 
-impl InsertIntoGraph for Dimension {
-    fn insert_into(&self, graph: &mut LightGraph, iri: SimpleTerm) -> anyhow::Result<()> {
-        for (pred, value) in [
-            (rdf::type_, &cat::Dimension.as_simple() as &dyn InsertIntoGraph),
-            (allodc::componentDataType, &self.component_data_type.as_simple()),
-            (rdfs::label, &self.concept.as_simple()),
-            (qudt::unit, &self.unit.iri().as_simple()),
-        ] {
-            value.attach_into(
-                graph,
-                Link { source_iri: iri.clone(), pred: pred.as_simple(), target_iri: None },
-            )?;
-        }
-        Ok(())
-    }
+/// Agilent exports write wavelengths in nm as `nM`, which is otherwise rejected as ambiguous.
+/// The unit keeps its label, so that it is written back as in the export.
+fn deserialize_dimension_unit<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Unit, D::Error> {
+    let label = String::deserialize(deserializer)?;
+    let unit = match label.as_str() {
+        "nM" => Unit::parse("nm").map(|unit| Unit { label, ..unit }),
+        label => Unit::parse(label),
+    };
+    unit.map_err(|error| de::Error::custom(format!("{:#}", error)))
 }
@@ -4,322 +4,214 @@
 // https://github.com/sdsc-ordes/catplus-ontology/tree/96091fd2e75e03de8a4c4d66ad502b2db27998bd/json-file/1-Synth
 use crate::{
     graph::{
-        insert_into::{InsertIntoGraph, Link},
+        from_graph::FromGraph,
+        insert_into::InsertIntoGraph,
         namespaces::{alloqual, allores, cat, obo, purl, qudt, schema},
     },
-    models::enums::Unit,
+    units::unit::Unit,
 };
-use anyhow;
 use serde::{Deserialize, Serialize};
-use sophia::{api::ns::rdf, inmem::graph::LightGraph};
-use sophia_api::term::{SimpleTerm, Term};
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, InsertIntoGraph, FromGraph)]
+#[rdf(type = "cat::Plate")]
 #[serde(rename_all = "camelCase")]
 pub struct Plate {
     #[serde(rename = "containerID")]
+    #[rdf(pred = "cat::containerID")]
     pub container_id: String,
+    #[rdf(pred = "cat::containerBarcode")]
     pub container_barcode: Option<String>,
 }
 
-impl InsertIntoGraph for Plate {
-    fn insert_into(&self, graph: &mut LightGraph, iri: SimpleTerm) -> anyhow::Result<()> {
-        for (prop, value) in [
-            (rdf::type_, &cat::Plate.as_simple() as &dyn InsertIntoGraph),
-            (cat::containerID, &self.container_id.as_simple() as &dyn InsertIntoGraph),
-            (
-                cat::containerBarcode,
-                &self.container_barcode.as_ref().clone().map(|s| s.as_simple()),
-            ),
-        ] {
-            value.attach_into(
-                graph,
-                Link { source_iri: iri.clone(), pred: prop.as_simple(), target_iri: None },
-            )?;
-        }
-        Ok(())
-    }
-}
-
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, InsertIntoGraph, FromGraph)]
+#[rdf(type = "cat::Observation")]
 #[serde(rename_all = "camelCase")]
 pub struct Observation {
+    #[rdf(pred = "qudt::value")]
     pub value: f64,
+    #[rdf(pred = "qudt::unit")]
     pub unit: Unit,
+    #[rdf(pred = "cat::errorMargin")]
     pub error_margin: Option<ErrorMargin>,
 }
 
-/// Implementation for concrete [Observation].
-impl InsertIntoGraph for Observation {
-    fn insert_into(&self, graph: &mut LightGraph, iri: SimpleTerm) -> anyhow::Result<()> {
-        for (prop, value) in [
-            (rdf::type_, &cat::Observation.as_simple() as &dyn InsertIntoGraph),
-            (qudt::unit, &self.unit.iri().as_simple() as &dyn InsertIntoGraph),
-            (qudt::value, &self.value.as_simple()),
-            (cat::errorMargin, &self.error_margin),
-        ] {
-            value.attach_into(
-                graph,
-                Link { source_iri: iri.clone(), pred: prop.as_simple(), target_iri: None },
-            )?;
-        }
-
-        Ok(())
-    }
-}
-
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, InsertIntoGraph, FromGraph)]
+#[rdf(type = "cat::errorMargin")]
 pub struct ErrorMargin {
+    #[rdf(pred = "qudt::value")]
     pub value: f64,
+    #[rdf(pred = "qudt::unit")]
     pub unit: Unit,
 }
 
-impl InsertIntoGraph for ErrorMargin {
-    fn insert_into(&self, graph: &mut LightGraph, iri: SimpleTerm) -> anyhow::Result<()> {
-        for (prop, value) in [
-            (rdf::type_, &cat::errorMargin.as_simple() as &dyn InsertIntoGraph),
-            (qudt::unit, &self.unit.iri().as_simple() as &dyn InsertIntoGraph),
-            (qudt::value, &self.value.as_simple()),
-        ] {
-            value.attach_into(
-                graph,
-                Link { source_iri: iri.clone(), pred: prop.as_simple(), target_iri: None },
-            )?;
-        }
-
-        Ok(())
-    }
-}
-
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, InsertIntoGraph, FromGraph)]
+#[rdf(type = "cat::Sample")]
 #[serde(rename_all = "camelCase")]
 pub struct Sample {
     #[serde(flatten)]
+    #[rdf(pred = "cat::hasPlate")]
     pub has_plate: Plate,
     #[serde(rename = "vialID")]
+    #[rdf(pred = "allores::AFR_0002464")]
     pub vial_id: String,
+    #[rdf(pred = "cat::vialShape")]
     pub vial_type: String,
+    #[rdf(pred = "cat::role")]
     pub role: String,
+    #[rdf(pred = "cat::expectedDatum")]
     pub expected_datum: Observation,
+    #[rdf(pred = "cat::hasSample")]
     pub has_sample: Vec<SampleItem>,
 }
 
-impl InsertIntoGraph for Sample {
-    fn insert_into(&self, graph: &mut LightGraph, iri: SimpleTerm) -> anyhow::Result<()> {
-        for (prop, value) in [
-            (rdf::type_, &cat::Sample.as_simple() as &dyn InsertIntoGraph),
-            (cat::hasPlate, &self.has_plate),
-            (cat::role, &self.role.as_simple()),
-            (cat::vialShape, &self.vial_type.as_simple()),
-            (allores::AFR_0002464, &self.vial_id.as_simple()),
-            (cat::expectedDatum, &self.expected_datum),
-            (cat::hasSample, &self.has_sample),
-        ] {
-            value.attach_into(
-                graph,
-                Link { source_iri: iri.clone(), pred: prop.as_simple(), target_iri: None },
-            )?;
-        }
-
-        Ok(())
-    }
-}
-
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, InsertIntoGraph, FromGraph)]
+#[rdf(type = "cat::Sample")]
 #[serde(rename_all = "camelCase")]
 pub struct SampleItem {
     #[serde(rename = "sampleID")]
+    #[rdf(pred = "purl::identifier")]
     pub sample_id: String,
+    #[rdf(pred = "cat::role")]
     pub role: String,
+    #[rdf(pred = "cat::internalBarCode")]
     pub internal_bar_code: String,
+    #[rdf(pred = "cat::expectedDatum")]
     pub expected_datum: Option<Observation>,
+    #[rdf(pred = "cat::measuredQuantity")]
     pub measured_quantity: Option<Observation>,
+    #[rdf(pred = "allores::AFR_0002036")]
     pub concentration: Option<Observation>,
+    #[rdf(pred = "alloqual::AFQ_0000111")]
     pub physical_state: String,
+    #[rdf(pred = "cat::hasChemical")]
     pub has_chemical: Chemical,
 }
 
-impl InsertIntoGraph for SampleItem {
-    fn insert_into(&self, graph: &mut LightGraph, iri: SimpleTerm) -> anyhow::Result<()> {
-        for (prop, value) in [
-            (rdf::type_, &cat::Sample.as_simple() as &dyn InsertIntoGraph),
-            (purl::identifier, &self.sample_id.as_simple()),
-            (cat::role, &self.role.as_simple()),
-            (cat::internalBarCode, &self.internal_bar_code.as_simple()),
-            (alloqual::AFQ_0000111, &self.physical_state.as_simple()),
-            (cat::expectedDatum, &self.expected_datum),
-            (cat::measuredQuantity, &self.measured_quantity),
-            (allores::AFR_0002036, &self.concentration),
-            (cat::hasChemical, &self.has_chemical),
-        ] {
-            value.attach_into(
-                graph,
-                Link { source_iri: iri.clone(), pred: prop.as_simple(), target_iri: None },
-            )?;
-        }
-
-        Ok(())
-    }
-}
-
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, InsertIntoGraph, FromGraph)]
+#[rdf(type = "obo::CHEBI_25367")]
 #[serde(rename_all = "camelCase")]
 pub struct Chemical {
     #[serde(rename = "chemicalID")]
+    #[rdf(pred = "purl::identifier")]
     pub chemical_id: String,
+    #[rdf(pred = "allores::AFR_0002292")]
     pub chemical_name: String,
     #[serde(rename = "CASNumber")]
+    #[rdf(pred = "cat::casNumber")]
     pub cas_number: Option<String>,
+    #[rdf(pred = "allores::AFR_0002294")]
     pub molecular_mass: Observation,
+    #[rdf(pred = "allores::AFR_0002295")]
     pub smiles: String,
+    #[rdf(pred = "cat::swissCatNumber")]
     pub swiss_cat_number: Option<String>,
     #[serde(rename = "Inchi")]
+    #[rdf(pred = "allores::AFR_0002296")]
     pub inchi: String,
+    #[rdf(pred = "schema::keywords")]
     pub keywords: Option<String>,
+    #[rdf(pred = "allores::AFR_0001952")]
     pub molecular_formula: String,
+    #[rdf(pred = "obo::PATO_0001019")]
     pub density: Option<Observation>,
 }
 
-impl InsertIntoGraph for Chemical {
-    fn insert_into(&self, graph: &mut LightGraph, iri: SimpleTerm) -> anyhow::Result<()> {
-        for (prop, value) in [
-            (rdf::type_, &obo::CHEBI_25367.as_simple() as &dyn InsertIntoGraph),
-            (purl::identifier, &self.chemical_id.as_simple()),
-            (allores::AFR_0002292, &self.chemical_name.as_simple()),
-            (allores::AFR_0001952, &self.molecular_formula.as_simple()),
-            (allores::AFR_0002295, &self.smiles.as_simple()),
-            (allores::AFR_0002294, &self.molecular_mass),
-            (allores::AFR_0002296, &self.inchi.as_simple()),
-            (cat::casNumber, &self.cas_number.as_ref().clone().map(|s| s.as_simple())),
-            (cat::swissCatNumber, &self.swiss_cat_number.as_ref().clone().map(|s| s.as_simple())),
-            (schema::keywords, &self.keywords.as_ref().clone().map(|s| s.as_simple())),
-            (obo::PATO_0001019, &self.density),
-        ] {
-            value.attach_into(
-                graph,
-                Link { source_iri: iri.clone(), pred: prop.as_simple(), target_iri: None },
-            )?;
-        }
-
-        Ok(())
-    }
-}
-
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, InsertIntoGraph, FromGraph)]
+#[rdf(type = "cat::Well")]
 pub struct Well {
     #[serde(flatten)]
+    #[rdf(pred = "cat::hasPlate")]
     pub has_plate: Plate,
+    #[rdf(pred = "allores::AFR_0002240")]
     pub position: String,
+    #[rdf(pred = "qudt::quantity")]
     pub quantity: Observation,
 }
 
-impl InsertIntoGraph for Well {
-    fn insert_into(&self, graph: &mut LightGraph, iri: SimpleTerm) -> anyhow::Result<()> {
-        for (pred, value) in [
-            (rdf::type_, &cat::Well.as_simple() as &dyn InsertIntoGraph),
-            (cat::hasPlate, &self.has_plate),
-            (allores::AFR_0002240, &self.position.as_simple()),
-            (qudt::quantity, &self.quantity),
-        ] {
-            value.attach_into(
-                graph,
-                Link { source_iri: iri.clone(), pred: pred.as_simple(), target_iri: None },
-            )?;
-        }
-
-        Ok(())
-    }
-}
-
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, InsertIntoGraph, FromGraph)]
+#[rdf(type = "cat::PeakList")]
 pub struct PeakList {
+    #[rdf(pred = "cat::Peak")]
     pub peak: Vec<Peak>,
 }
 
-impl InsertIntoGraph for PeakList {
-    fn insert_into(&self, graph: &mut LightGraph, iri: SimpleTerm) -> anyhow::Result<()> {
-        for (pred, value) in [
-            (rdf::type_, &cat::PeakList.as_simple() as &dyn InsertIntoGraph),
-            (cat::Peak, &self.peak),
-        ] {
-            value.attach_into(
-                graph,
-                Link { source_iri: iri.clone(), pred: pred.as_simple(), target_iri: None },
-            )?;
-        }
-        Ok(())
-    }
-}
-
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, InsertIntoGraph, FromGraph)]
+#[rdf(type = "allores::AFR_0000413")]
 pub struct Peak {
+    /// Not part of the graph, which records the order of the peaks with `schema:position`.
     #[serde(rename = "@index")]
+    #[rdf(skip)]
     pub index: i64,
     #[serde(rename = "peakIdentifier")]
+    #[rdf(pred = "allores::AFR_0001164")]
     pub peak_identifier: String,
     #[serde(rename = "peak area")]
+    #[rdf(pred = "allores::AFR_0001073")]
     pub peak_area: Measurement,
     #[serde(rename = "retention time")]
+    #[rdf(pred = "allores::AFR_0001089")]
     pub retention_time: Measurement,
     #[serde(rename = "peak start")]
+    #[rdf(pred = "allores::AFR_0001178")]
     pub peak_start: Measurement,
     #[serde(rename = "peak end")]
+    #[rdf(pred = "allores::AFR_0001180")]
     pub peak_end: Measurement,
     #[serde(rename = "peak height")]
+    #[rdf(pred = "allores::AFR_0000948")]
     pub peak_height: Measurement,
     #[serde(rename = "relative peak area")]
+    #[rdf(pred = "allores::AFR_0001165")]
     pub relative_peak_area: Measurement,
     #[serde(rename = "relative peak height")]
+    #[rdf(pred = "allores::AFR_0000949")]
     pub relative_peak_height: Measurement,
     #[serde(rename = "peak value at start")]
+    #[rdf(pred = "allores::AFR_0001179")]
     pub peak_value_at_start: Measurement,
     #[serde(rename = "peak value at end")]
+    #[rdf(pred = "allores::AFR_0001181")]
     pub peak_value_at_end: Measurement,
 }
 
-impl InsertIntoGraph for Peak {
-    fn insert_into(&self, graph: &mut LightGraph, iri: SimpleTerm) -> anyhow::Result<()> {
-        for (pred, value) in [
-            (rdf::type_, &allores::AFR_0000413.as_simple() as &dyn InsertIntoGraph),
-            (allores::AFR_0001164, &self.peak_identifier.as_simple()),
-            (allores::AFR_0001073, &self.peak_area),
-            (allores::AFR_0001089, &self.retention_time),
-            (allores::AFR_0001178, &self.peak_start),
-            (allores::AFR_0001180, &self.peak_end),
-            (allores::AFR_0000948, &self.peak_height),
-            (allores::AFR_0001165, &self.relative_peak_area),
-            (allores::AFR_0000949, &self.relative_peak_height),
-            (allores::AFR_0001179, &self.peak_value_at_start),
-            (allores::AFR_0001181, &self.peak_value_at_end),
-        ] {
-            value.attach_into(
-                graph,
-                Link { source_iri: iri.clone(), pred: pred.as_simple(), target_iri: None },
-            )?;
-        }
-        Ok(())
-    }
+#[derive(Clone, Debug, Serialize, Deserialize, InsertIntoGraph, FromGraph)]
+#[rdf(type = "cat::Measurement")]
+pub struct Measurement {
+    #[rdf(pred = "qudt::value")]
+    pub value: f64,
+    #[rdf(pred = "qudt::unit")]
+    pub unit: Unit,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct Measurement {
+/// A value converted to the SI unit of its quantity kind, attached to observations and
+/// measurements with `cat:normalizedValue` (see [Unit::to_si]).
+#[derive(Clone, Debug, Serialize, Deserialize, InsertIntoGraph, FromGraph)]
+#[rdf(type = "qudt::QuantityValue")]
+pub struct QuantityValue {
+    #[rdf(pred = "qudt::value")]
     pub value: f64,
+    #[rdf(pred = "qudt::unit")]
     pub unit: Unit,
 }
 
-impl InsertIntoGraph for Measurement {
-    fn insert_into(&self, graph: &mut LightGraph, iri: SimpleTerm) -> anyhow::Result<()> {
-        for (prop, value) in [
-            (rdf::type_, &cat::Measurement.as_simple() as &dyn InsertIntoGraph),
-            (qudt::unit, &self.unit.iri().as_simple() as &dyn InsertIntoGraph),
-            (qudt::value, &self.value.as_simple()),
-        ] {
-            value.attach_into(
-                graph,
-                Link { source_iri: iri.clone(), pred: prop.as_simple(), target_iri: None },
-            )?;
-        }
-        Ok(())
+impl QuantityValue {
+    /// Normalizes `value` in `unit` to SI, `None` if the unit has no SI counterpart.
+    pub fn normalized(value: f64, unit: &Unit) -> Option<QuantityValue> {
+        unit.to_si(value).map(|(value, unit)| QuantityValue { value, unit })
+    }
+}
+
+impl Observation {
+    /// The value of the observation in the SI unit of its quantity kind.
+    pub fn normalized(&self) -> Option<QuantityValue> {
+        QuantityValue::normalized(self.value, &self.unit)
+    }
+}
+
+impl Measurement {
+    /// The value of the measurement in the SI unit of its quantity kind.
+    pub fn normalized(&self) -> Option<QuantityValue> {
+        QuantityValue::normalized(self.value, &self.unit)
     }
 }
 
@@ -334,12 +226,29 @@ mod tests {
         models::{ErrorMargin, Observation},
     };
 
+    fn unit(label: &str) -> Unit {
+        Unit::parse(label).unwrap()
+    }
+
+    #[test]
+    fn test_normalize_to_si() {
+        let observation = Observation { value: 25.0, unit: unit("°C"), error_margin: None };
+        let normalized = observation.normalized().unwrap();
+        assert!((normalized.value - 298.15).abs() < 1e-9);
+        assert_eq!(normalized.unit.iri(), Some("http://qudt.org/vocab/unit/K"));
+
+        let measurement = Measurement { value: 2.5, unit: unit("min") };
+        let normalized = measurement.normalized().unwrap();
+        assert_eq!(normalized.value, 150.0);
+        assert_eq!(normalized.unit.iri(), Some("http://qudt.org/vocab/unit/SEC"));
+    }
+
     #[test]
     fn test_observation_to_triples() -> anyhow::Result<()> {
         let observation = Observation {
             value: 42.0,
-            unit: Unit::DegC,
-            error_margin: Some(ErrorMargin { value: 0.5, unit: Unit::DegC }),
+            unit: unit("°C"),
+            error_margin: Some(ErrorMargin { value: 0.5, unit: unit("°C") }),
         };
 
         let mut b = GraphBuilder::new();
@@ -1,102 +1,16 @@
-use crate::graph::namespaces::{cat, qudtext, unit};
+use crate::graph::{
+    from_graph::{iri_of, FromGraph},
+    insert_into::{literal_error, InsertIntoGraph, Link},
+    namespaces::cat,
+};
 use serde::{Deserialize, Serialize};
-use sophia::api::ns::Namespace;
-use sophia_api::ns::NsTerm;
+use sophia::inmem::graph::LightGraph;
+use sophia_api::{
+    ns::NsTerm,
+    term::{SimpleTerm, Term},
+};
 use std::fmt;
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
-#[allow(non_snake_case, non_camel_case_types)]
-pub enum Unit {
-    #[serde(rename = "bar")]
-    Bar,
-    #[serde(rename = "°C")]
-    DegC,
-    #[serde(rename = "mg")]
-    MilliGM,
-    #[serde(rename = "g/mL")]
-    GMPerMilliL,
-    #[serde(rename = "g/mol")]
-    GMPerMol,
-    #[serde(rename = "mol/L")]
-    MolPerL,
-    #[serde(rename = "rpm")]
-    RevPerMin,
-    #[serde(rename = "mm^3")]
-    MilliM3,
-    #[serde(rename = "nM")]
-    NanoM,
-    #[serde(rename = "s")]
-    SEC,
-    #[serde(rename = "min")]
-    MIN,
-    #[serde(rename = "%")]
-    PERCENT,
-    #[serde(alias = "unitless", alias = "(unitless)")]
-    UNITLESS,
-    #[serde(rename = "Counts.s")]
-    CountsPerSec,
-    #[serde(rename = "mAU")]
-    mAU,
-    #[serde(rename = "mAU.s")]
-    mAUs,
-}
-
-impl Unit {
-    pub fn display_name(&self) -> &'static str {
-        match self {
-            Unit::Bar => "Bar",
-            Unit::DegC => "DEG-C",
-            Unit::MilliGM => "MilliGM",
-            Unit::GMPerMilliL => "GM-PER-MilliL",
-            Unit::GMPerMol => "GM-PER-MOL",
-            Unit::MolPerL => "MOL-PER-L",
-            Unit::RevPerMin => "REV-PER-MIN",
-            Unit::MilliM3 => "MilliM3",
-            Unit::SEC => "SEC",
-            Unit::MIN => "MIN",
-            Unit::UNITLESS => "UNITLESS",
-            Unit::PERCENT => "PERCENT",
-            Unit::CountsPerSec => "NUM-PER-SEC",
-            Unit::NanoM => "NanoM",
-            Unit::mAU => "MilliAbsorbanceUnit",
-            Unit::mAUs => "MilliAbsorbanceUnitTimesSecond",
-        }
-    }
-
-    /// Determines whether the unit belongs to qudt or allotrope qudt-ext.
-    fn namespace(&self) -> &Namespace<&'static str> {
-        match self {
-            // Standard QUDT units
-            Unit::Bar
-            | Unit::DegC
-            | Unit::MilliGM
-            | Unit::GMPerMilliL
-            | Unit::GMPerMol
-            | Unit::MolPerL
-            | Unit::RevPerMin
-            | Unit::MilliM3
-            | Unit::SEC
-            | Unit::MIN
-            | Unit::PERCENT
-            | Unit::NanoM
-            | Unit::UNITLESS
-            | Unit::CountsPerSec => &unit::ns,
-
-            // QUDT-EXT units
-            Unit::mAU | Unit::mAUs => &qudtext::ns,
-        }
-    }
-    pub fn iri(&self) -> NsTerm {
-        self.namespace().get(self.display_name()).expect("Term not found")
-    }
-}
-
-impl fmt::Display for Unit {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "<{}>", self.iri().to_string())
-    }
-}
-
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[allow(non_snake_case, non_camel_case_types)]
 pub enum ActionName {
@@ -125,4 +39,40 @@ impl ActionName {
             Self::filtrateAction => cat::FiltrateAction,
         }
     }
+
+    /// Returns the action name whose class is `iri`, if any.
+    pub fn from_iri(iri: &str) -> Option<ActionName> {
+        [
+            Self::AddAction,
+            Self::setTemperatureAction,
+            Self::filtrateAction,
+            Self::shakeAction,
+            Self::setVacuumAction,
+            Self::setPressureAction,
+        ]
+        .into_iter()
+        .find(|action| action.iri().iriref().as_str() == iri)
+    }
+}
+
+/// Action names are attached as the IRI of the action class.
+impl InsertIntoGraph for ActionName {
+    fn insert_into(&self, _graph: &mut LightGraph, _iri: SimpleTerm) -> anyhow::Result<()> {
+        Err(literal_error(self))
+    }
+
+    fn attach_into(&self, graph: &mut LightGraph, attach: Link) -> anyhow::Result<()> {
+        self.iri().as_simple().attach_into(graph, attach)
+    }
+
+    fn is_node(&self) -> bool {
+        false
+    }
+}
+
+impl FromGraph for ActionName {
+    fn from_graph(_graph: &LightGraph, iri: &SimpleTerm) -> anyhow::Result<Self> {
+        let iri = iri_of(iri)?;
+        ActionName::from_iri(&iri).ok_or_else(|| anyhow::anyhow!("Unknown action <{}>", iri))
+    }
 }
@@ -1,6 +1,8 @@
 use crate::{
+    error::{RecordArray, Records},
     graph::{
-        insert_into::{InsertIntoGraph, Link},
+        from_graph::FromGraph,
+        insert_into::InsertIntoGraph,
         namespaces::{allocom, allohdf, allores, cat, obo, purl, schema},
     },
     models::core::Chemical,
@@ -8,76 +10,50 @@ use crate::{
 
 use anyhow;
 use serde::{Deserialize, Serialize};
-use sophia::{api::ns::rdf, inmem::graph::LightGraph};
-use sophia_api::term::{SimpleTerm, Term};
+use sophia::inmem::graph::LightGraph;
+use sophia_api::term::SimpleTerm;
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, InsertIntoGraph, FromGraph)]
+#[rdf(type = "cat::Campaign")]
 #[serde(rename_all = "camelCase")]
 pub struct Campaign {
+    #[rdf(pred = "schema::name")]
     pub campaign_name: String,
+    #[rdf(pred = "schema::description")]
     pub description: String,
     #[serde(rename = "objective")]
+    #[rdf(pred = "cat::genericObjective")]
     pub generic_objective: String,
+    #[rdf(pred = "cat::campaignClass")]
     pub campaign_class: String,
     #[serde(rename = "type")]
+    #[rdf(pred = "cat::campaignType")]
     pub campaign_type: String,
+    #[rdf(pred = "allores::AFR_0002764")]
     pub reference: String,
+    #[rdf(pred = "cat::hasObjective")]
     pub has_objective: Option<Objective>,
+    #[rdf(pred = "cat::hasBatch")]
     pub has_batch: HciBatch,
+    #[rdf(pred = "cat::hasChemical")]
     pub has_chemical: Option<Vec<Chemical>>,
 }
 
-impl InsertIntoGraph for Campaign {
-    fn insert_into(&self, graph: &mut LightGraph, iri: SimpleTerm) -> anyhow::Result<()> {
-        for (pred, value) in [
-            (rdf::type_, &cat::Campaign.as_simple() as &dyn InsertIntoGraph),
-            (schema::name, &self.campaign_name.as_simple()),
-            (schema::description, &self.description.as_simple()),
-            (cat::genericObjective, &self.generic_objective.as_simple()),
-            (cat::campaignClass, &self.campaign_class.as_simple()),
-            (cat::campaignType, &self.campaign_type.as_simple()),
-            (allores::AFR_0002764, &self.reference.as_simple()),
-            (cat::hasObjective, &self.has_objective),
-            (cat::hasBatch, &self.has_batch),
-            (cat::hasChemical, &self.has_chemical),
-        ] {
-            value.attach_into(
-                graph,
-                Link { source_iri: iri.clone(), pred: pred.as_simple(), target_iri: None },
-            )?;
-        }
-        Ok(())
-    }
-}
-
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, InsertIntoGraph, FromGraph)]
+#[rdf(type = "obo::IAO_0000005")]
 #[serde(rename_all = "camelCase")]
 pub struct Objective {
+    #[rdf(pred = "cat::criteria")]
     pub criteria: String,
+    #[rdf(pred = "allocom::AFC_0000090")]
     pub condition: String,
+    #[rdf(pred = "schema::description")]
     pub description: String,
+    #[rdf(pred = "schema::name")]
     pub objective_name: String,
 }
 
-impl InsertIntoGraph for Objective {
-    fn insert_into(&self, graph: &mut LightGraph, iri: SimpleTerm) -> anyhow::Result<()> {
-        for (pred, value) in [
-            (rdf::type_, &obo::IAO_0000005.as_simple()),
-            (schema::name, &self.objective_name.as_simple()),
-            (schema::description, &self.description.as_simple()),
-            (cat::criteria, &self.criteria.as_simple()),
-            (allocom::AFC_0000090, &self.condition.as_simple()),
-        ] {
-            value.attach_into(
-                graph,
-                Link { source_iri: iri.clone(), pred: pred.as_simple(), target_iri: None },
-            )?;
-        }
-        Ok(())
-    }
-}
-
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 pub struct CampaignWrapper {
     #[serde(rename = "hasCampaign")]
     pub has_campaign: Campaign,
@@ -88,39 +64,38 @@ impl InsertIntoGraph for CampaignWrapper {
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+impl Records for CampaignWrapper {
+    fn records() -> Vec<RecordArray> {
+        vec![RecordArray::of::<Chemical>("/hasCampaign/hasChemical")]
+    }
+}
+
+impl FromGraph for CampaignWrapper {
+    fn from_graph(graph: &LightGraph, iri: &SimpleTerm) -> anyhow::Result<Self> {
+        Ok(CampaignWrapper { has_campaign: Campaign::from_graph(graph, iri)? })
+    }
+
+    fn from_root(graph: &LightGraph) -> anyhow::Result<Self> {
+        Ok(CampaignWrapper { has_campaign: Campaign::from_root(graph)? })
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, InsertIntoGraph, FromGraph)]
+#[rdf(type = "cat::Batch")]
 #[serde(rename_all = "camelCase")]
 #[serde(rename = "Batch")]
 pub struct HciBatch {
     #[serde(rename = "batchID")]
+    #[rdf(pred = "purl::identifier")]
     pub batch_id: String,
+    #[rdf(pred = "schema::name")]
     pub batch_name: Option<String>,
+    #[rdf(pred = "cat::reactionType")]
     pub reaction_type: Option<String>,
+    #[rdf(pred = "cat::reactionName")]
     pub reaction_name: Option<String>,
+    #[rdf(pred = "cat::optimizationType")]
     pub optimization_type: Option<String>,
+    #[rdf(pred = "allohdf::HardLink")]
     pub link: Option<String>,
 }
-
-impl InsertIntoGraph for HciBatch {
-    fn insert_into(&self, graph: &mut LightGraph, iri: SimpleTerm) -> anyhow::Result<()> {
-        for (pred, value) in [
-            (rdf::type_, &cat::Batch.as_simple() as &dyn InsertIntoGraph),
-            (purl::identifier, &self.batch_id.as_simple()),
-            (schema::name, &self.batch_name.as_ref().clone().map(|s| s.as_simple())),
-            (allohdf::HardLink, &self.link.as_ref().clone().map(|s| s.as_simple())),
-            (cat::reactionType, &self.reaction_type.as_ref().clone().map(|s| s.as_simple())),
-            (cat::reactionName, &self.reaction_name.as_ref().clone().map(|s| s.as_simple())),
-            (
-                cat::optimizationType,
-                &self.optimization_type.as_ref().clone().map(|s| s.as_simple()),
-            ),
-        ] {
-            value.attach_into(
-                graph,
-                Link { source_iri: iri.clone(), pred: pred.as_simple(), target_iri: None },
-            )?;
-        }
-
-        Ok(())
-    }
-}
@@ -2,6 +2,7 @@ pub mod agilent;
 pub mod core;
 pub mod enums;
 pub mod hci;
+pub mod provenance;
 pub mod synth;
 
 // Re-export all models;
@@ -9,4 +10,5 @@ pub use agilent::*;
 pub use core::*;
 pub use enums::*;
 pub use hci::*;
+pub use provenance::*;
 pub use synth::*;
@@ -0,0 +1,63 @@
+use crate::graph::{
+    insert_into::InsertIntoGraph,
+    namespaces::{cat, prov, schema},
+};
+use sha2::{Digest, Sha256};
+use sophia::api::ns::xsd;
+
+/// A conversion, recorded as a `prov:Activity` that the converted documents were generated by.
+#[derive(Clone, Debug, InsertIntoGraph)]
+#[rdf(type = "prov::Activity")]
+pub struct ConversionActivity {
+    #[rdf(pred = "prov::startedAtTime", datatype = "xsd::dateTime")]
+    pub started_at_time: String,
+    #[rdf(pred = "prov::used")]
+    pub input: InputDocument,
+    #[rdf(pred = "prov::wasAssociatedWith")]
+    pub converter: SoftwareAgent,
+}
+
+/// The input document of a conversion.
+#[derive(Clone, Debug, InsertIntoGraph)]
+#[rdf(type = "prov::Entity")]
+pub struct InputDocument {
+    /// The path of the input file, as given to the converter.
+    #[rdf(pred = "schema::name")]
+    pub name: Option<String>,
+    #[rdf(pred = "schema::sha256")]
+    pub sha256: String,
+    /// The input type used for the conversion, e.g. `Synth`.
+    #[rdf(pred = "cat::inputType")]
+    pub input_type: Option<String>,
+}
+
+/// The software that ran a conversion.
+#[derive(Clone, Debug, InsertIntoGraph)]
+#[rdf(type = "prov::SoftwareAgent")]
+pub struct SoftwareAgent {
+    #[rdf(pred = "schema::name")]
+    pub name: String,
+    #[rdf(pred = "schema::softwareVersion")]
+    pub software_version: String,
+}
+
+impl ConversionActivity {
+    /// Describes the conversion of `input_content`, started now.
+    pub fn new(
+        input_content: &str,
+        name: Option<String>,
+        input_type: Option<String>,
+        converter: SoftwareAgent,
+    ) -> Self {
+        ConversionActivity {
+            started_at_time: chrono::Utc::now()
+                .to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            input: InputDocument {
+                name,
+                sha256: format!("{:x}", Sha256::digest(input_content.as_bytes())),
+                input_type,
+            },
+            converter,
+        }
+    }
+}
@@ -1,110 +1,79 @@
 use crate::{
+    error::{RecordArray, Records},
     graph::{
-        insert_into::{InsertIntoGraph, Link},
+        from_graph::FromGraph,
+        insert_into::InsertIntoGraph,
         namespaces::{alloproc, alloqual, allores, cat, purl},
     },
     models::{
-        core::{Observation, Plate, Sample, Well},
+        core::{Observation, Plate, Sample, SampleItem, Well},
         enums::ActionName,
     },
 };
-use anyhow;
 use serde::{Deserialize, Serialize};
-use sophia::{
-    api::ns::{rdf, xsd},
-    inmem::graph::LightGraph,
-};
-use sophia_api::{
-    graph::MutableGraph,
-    term::{SimpleTerm, Term},
-};
+use sophia::api::ns::{rdf, xsd};
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, InsertIntoGraph, FromGraph)]
+#[rdf(type = "cat::Batch")]
 #[serde(rename_all = "camelCase")]
 #[serde(rename = "Batch")]
 pub struct SynthBatch {
     #[serde(rename = "batchID")]
+    #[rdf(pred = "purl::identifier")]
     pub batch_id: String,
+    // NOTE: for actions, the direction is reversed (action hasbatch batch)
     #[serde(rename = "Actions")]
+    #[rdf(pred = "cat::hasBatch", reverse)]
     pub actions: Option<Vec<SynthAction>>,
 }
 
-impl InsertIntoGraph for SynthBatch {
-    fn insert_into(&self, graph: &mut LightGraph, iri: SimpleTerm) -> anyhow::Result<()> {
-        for (pred, value) in [
-            (rdf::type_, &cat::Batch.as_simple() as &dyn InsertIntoGraph),
-            (purl::identifier, &self.batch_id.as_simple()),
-        ] {
-            value.attach_into(
-                graph,
-                Link { source_iri: iri.clone(), pred: pred.as_simple(), target_iri: None },
-            )?;
-        }
-
-        // NOTE: for actions, the direction is reversed (action hasbatch batch)
-        if let Some(actions) = &self.actions {
-            for action in actions {
-                let action_uri = action.get_uri();
-                graph.insert(&action_uri, cat::hasBatch.as_simple(), iri.clone())?;
-                action.insert_into(graph, action_uri)?;
-            }
-        }
-
-        Ok(())
+impl Records for SynthBatch {
+    fn records() -> Vec<RecordArray> {
+        vec![
+            RecordArray::of::<SynthAction>("/Actions"),
+            RecordArray::of::<Well>("/Actions/*/hasWell"),
+            RecordArray::of::<SampleItem>("/Actions/*/hasSample/hasSample"),
+        ]
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, InsertIntoGraph, FromGraph)]
 #[serde(rename_all = "camelCase")]
 #[serde(rename = "Action")]
 pub struct SynthAction {
+    #[rdf(pred = "rdf::type_")]
     pub action_name: ActionName,
+    #[rdf(pred = "allores::AFX_0000622", datatype = "xsd::dateTime")]
     pub start_time: String,
+    #[rdf(pred = "allores::AFR_0002423", datatype = "xsd::dateTime")]
     pub ending_time: String,
+    #[rdf(pred = "allores::AFR_0001606")]
     pub method_name: String,
+    #[rdf(pred = "allores::AFR_0001723")]
     pub equipment_name: String,
+    #[rdf(pred = "cat::subEquipmentName")]
     pub sub_equipment_name: String,
     #[serde(flatten)]
+    #[rdf(pred = "cat::hasPlate")]
     pub has_plate: Option<Plate>,
+    #[rdf(pred = "cat::speedInRPM")]
     pub speed_shaker: Option<Observation>,
+    #[rdf(pred = "cat::hasWell")]
     pub has_well: Option<Vec<Well>>,
+    #[rdf(pred = "alloqual::AFQ_0000111")]
     pub dispense_state: Option<String>,
+    #[rdf(pred = "cat::dispenseType")]
     pub dispense_type: Option<String>,
+    #[rdf(pred = "cat::hasSample")]
     pub has_sample: Option<Sample>,
+    #[rdf(pred = "cat::speedTumbleStirrerShape")]
     pub speed_tumble_stirrer: Option<Observation>,
+    #[rdf(pred = "cat::temperatureTumbleStirrerShape")]
     pub temperature_tumble_stirrer: Option<Observation>,
+    #[rdf(pred = "cat::temperatureShakerShape")]
     pub temperature_shaker: Option<Observation>,
+    #[rdf(pred = "alloproc::AFP_0002677")]
     pub pressure_measurement: Option<Observation>,
+    #[rdf(pred = "cat::vacuum")]
     pub vacuum: Option<Observation>,
 }
-
-impl InsertIntoGraph for SynthAction {
-    fn insert_into(&self, graph: &mut LightGraph, iri: SimpleTerm) -> anyhow::Result<()> {
-        for (pred, value) in [
-            (rdf::type_, &self.action_name.iri().as_simple() as &dyn InsertIntoGraph),
-            (allores::AFX_0000622, &(self.start_time.as_str() * xsd::dateTime).as_simple()),
-            (allores::AFR_0002423, &(self.ending_time.as_str() * xsd::dateTime).as_simple()),
-            (allores::AFR_0001606, &self.method_name.as_simple()),
-            (allores::AFR_0001723, &self.equipment_name.as_simple()),
-            (cat::subEquipmentName, &self.sub_equipment_name.as_simple()),
-            (cat::speedInRPM, &self.speed_shaker),
-            (cat::temperatureTumbleStirrerShape, &self.temperature_tumble_stirrer),
-            (cat::speedTumbleStirrerShape, &self.speed_tumble_stirrer),
-            (cat::vacuum, &self.vacuum),
-            (cat::temperatureShakerShape, &self.temperature_shaker),
-            (alloproc::AFP_0002677, &self.pressure_measurement),
-            (cat::hasSample, &self.has_sample),
-            (cat::hasWell, &self.has_well),
-            (cat::hasPlate, &self.has_plate),
-            (alloqual::AFQ_0000111, &self.dispense_state.as_ref().clone().map(|s| s.as_simple())),
-            (cat::dispenseType, &self.dispense_type.as_ref().clone().map(|s| s.as_simple())),
-        ] {
-            value.attach_into(
-                graph,
-                Link { source_iri: iri.clone(), pred: pred.as_simple(), target_iri: None },
-            )?;
-        }
-
-        Ok(())
-    }
-}
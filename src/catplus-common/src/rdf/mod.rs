@@ -1,2 +1,3 @@
+pub mod rdf_format;
 pub mod rdf_parser;
 pub mod rdf_serializers;
@@ -0,0 +1,71 @@
+use serde::Deserialize;
+use std::path::Path;
+
+// Derive Deserialize and ValueEnum
+#[derive(Deserialize, Debug, clap::ValueEnum, Clone, Copy, PartialEq, Eq)]
+pub enum RdfFormat {
+    #[value(alias = "ttl")]
+    Turtle,
+    Jsonld,
+    #[value(name = "ntriples", alias = "nt")]
+    NTriples,
+    #[value(name = "nquads", alias = "nq")]
+    NQuads,
+    Trig,
+    #[value(name = "rdfxml", alias = "rdf")]
+    RdfXml,
+}
+
+impl RdfFormat {
+    /// File extension of the serialization format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            RdfFormat::Turtle => "ttl",
+            RdfFormat::Jsonld => "jsonld",
+            RdfFormat::NTriples => "nt",
+            RdfFormat::NQuads => "nq",
+            RdfFormat::Trig => "trig",
+            RdfFormat::RdfXml => "rdf",
+        }
+    }
+
+    /// Guesses the serialization format of a file from its extension.
+    pub fn from_path(path: &Path) -> Option<RdfFormat> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "ttl" => Some(RdfFormat::Turtle),
+            "jsonld" | "json" => Some(RdfFormat::Jsonld),
+            "nt" => Some(RdfFormat::NTriples),
+            "nq" => Some(RdfFormat::NQuads),
+            "trig" => Some(RdfFormat::Trig),
+            "rdf" | "xml" | "owl" => Some(RdfFormat::RdfXml),
+            _ => None,
+        }
+    }
+
+    /// Media type of the serialization format, e.g. for the `Content-Type` of HTTP responses.
+    pub fn media_type(&self) -> &'static str {
+        match self {
+            RdfFormat::Turtle => "text/turtle",
+            RdfFormat::Jsonld => "application/ld+json",
+            RdfFormat::NTriples => "application/n-triples",
+            RdfFormat::NQuads => "application/n-quads",
+            RdfFormat::Trig => "application/trig",
+            RdfFormat::RdfXml => "application/rdf+xml",
+        }
+    }
+
+    /// The serialization format of a media type, ignoring its parameters (e.g. `charset`).
+    pub fn from_media_type(media_type: &str) -> Option<RdfFormat> {
+        let media_type = media_type.split(';').next()?.trim().to_lowercase();
+        match media_type.as_str() {
+            "text/turtle" => Some(RdfFormat::Turtle),
+            "application/ld+json" => Some(RdfFormat::Jsonld),
+            "application/n-triples" => Some(RdfFormat::NTriples),
+            "application/n-quads" => Some(RdfFormat::NQuads),
+            "application/trig" => Some(RdfFormat::Trig),
+            "application/rdf+xml" => Some(RdfFormat::RdfXml),
+            _ => None,
+        }
+    }
+}
@@ -1,7 +1,13 @@
+use crate::rdf::rdf_format::RdfFormat;
 use anyhow::Result;
-use sophia::inmem::graph::LightGraph;
-use sophia_api::{parser::TripleParser, prelude::TripleSource};
-use sophia_turtle::parser::turtle::TurtleParser;
+use sophia::{inmem::graph::LightGraph, jsonld::JsonLdParser, xml::parser::RdfXmlParser};
+use sophia_api::{
+    parser::{QuadParser, TripleParser},
+    prelude::{QuadSource, TripleSource},
+};
+use sophia_turtle::parser::{
+    nq::NQuadsParser, nt::NTriplesParser, trig::TriGParser, turtle::TurtleParser,
+};
 
 /// Parses a Turtle string into an RDF graph.
 ///
@@ -22,3 +28,127 @@ pub fn parse_turtle_to_graph(turtle_input: &str) -> Result<LightGraph> {
 
     Ok(graph)
 }
+
+/// Parses an N-Triples string into an RDF graph.
+pub fn parse_ntriples_to_graph(ntriples_input: &str) -> Result<LightGraph> {
+    let mut graph = LightGraph::new();
+
+    NTriplesParser {}
+        .parse_str(ntriples_input)
+        .add_to_graph(&mut graph)
+        .map_err(|e| anyhow::anyhow!("Failed to parse N-Triples input: {}", e))?;
+
+    Ok(graph)
+}
+
+/// Parses an N-Quads string into an RDF graph, the triples of all graphs are merged.
+pub fn parse_nquads_to_graph(nquads_input: &str) -> Result<LightGraph> {
+    let mut graph = LightGraph::new();
+
+    NQuadsParser {}
+        .parse_str(nquads_input)
+        .to_triples()
+        .add_to_graph(&mut graph)
+        .map_err(|e| anyhow::anyhow!("Failed to parse N-Quads input: {}", e))?;
+
+    Ok(graph)
+}
+
+/// Parses a TriG string into an RDF graph, the triples of all graphs are merged.
+pub fn parse_trig_to_graph(trig_input: &str) -> Result<LightGraph> {
+    let mut graph = LightGraph::new();
+
+    TriGParser::default()
+        .parse_str(trig_input)
+        .to_triples()
+        .add_to_graph(&mut graph)
+        .map_err(|e| anyhow::anyhow!("Failed to parse TriG input: {}", e))?;
+
+    Ok(graph)
+}
+
+/// Parses a JSON-LD string into an RDF graph, the triples of all graphs are merged.
+///
+/// Remote contexts are not loaded, the context must be embedded in the document.
+pub fn parse_jsonld_to_graph(jsonld_input: &str) -> Result<LightGraph> {
+    let mut graph = LightGraph::new();
+
+    JsonLdParser::new()
+        .parse_str(jsonld_input)
+        .to_triples()
+        .add_to_graph(&mut graph)
+        .map_err(|e| anyhow::anyhow!("Failed to parse JSON-LD input: {}", e))?;
+
+    Ok(graph)
+}
+
+/// Parses an RDF/XML string into an RDF graph.
+pub fn parse_rdfxml_to_graph(rdfxml_input: &str) -> Result<LightGraph> {
+    let mut graph = LightGraph::new();
+
+    RdfXmlParser::default()
+        .parse_str(rdfxml_input)
+        .add_to_graph(&mut graph)
+        .map_err(|e| anyhow::anyhow!("Failed to parse RDF/XML input: {}", e))?;
+
+    Ok(graph)
+}
+
+/// Parses RDF in the given serialization format into a graph.
+///
+/// Without a format, it is detected from the content: JSON-LD for JSON documents, RDF/XML for
+/// XML documents, otherwise TriG (which covers Turtle and N-Triples) and then N-Quads. Input
+/// that only looks like JSON or XML, e.g. Turtle starting with `[` or N-Triples starting with
+/// `<urn:a>`, is parsed as TriG or N-Quads if it is not valid JSON-LD or RDF/XML.
+pub fn parse_rdf_to_graph(input: &str, format: Option<&RdfFormat>) -> Result<LightGraph> {
+    match format {
+        Some(RdfFormat::Turtle) => parse_turtle_to_graph(input),
+        Some(RdfFormat::Jsonld) => parse_jsonld_to_graph(input),
+        Some(RdfFormat::NTriples) => parse_ntriples_to_graph(input),
+        Some(RdfFormat::NQuads) => parse_nquads_to_graph(input),
+        Some(RdfFormat::Trig) => parse_trig_to_graph(input),
+        Some(RdfFormat::RdfXml) => parse_rdfxml_to_graph(input),
+        None => {
+            let content = input.trim_start_matches('\u{feff}').trim_start();
+            let sniffed = if content.starts_with('{') || content.starts_with('[') {
+                Some(parse_jsonld_to_graph(input))
+            } else if looks_like_xml(content) {
+                Some(parse_rdfxml_to_graph(input))
+            } else {
+                None
+            };
+            match sniffed {
+                Some(Ok(graph)) => Ok(graph),
+                Some(Err(error)) => parse_trig_or_nquads(input).map_err(|_| error),
+                None => parse_trig_or_nquads(input).map_err(|error| {
+                    error.context("Could not detect the RDF format, the input was parsed as TriG")
+                }),
+            }
+        }
+    }
+}
+
+/// Parses TriG (which covers Turtle and N-Triples), then N-Quads, and returns the TriG error
+/// if both fail.
+fn parse_trig_or_nquads(input: &str) -> Result<LightGraph> {
+    parse_trig_to_graph(input).or_else(|error| parse_nquads_to_graph(input).map_err(|_| error))
+}
+
+/// Whether the content starts with an XML declaration, comment or element, as opposed to an
+/// IRI such as `<http://example.org/>` in N-Triples or Turtle.
+fn looks_like_xml(content: &str) -> bool {
+    let Some(rest) = content.strip_prefix('<') else {
+        return false;
+    };
+    if rest.starts_with('?') || rest.starts_with('!') {
+        return true;
+    }
+    let name_length = rest
+        .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | ':')))
+        .unwrap_or(rest.len());
+    let name = &rest[..name_length];
+    let next = rest[name_length..].chars().next();
+    !name.is_empty()
+        && !name.ends_with(':')
+        && matches!(next, Some(c) if c.is_whitespace() || c == '>' || c == '/')
+}
@@ -1,14 +1,22 @@
-use crate::graph::prefix_map::generate_prefix_map;
-use anyhow::{Context, Result};
+use crate::{graph::prefix_map::generate_prefix_map, rdf::rdf_format::RdfFormat};
+use anyhow::{bail, Context, Result};
 use sophia::{
     api::{
         prelude::*,
-        serializer::{Stringifier, TripleSerializer},
+        serializer::{QuadSerializer, Stringifier, TripleSerializer},
+        term::{BnodeId, SimpleTerm},
     },
-    inmem::graph::LightGraph,
+    inmem::{dataset::LightDataset, graph::LightGraph},
     jsonld::{serializer::JsonLdSerializer, JsonLdOptions},
+    xml::serializer::{RdfXmlConfig, RdfXmlSerializer},
 };
-use sophia_turtle::serializer::turtle::{TurtleConfig, TurtleSerializer};
+use sophia_turtle::serializer::{
+    nq::NqSerializer,
+    nt::NtSerializer,
+    trig::{TrigConfig, TrigSerializer},
+    turtle::{TurtleConfig, TurtleSerializer},
+};
+use std::io;
 
 /// Serialize an RDF graph to Turtle format
 ///
@@ -29,6 +37,40 @@ pub fn serialize_graph_to_turtle(graph: &LightGraph) -> Result<String> {
     Ok(serializer.as_str().to_string())
 }
 
+/// Writes RDF graphs one after the other to `W`, e.g. one graph per measurement document of a
+/// large input, so that the output is never held in memory.
+///
+/// Only N-Triples and Turtle can be written this way. Turtle is written without prefixes nor
+/// nesting of blank nodes, which would require the whole graph.
+pub enum StreamingSerializer<W: io::Write> {
+    NTriples(NtSerializer<W>),
+    Turtle(TurtleSerializer<W>),
+}
+
+impl<W: io::Write> StreamingSerializer<W> {
+    /// Creates a serializer writing to `write` in `format`, N-Triples or Turtle.
+    pub fn new(write: W, format: &RdfFormat) -> Result<Self> {
+        match format {
+            RdfFormat::NTriples => Ok(StreamingSerializer::NTriples(NtSerializer::new(write))),
+            RdfFormat::Turtle => Ok(StreamingSerializer::Turtle(TurtleSerializer::new(write))),
+            format => bail!("{:?} cannot be written as a stream, use NTriples or Turtle", format),
+        }
+    }
+
+    /// Writes the triples of `graph` after those already written.
+    pub fn serialize_graph(&mut self, graph: &LightGraph) -> Result<()> {
+        match self {
+            StreamingSerializer::NTriples(serializer) => {
+                serializer.serialize_graph(graph).context("Failed to write N-Triples")?;
+            }
+            StreamingSerializer::Turtle(serializer) => {
+                serializer.serialize_graph(graph).context("Failed to write Turtle")?;
+            }
+        }
+        Ok(())
+    }
+}
+
 /// Serialize an RDF graph to JSON-LD format
 ///
 /// # Parameters
@@ -48,3 +90,115 @@ pub fn serialize_graph_to_jsonld(graph: &LightGraph) -> Result<String> {
     // Extract the JSON-LD string from the serializer
     Ok(serializer.as_str().to_string())
 }
+
+/// Serialize an RDF graph to N-Triples format
+///
+/// # Parameters
+/// - `graph`: A reference to the graph to be serialized.
+///
+/// # Returns
+/// A `Result` containing the N-Triples serialization as a `String`, or an error if serialization fails.
+pub fn serialize_graph_to_ntriples(graph: &LightGraph) -> Result<String> {
+    let mut serializer = NtSerializer::new_stringifier();
+
+    serializer.serialize_graph(graph).context("Failed to serialize graph to N-Triples")?;
+
+    Ok(serializer.as_str().to_string())
+}
+
+/// Serialize an RDF graph to N-Quads format, all triples are in the default graph.
+///
+/// # Parameters
+/// - `graph`: A reference to the graph to be serialized.
+///
+/// # Returns
+/// A `Result` containing the N-Quads serialization as a `String`, or an error if serialization fails.
+pub fn serialize_graph_to_nquads(graph: &LightGraph) -> Result<String> {
+    let mut serializer = NqSerializer::new_stringifier();
+
+    serializer
+        .serialize_quads(graph.triples().to_quads())
+        .context("Failed to serialize graph to N-Quads")?;
+
+    Ok(serializer.as_str().to_string())
+}
+
+/// Serialize an RDF graph to TriG format, all triples are in the default graph.
+///
+/// # Parameters
+/// - `graph`: A reference to the graph to be serialized.
+///
+/// # Returns
+/// A `Result` containing the TriG serialization as a `String`, or an error if serialization fails.
+pub fn serialize_graph_to_trig(graph: &LightGraph) -> Result<String> {
+    let config = TrigConfig::default().with_pretty(true).with_own_prefix_map(generate_prefix_map());
+
+    let mut serializer = TrigSerializer::new_stringifier_with_config(config);
+
+    serializer
+        .serialize_quads(graph.triples().to_quads())
+        .context("Failed to serialize graph to TriG")?;
+
+    Ok(serializer.as_str().to_string())
+}
+
+/// Serialize an RDF dataset to N-Quads format, keeping the named graphs.
+///
+/// # Parameters
+/// - `dataset`: A reference to the dataset to be serialized.
+///
+/// # Returns
+/// A `Result` containing the N-Quads serialization as a `String`, or an error if serialization fails.
+pub fn serialize_dataset_to_nquads(dataset: &LightDataset) -> Result<String> {
+    let mut serializer = NqSerializer::new_stringifier();
+
+    serializer.serialize_dataset(dataset).context("Failed to serialize dataset to N-Quads")?;
+
+    Ok(serializer.as_str().to_string())
+}
+
+/// Serialize an RDF dataset to TriG format, keeping the named graphs.
+///
+/// # Parameters
+/// - `dataset`: A reference to the dataset to be serialized.
+///
+/// # Returns
+/// A `Result` containing the TriG serialization as a `String`, or an error if serialization fails.
+pub fn serialize_dataset_to_trig(dataset: &LightDataset) -> Result<String> {
+    let config = TrigConfig::default().with_pretty(true).with_own_prefix_map(generate_prefix_map());
+
+    let mut serializer = TrigSerializer::new_stringifier_with_config(config);
+
+    serializer.serialize_dataset(dataset).context("Failed to serialize dataset to TriG")?;
+
+    Ok(serializer.as_str().to_string())
+}
+
+/// Serialize an RDF graph to RDF/XML format
+///
+/// # Parameters
+/// - `graph`: A reference to the graph to be serialized.
+///
+/// # Returns
+/// A `Result` containing the RDF/XML serialization as a `String`, or an error if serialization fails.
+pub fn serialize_graph_to_rdfxml(graph: &LightGraph) -> Result<String> {
+    let config = RdfXmlConfig::new().with_indentation(2);
+
+    let mut serializer = RdfXmlSerializer::new_stringifier_with_config(config);
+
+    // Blank node identifiers are UUIDs, which may start with a digit: prefix them to make them
+    // valid `rdf:nodeID` values (XML names).
+    let triples = graph.triples().map(|triple| {
+        triple.map(|triple| {
+            triple.map(|term| match term.bnode_id() {
+                Some(id) => SimpleTerm::BlankNode(BnodeId::new_unchecked(
+                    format!("b{}", id.as_str()).into(),
+                )),
+                None => term.into_term(),
+            })
+        })
+    });
+    serializer.serialize_triples(triples).context("Failed to serialize graph to RDF/XML")?;
+
+    Ok(serializer.as_str().to_string())
+}
@@ -0,0 +1,321 @@
+//! Parsing of UCUM-style unit expressions, e.g. `mg/mL`, `mAU.s`, `m^3` or `s-1`.
+//!
+//! See https://ucum.org/ucum for the syntax: terms are multiplied with `.` and divided with
+//! `/` from left to right, and each term is an optionally prefixed unit symbol followed by an
+//! exponent. Parentheses and numeric factors other than `1` are not supported.
+use anyhow::{bail, Result};
+use std::{f64::consts::PI, fmt, str::FromStr};
+
+/// Exponents of the base dimensions of a unit: length, mass, time, temperature,
+/// amount of substance and angle.
+pub type Dimension = [i8; 6];
+
+const DIMENSIONLESS: Dimension = [0, 0, 0, 0, 0, 0];
+const LENGTH: Dimension = [1, 0, 0, 0, 0, 0];
+const MASS: Dimension = [0, 1, 0, 0, 0, 0];
+const TIME: Dimension = [0, 0, 1, 0, 0, 0];
+const TEMPERATURE: Dimension = [0, 0, 0, 1, 0, 0];
+const AMOUNT: Dimension = [0, 0, 0, 0, 1, 0];
+const ANGLE: Dimension = [0, 0, 0, 0, 0, 1];
+const VOLUME: Dimension = [3, 0, 0, 0, 0, 0];
+const PRESSURE: Dimension = [-1, 1, -2, 0, 0, 0];
+const FREQUENCY: Dimension = [0, 0, -1, 0, 0, 0];
+
+/// A unit symbol, with its factor (and offset) to the SI unit of the same dimension.
+struct Atom {
+    symbol: &'static str,
+    factor: f64,
+    offset: f64,
+    dimension: Dimension,
+    /// Whether metric prefixes can be applied, e.g. `mL` or `kPa`.
+    metric: bool,
+}
+
+const fn atom(symbol: &'static str, factor: f64, dimension: Dimension, metric: bool) -> Atom {
+    Atom { symbol, factor, offset: 0.0, dimension, metric }
+}
+
+const ATOMS: &[Atom] = &[
+    atom("m", 1.0, LENGTH, true),
+    atom("g", 1e-3, MASS, true),
+    atom("s", 1.0, TIME, true),
+    atom("min", 60.0, TIME, false),
+    atom("h", 3600.0, TIME, false),
+    atom("d", 86400.0, TIME, false),
+    atom("L", 1e-3, VOLUME, true),
+    atom("l", 1e-3, VOLUME, true),
+    atom("mol", 1.0, AMOUNT, true),
+    atom("K", 1.0, TEMPERATURE, true),
+    Atom { symbol: "Cel", factor: 1.0, offset: 273.15, dimension: TEMPERATURE, metric: false },
+    atom("Pa", 1.0, PRESSURE, true),
+    atom("bar", 1e5, PRESSURE, true),
+    atom("atm", 101325.0, PRESSURE, false),
+    atom("Hz", 1.0, FREQUENCY, true),
+    atom("rad", 1.0, ANGLE, true),
+    atom("deg", PI / 180.0, ANGLE, false),
+    atom("rev", 2.0 * PI, ANGLE, false),
+    atom("%", 1e-2, DIMENSIONLESS, false),
+    atom("ppm", 1e-6, DIMENSIONLESS, false),
+    atom("ppb", 1e-9, DIMENSIONLESS, false),
+    // Absorbance unit, an arbitrary unit without SI counterpart.
+    atom("AU", 1.0, DIMENSIONLESS, true),
+];
+
+/// Symbols of arbitrary units, which cannot be converted to SI.
+const ARBITRARY_ATOMS: &[&str] = &["AU"];
+
+const PREFIXES: &[(&str, f64)] = &[
+    ("da", 1e1),
+    ("T", 1e12),
+    ("G", 1e9),
+    ("M", 1e6),
+    ("k", 1e3),
+    ("h", 1e2),
+    ("d", 1e-1),
+    ("c", 1e-2),
+    ("m", 1e-3),
+    ("u", 1e-6),
+    ("µ", 1e-6),
+    ("μ", 1e-6),
+    ("n", 1e-9),
+    ("p", 1e-12),
+    ("f", 1e-15),
+];
+
+/// Spellings found in instrument exports that are not UCUM, and their UCUM expression.
+const ALIASES: &[(&str, &str)] = &[
+    ("°C", "Cel"),
+    ("℃", "Cel"),
+    ("degC", "Cel"),
+    ("rpm", "rev/min"),
+    ("sec", "s"),
+    ("hr", "h"),
+    ("Counts.s", "{Counts}.s"),
+    ("unitless", "1"),
+    ("(unitless)", "1"),
+    ("UNITLESS", "1"),
+];
+
+/// Spellings that stand for different units depending on the instrument, and what they may
+/// mean: they are rejected rather than guessed.
+const AMBIGUOUS: &[(&str, &str)] = &[("nM", "nanomolar (`nmol/L`) or nanometre (`nm`)")];
+
+/// A term of an expression: a prefixed unit symbol or an annotation such as `{Counts}`,
+/// with its exponent.
+#[derive(Clone, Debug, PartialEq)]
+struct Term {
+    symbol: String,
+    exponent: i32,
+}
+
+/// A parsed unit expression, with its conversion to the SI unit of the same dimension:
+/// `si_value = value * factor + offset`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnitExpression {
+    terms: Vec<Term>,
+    pub factor: f64,
+    pub offset: f64,
+    pub dimension: Dimension,
+    /// Whether the expression contains arbitrary units or annotations, which cannot be
+    /// converted to SI.
+    pub arbitrary: bool,
+}
+
+impl FromStr for UnitExpression {
+    type Err = anyhow::Error;
+
+    fn from_str(expression: &str) -> Result<Self> {
+        let expression = expression.trim();
+        if let Some((_, meanings)) = AMBIGUOUS.iter().find(|(symbol, _)| *symbol == expression) {
+            bail!("ambiguous unit, either {}", meanings);
+        }
+        let expression = ALIASES
+            .iter()
+            .find(|(alias, _)| *alias == expression)
+            .map_or(expression, |(_, ucum)| ucum);
+
+        let mut parsed = UnitExpression {
+            terms: Vec::new(),
+            factor: 1.0,
+            offset: 0.0,
+            dimension: DIMENSIONLESS,
+            arbitrary: false,
+        };
+        let mut offset = None;
+
+        for (sign, term) in split_terms(expression)? {
+            if term == "1" {
+                continue;
+            }
+            if term.starts_with('{') {
+                if !term.ends_with('}') {
+                    bail!("unclosed annotation '{}'", term);
+                }
+                parsed.arbitrary = true;
+                parsed.terms.push(Term { symbol: term.to_string(), exponent: sign });
+                continue;
+            }
+
+            let (symbol, exponent) = split_exponent(term)?;
+            let exponent = exponent * sign;
+            let (prefix, factor, atom) = resolve_symbol(symbol)?;
+            parsed.factor *= (factor * atom.factor).powi(exponent);
+            for (total, dimension) in parsed.dimension.iter_mut().zip(atom.dimension) {
+                *total += dimension * exponent as i8;
+            }
+            parsed.arbitrary |= ARBITRARY_ATOMS.contains(&atom.symbol);
+            offset = (prefix.is_empty() && exponent == 1).then_some(atom.offset);
+
+            // Spellings of the same unit share their canonical form: µL, μL and ul are uL.
+            let prefix = if factor == 1e-6 { "u" } else { prefix };
+            let symbol = if atom.symbol == "l" { "L" } else { atom.symbol };
+            parsed.terms.push(Term { symbol: format!("{}{}", prefix, symbol), exponent });
+        }
+
+        // An offset (e.g. °C to K) only applies to a unit on its own, a compound unit such
+        // as Cel/min measures differences.
+        if parsed.terms.len() == 1 {
+            parsed.offset = offset.unwrap_or(0.0);
+        }
+        Ok(parsed)
+    }
+}
+
+/// Splits an expression into its terms, with the sign of their exponent: `-1` for the term
+/// following a `/`, `1` otherwise.
+fn split_terms(expression: &str) -> Result<Vec<(i32, &str)>> {
+    if expression.is_empty() {
+        bail!("empty unit");
+    }
+    if expression.contains(['(', ')']) && !expression.starts_with('{') {
+        bail!("parentheses are not supported");
+    }
+
+    let mut terms = Vec::new();
+    let mut sign = 1;
+    let mut start = 0;
+    let mut depth = 0;
+    for (index, c) in expression.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            '.' | '*' | '·' | '/' if depth == 0 => {
+                let term = &expression[start..index];
+                // A leading `/` divides 1, e.g. `/s`.
+                if !(term.is_empty() && index == 0 && c == '/') {
+                    if term.is_empty() {
+                        bail!("missing unit before '{}'", c);
+                    }
+                    terms.push((sign, term));
+                }
+                sign = if c == '/' { -1 } else { 1 };
+                start = index + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    let term = &expression[start..];
+    if term.is_empty() {
+        bail!("missing unit at the end");
+    }
+    terms.push((sign, term));
+    Ok(terms)
+}
+
+/// Splits a term into its symbol and exponent, e.g. `m3`, `m^3`, `m³` or `s-1`.
+fn split_exponent(term: &str) -> Result<(&str, i32)> {
+    let superscript = |c: char| match c {
+        '⁻' => Some('-'),
+        '¹' => Some('1'),
+        '²' => Some('2'),
+        '³' => Some('3'),
+        _ => None,
+    };
+    if let Some(index) = term.find(|c: char| superscript(c).is_some()) {
+        let exponent: String = term[index..].chars().map(|c| superscript(c).unwrap_or(c)).collect();
+        let exponent = exponent.parse().map_err(|_| anyhow::anyhow!("invalid exponent"))?;
+        return Ok((&term[..index], exponent));
+    }
+
+    let digits = term.trim_end_matches(|c: char| c.is_ascii_digit());
+    if digits.len() == term.len() || digits.is_empty() {
+        return Ok((term, 1));
+    }
+    let (symbol, sign) = match digits.strip_suffix(['-', '+']) {
+        Some(symbol) => (symbol, &digits[symbol.len()..]),
+        None => (digits, ""),
+    };
+    let symbol = symbol.strip_suffix('^').unwrap_or(symbol);
+    if symbol.is_empty() {
+        bail!("invalid unit '{}'", term);
+    }
+    let exponent: i32 = format!("{}{}", sign, &term[digits.len()..])
+        .parse()
+        .map_err(|_| anyhow::anyhow!("invalid exponent in '{}'", term))?;
+    if exponent == 0 {
+        bail!("invalid exponent 0 in '{}'", term);
+    }
+    Ok((symbol, exponent))
+}
+
+/// Finds the unit of a symbol, either as is or with a metric prefix.
+fn resolve_symbol(symbol: &str) -> Result<(&'static str, f64, &'static Atom)> {
+    if let Some(atom) = ATOMS.iter().find(|atom| atom.symbol == symbol) {
+        return Ok(("", 1.0, atom));
+    }
+    for (prefix, factor) in PREFIXES {
+        if let Some(rest) = symbol.strip_prefix(prefix) {
+            if let Some(atom) = ATOMS.iter().find(|atom| atom.metric && atom.symbol == rest) {
+                return Ok((prefix, *factor, atom));
+            }
+        }
+    }
+    bail!("unknown unit symbol '{}'", symbol)
+}
+
+impl UnitExpression {
+    /// The canonical form of the expression, used to look units up: UCUM symbols with the
+    /// terms of the numerator then of the denominator, sorted, e.g. `mAU.s` or `g/mL`.
+    pub fn canonical(&self) -> String {
+        let render = |term: &Term| match term.exponent.abs() {
+            1 => term.symbol.clone(),
+            exponent => format!("{}{}", term.symbol, exponent),
+        };
+        let mut numerator: Vec<String> =
+            self.terms.iter().filter(|term| term.exponent > 0).map(render).collect();
+        let mut denominator: Vec<String> =
+            self.terms.iter().filter(|term| term.exponent < 0).map(render).collect();
+        numerator.sort();
+        denominator.sort();
+
+        let mut canonical = numerator.join(".");
+        for term in denominator {
+            canonical.push('/');
+            canonical.push_str(&term);
+        }
+        if canonical.is_empty() {
+            canonical.push('1');
+        }
+        canonical
+    }
+
+    /// Whether a value can be converted to the SI unit of the dimension.
+    pub fn is_convertible(&self) -> bool {
+        !self.arbitrary
+    }
+
+    /// Whether both expressions denote the same unit, e.g. `cm3` and `mL`.
+    pub fn is_equivalent(&self, other: &UnitExpression) -> bool {
+        self.is_convertible()
+            && other.is_convertible()
+            && self.dimension == other.dimension
+            && self.offset == other.offset
+            && (self.factor - other.factor).abs() <= 1e-9 * self.factor.abs()
+    }
+}
+
+impl fmt::Display for UnitExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.canonical())
+    }
+}
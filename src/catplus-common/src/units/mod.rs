@@ -0,0 +1,3 @@
+pub mod expression;
+pub mod table;
+pub mod unit;
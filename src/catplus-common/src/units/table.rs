@@ -0,0 +1,158 @@
+//! Lookup of the QUDT (or qudt-ext) IRI of unit expressions.
+use crate::{
+    graph::prefix_map::generate_prefix_map,
+    units::{expression::UnitExpression, unit::Unit},
+};
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use sophia_api::prelude::Iri;
+use std::{collections::HashMap, fs, path::Path};
+
+/// The built-in table, see [UnitTable::extend_from_file] for the format.
+const BUILTIN_UNITS: &str = include_str!("units.json");
+
+/// Units to which values are normalized, one per dimension (see [Unit::to_si]).
+const SI_UNITS: &[&str] = &[
+    "m", "kg", "s", "K", "mol", "Pa", "m^3", "kg/m^3", "kg/mol", "mol/m^3", "rad/s", "m^3/s", "Hz",
+    "UNITLESS",
+];
+
+#[derive(Deserialize)]
+struct Entry {
+    label: String,
+    /// An IRI, or a CURIE with one of the prefixes of the graphs, e.g. `unit:MilliL`.
+    iri: String,
+}
+
+/// Maps unit expressions to IRIs, and IRIs back to a label.
+///
+/// Expressions are looked up by their canonical form, so that `mL`, `ml` and `µl` resolve to
+/// the same IRI, then by equivalence, so that `cm3` resolves to the IRI of `mL`. Labels that
+/// are not UCUM expressions are looked up as is.
+#[derive(Clone, Debug, Default)]
+pub struct UnitTable {
+    iris: HashMap<String, String>,
+    expressions: Vec<(UnitExpression, String)>,
+    labels: HashMap<String, String>,
+}
+
+impl UnitTable {
+    /// The table of the units found in the supported instrument exports.
+    pub fn builtin() -> Self {
+        let mut table = UnitTable::default();
+        table.extend_from_str(BUILTIN_UNITS).expect("The built-in unit table should be valid");
+        table
+    }
+
+    /// Adds the units of a JSON file, an array of `{"label": "mbar", "iri": "unit:MilliBAR"}`
+    /// entries. Entries override the units with the same expression.
+    pub fn extend_from_file(&mut self, path: &Path) -> Result<()> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read unit table '{}'", path.display()))?;
+        self.extend_from_str(&content)
+            .with_context(|| format!("Invalid unit table '{}'", path.display()))
+    }
+
+    pub fn extend_from_str(&mut self, content: &str) -> Result<()> {
+        let entries: Vec<Entry> = serde_json::from_str(content)?;
+        let prefixes = generate_prefix_map();
+        for Entry { label, iri } in entries {
+            let iri = match iri.split_once(':') {
+                Some((prefix, local)) => prefixes
+                    .iter()
+                    .find(|(known, _)| known.as_str() == prefix)
+                    .map_or(iri.clone(), |(_, namespace)| {
+                        format!("{}{}", namespace.as_str(), local)
+                    }),
+                None => iri,
+            };
+            Iri::new(iri.as_str())
+                .with_context(|| format!("Invalid IRI '{}' for unit '{}'", iri, label))?;
+            self.insert(&label, &iri);
+        }
+        Ok(())
+    }
+
+    /// Maps `label` to `iri`; the first label of an IRI is the one used to read it back.
+    pub fn insert(&mut self, label: &str, iri: &str) {
+        self.iris.insert(lookup_key(label), iri.to_string());
+        if let Ok(expression) = label.parse::<UnitExpression>() {
+            self.expressions.push((expression, iri.to_string()));
+        }
+        self.labels.entry(iri.to_string()).or_insert_with(|| label.to_string());
+    }
+
+    /// Resolves `label` to a unit.
+    ///
+    /// Units without IRI are an error, unless `untyped` is set: they are then kept as a
+    /// plain literal.
+    pub fn resolve(&self, label: &str, untyped: bool) -> Result<Unit> {
+        let expression = label.parse::<UnitExpression>();
+        let key = expression.as_ref().map_or_else(|_| label.trim().to_string(), |e| e.canonical());
+        // The latest entries take precedence, as they override the built-in ones.
+        let iri = self.iris.get(&key).cloned().or_else(|| {
+            let expression = expression.as_ref().ok()?;
+            self.expressions
+                .iter()
+                .rev()
+                .find(|(known, _)| known.is_equivalent(expression))
+                .map(|(_, iri)| iri.clone())
+        });
+
+        match (iri, expression) {
+            (Some(iri), expression) => {
+                Ok(Unit { label: label.to_string(), iri: Some(iri), expression: expression.ok() })
+            }
+            (None, expression) if untyped => {
+                Ok(Unit { label: label.to_string(), iri: None, expression: expression.ok() })
+            }
+            (None, Ok(expression)) => bail!(
+                "Unit '{}' ({}) has no known QUDT IRI, add it to a unit table file",
+                label,
+                expression
+            ),
+            (None, Err(error)) => bail!("Invalid unit '{}': {}", label, error),
+        }
+    }
+
+    /// The unit identified by `iri`, if it is in the table.
+    pub fn from_iri(&self, iri: &str) -> Option<Unit> {
+        self.labels
+            .get(iri)
+            .and_then(|label| self.resolve(label, false).ok())
+            .filter(|unit| unit.iri() == Some(iri))
+    }
+
+    /// The unit to which values in `unit` are normalized, with the same dimension.
+    pub fn si_unit(&self, unit: &UnitExpression) -> Option<Unit> {
+        SI_UNITS
+            .iter()
+            .filter_map(|label| self.resolve(label, false).ok())
+            .find(|si| si.expression.as_ref().is_some_and(|si| si.dimension == unit.dimension))
+    }
+}
+
+fn lookup_key(label: &str) -> String {
+    label.parse::<UnitExpression>().map_or_else(|_| label.trim().to_string(), |e| e.canonical())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::units::unit::QuantityKind;
+
+    #[test]
+    fn test_unit_table() {
+        let mut table = UnitTable::builtin();
+        assert!(table.resolve("mg/dL", false).is_err());
+        let untyped = table.resolve("mg/dL", true).unwrap();
+        assert_eq!(untyped.iri(), None);
+        assert_eq!(untyped.quantity_kind(), QuantityKind::MassDensity);
+
+        table.extend_from_str(r#"[{"label": "mg/dL", "iri": "unit:MilliGM-PER-DeciL"}]"#).unwrap();
+        let unit = table.resolve("mg/dL", false).unwrap();
+        assert_eq!(unit.iri(), Some("http://qudt.org/vocab/unit/MilliGM-PER-DeciL"));
+        assert_eq!(table.from_iri(unit.iri().unwrap()).unwrap().label(), "mg/dL");
+        assert_eq!(table.from_iri("http://qudt.org/vocab/unit/DEG-C").unwrap().label(), "°C");
+    }
+}
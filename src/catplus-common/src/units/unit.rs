@@ -0,0 +1,298 @@
+//! Units of measured values, resolved from UCUM-style expressions to QUDT IRIs.
+use crate::{
+    graph::{
+        from_graph::FromGraph,
+        insert_into::{literal_error, InsertIntoGraph, Link},
+    },
+    units::{expression::UnitExpression, table::UnitTable},
+};
+use anyhow::Result;
+use lazy_static::lazy_static;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use sophia::{inmem::graph::LightGraph, iri::IriRef};
+use sophia_api::term::{SimpleTerm, Term};
+use std::{cell::RefCell, fmt, path::Path, sync::Arc};
+
+lazy_static! {
+    static ref BUILTIN_TABLE: Arc<UnitTable> = Arc::new(UnitTable::builtin());
+}
+
+thread_local! {
+    /// Options of the conversion running on this thread, see [UnitOptions::scope].
+    static SCOPED_OPTIONS: RefCell<Option<UnitOptions>> = const { RefCell::new(None) };
+}
+
+/// How units are resolved during a conversion.
+#[derive(Clone, Debug)]
+pub struct UnitOptions {
+    /// Table mapping unit expressions to IRIs, the built-in table by default.
+    pub table: Arc<UnitTable>,
+    /// Keep units without known IRI as plain literals (`qudt:unit "furlong"`) instead of
+    /// failing the conversion.
+    pub untyped: bool,
+}
+
+impl Default for UnitOptions {
+    fn default() -> Self {
+        UnitOptions { table: BUILTIN_TABLE.clone(), untyped: false }
+    }
+}
+
+impl UnitOptions {
+    /// Options resolving units with the built-in table, extended with the units of
+    /// `units_file` if any (see [UnitTable::extend_from_file]).
+    pub fn load(units_file: Option<&Path>, untyped: bool) -> Result<Self> {
+        let table = match units_file {
+            Some(path) => {
+                let mut table = UnitTable::builtin();
+                table.extend_from_file(path)?;
+                Arc::new(table)
+            }
+            None => BUILTIN_TABLE.clone(),
+        };
+        Ok(UnitOptions { table, untyped })
+    }
+
+    /// Runs `f` with these options resolving the units, e.g. those deserialized from the input.
+    /// Units are resolved with the default options outside of a scope, including on threads
+    /// spawned within it.
+    pub fn scope<R>(&self, f: impl FnOnce() -> R) -> R {
+        /// Restores the options of the enclosing scope, even if `f` panics.
+        struct Restore(Option<UnitOptions>);
+
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                SCOPED_OPTIONS.set(self.0.take());
+            }
+        }
+
+        let _restore = Restore(SCOPED_OPTIONS.replace(Some(self.clone())));
+        f()
+    }
+
+    /// Calls `f` with the options of the current scope.
+    fn with_scoped<R>(f: impl FnOnce(&UnitOptions) -> R) -> R {
+        SCOPED_OPTIONS.with_borrow(|options| match options {
+            Some(options) => f(options),
+            None => f(&UnitOptions::default()),
+        })
+    }
+}
+
+/// The kind of quantity a unit measures, e.g. a temperature or a duration.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QuantityKind {
+    Pressure,
+    Temperature,
+    Mass,
+    MassDensity,
+    MolarMass,
+    Concentration,
+    Angle,
+    AngularVelocity,
+    Volume,
+    VolumeFlowRate,
+    Length,
+    Time,
+    Dimensionless,
+    Frequency,
+    /// Arbitrary units, e.g. absorbance units or counts.
+    Arbitrary,
+    Other,
+}
+
+/// A unit, written as a UCUM-style expression such as `mg/mL`, and identified in graphs by
+/// its QUDT (or qudt-ext) IRI.
+///
+/// Units are resolved with the [UnitOptions] of the conversion, see [UnitOptions::scope].
+/// Units without IRI are an error, or plain literals if [UnitOptions::untyped] is set.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Unit {
+    pub(crate) label: String,
+    pub(crate) iri: Option<String>,
+    pub(crate) expression: Option<UnitExpression>,
+}
+
+impl Unit {
+    /// Resolves `label`, e.g. `µL`, `mbar` or `°C`.
+    pub fn parse(label: &str) -> Result<Unit> {
+        UnitOptions::with_scoped(|options| options.table.resolve(label, options.untyped))
+    }
+
+    /// Returns the unit identified by `iri`, if any.
+    pub fn from_iri(iri: &str) -> Option<Unit> {
+        UnitOptions::with_scoped(|options| options.table.from_iri(iri))
+    }
+
+    /// Reads a unit from a graph: a known IRI, or a literal for untyped units.
+    pub fn from_term(term: &SimpleTerm) -> Option<Unit> {
+        match (term.iri(), term.lexical_form()) {
+            (Some(iri), _) => Unit::from_iri(iri.as_str()),
+            (_, Some(label)) => {
+                UnitOptions::with_scoped(|options| options.table.resolve(&label, true).ok())
+            }
+            _ => None,
+        }
+    }
+
+    /// The unit as written in the input data.
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// The IRI of the unit, `None` for untyped units.
+    pub fn iri(&self) -> Option<&str> {
+        self.iri.as_deref()
+    }
+
+    /// The kind of quantity measured in this unit.
+    pub fn quantity_kind(&self) -> QuantityKind {
+        let Some(expression) = &self.expression else {
+            return QuantityKind::Other;
+        };
+        if expression.arbitrary {
+            return QuantityKind::Arbitrary;
+        }
+        // Exponents of length, mass, time, temperature, amount of substance and angle.
+        match expression.dimension {
+            [-1, 1, -2, 0, 0, 0] => QuantityKind::Pressure,
+            [0, 0, 0, 1, 0, 0] => QuantityKind::Temperature,
+            [0, 1, 0, 0, 0, 0] => QuantityKind::Mass,
+            [-3, 1, 0, 0, 0, 0] => QuantityKind::MassDensity,
+            [0, 1, 0, 0, -1, 0] => QuantityKind::MolarMass,
+            [-3, 0, 0, 0, 1, 0] => QuantityKind::Concentration,
+            [0, 0, 0, 0, 0, 1] => QuantityKind::Angle,
+            [0, 0, -1, 0, 0, 1] => QuantityKind::AngularVelocity,
+            [3, 0, 0, 0, 0, 0] => QuantityKind::Volume,
+            [3, 0, -1, 0, 0, 0] => QuantityKind::VolumeFlowRate,
+            [1, 0, 0, 0, 0, 0] => QuantityKind::Length,
+            [0, 0, 1, 0, 0, 0] => QuantityKind::Time,
+            [0, 0, 0, 0, 0, 0] => QuantityKind::Dimensionless,
+            [0, 0, -1, 0, 0, 0] => QuantityKind::Frequency,
+            _ => QuantityKind::Other,
+        }
+    }
+
+    /// The SI unit of the same dimension, with the factor and offset converting a value
+    /// to it: `si_value = value * factor + offset`.
+    ///
+    /// Arbitrary units (e.g. absorbance units) have no SI counterpart and return `None`.
+    pub fn si_conversion(&self) -> Option<(Unit, f64, f64)> {
+        let expression = self.expression.as_ref().filter(|e| e.is_convertible())?;
+        let si_unit = UnitOptions::with_scoped(|options| options.table.si_unit(expression))?;
+        Some((si_unit, expression.factor, expression.offset))
+    }
+
+    /// Converts `value` from this unit to the SI unit of its dimension.
+    pub fn to_si(&self, value: f64) -> Option<(f64, Unit)> {
+        self.si_conversion().map(|(si_unit, factor, offset)| (value * factor + offset, si_unit))
+    }
+}
+
+/// Units are written as in the input data.
+impl Serialize for Unit {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.label)
+    }
+}
+
+/// Units are resolved with the options of the [UnitOptions::scope] of the current thread, and
+/// with the built-in table outside of any scope. The scope is not inherited by other threads:
+/// work spawned on a thread pool (e.g. with rayon) must enter it again on the worker threads,
+/// as each conversion does in `json_to_graph`.
+impl<'de> Deserialize<'de> for Unit {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let label = String::deserialize(deserializer)?;
+        Unit::parse(&label).map_err(|error| de::Error::custom(format!("{:#}", error)))
+    }
+}
+
+/// Units are attached as the IRI of the unit, or as a literal for untyped units.
+impl InsertIntoGraph for Unit {
+    fn insert_into(&self, _graph: &mut LightGraph, _iri: SimpleTerm) -> Result<()> {
+        Err(literal_error(self))
+    }
+
+    fn attach_into(&self, graph: &mut LightGraph, attach: Link) -> Result<()> {
+        match &self.iri {
+            Some(iri) => IriRef::new_unchecked(iri.as_str()).as_simple().attach_into(graph, attach),
+            None => self.label.as_str().as_simple().attach_into(graph, attach),
+        }
+    }
+
+    fn is_node(&self) -> bool {
+        false
+    }
+}
+
+impl FromGraph for Unit {
+    fn from_graph(_graph: &LightGraph, iri: &SimpleTerm) -> Result<Self> {
+        Unit::from_term(iri).ok_or_else(|| match iri.iri() {
+            Some(iri) => anyhow::anyhow!("Unknown unit <{}>", iri.as_str()),
+            None => anyhow::anyhow!("Expected a unit IRI or label, found {:?}", iri),
+        })
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.iri {
+            Some(iri) => write!(f, "<{}>", iri),
+            None => write!(f, "\"{}\"", self.label),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit(label: &str) -> Unit {
+        Unit::parse(label).unwrap()
+    }
+
+    #[test]
+    fn test_parse_units() {
+        for (label, iri) in [
+            ("µL", "http://qudt.org/vocab/unit/MicroL"),
+            ("uL", "http://qudt.org/vocab/unit/MicroL"),
+            ("ml", "http://qudt.org/vocab/unit/MilliL"),
+            ("cm3", "http://qudt.org/vocab/unit/MilliL"),
+            ("K", "http://qudt.org/vocab/unit/K"),
+            ("mbar", "http://qudt.org/vocab/unit/MilliBAR"),
+            ("nm", "http://qudt.org/vocab/unit/NanoM"),
+            ("Hz", "http://qudt.org/vocab/unit/HZ"),
+            ("ppm", "http://qudt.org/vocab/unit/PPM"),
+            ("s-1", "http://qudt.org/vocab/unit/PER-SEC"),
+            ("kg.m-3", "http://qudt.org/vocab/unit/KiloGM-PER-M3"),
+            ("m³", "http://qudt.org/vocab/unit/M3"),
+            (
+                "mAU.s",
+                "http://purl.allotrope.org/ontology/qudt-ext/unit#MilliAbsorbanceUnitTimesSecond",
+            ),
+            ("(unitless)", "http://qudt.org/vocab/unit/UNITLESS"),
+        ] {
+            assert_eq!(unit(label).iri(), Some(iri), "{}", label);
+        }
+
+        let error = Unit::parse("furlong").unwrap_err().to_string();
+        assert!(error.contains("Invalid unit 'furlong'"), "{}", error);
+        let error = Unit::parse("mg/dL").unwrap_err().to_string();
+        assert!(error.contains("no known QUDT IRI"), "{}", error);
+        // Nanomolar or nanometre, depending on the instrument.
+        let error = Unit::parse("nM").unwrap_err().to_string();
+        assert!(error.contains("ambiguous unit"), "{}", error);
+    }
+
+    #[test]
+    fn test_to_si() {
+        let (value, si_unit) = unit("mg").to_si(500.0).unwrap();
+        assert!((value - 5e-4).abs() < 1e-15);
+        assert_eq!(si_unit.quantity_kind(), QuantityKind::Mass);
+        assert!(unit("mAU").to_si(1.0).is_none());
+
+        let (value, si_unit) = unit("µL/min").to_si(60.0).unwrap();
+        assert!((value - 1e-9).abs() < 1e-21);
+        assert_eq!(si_unit.quantity_kind(), QuantityKind::VolumeFlowRate);
+    }
+}
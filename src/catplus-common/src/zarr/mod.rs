@@ -0,0 +1,3 @@
+pub mod nd_array;
+pub mod store;
+pub mod write_into;
@@ -0,0 +1,116 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A dense n-dimensional array of `f64` values stored in row-major (C) order.
+///
+/// Data cubes in Allotrope JSON store their values as (possibly nested) JSON
+/// arrays. `NdArray` flattens them and keeps track of the shape so they can be
+/// written as a single Zarr array.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "Value", into = "Value")]
+pub struct NdArray {
+    pub shape: Vec<usize>,
+    pub values: Vec<f64>,
+}
+
+impl NdArray {
+    /// Returns a copy of the array with a new shape, if the number of elements matches.
+    pub fn reshape(&self, shape: &[usize]) -> Option<NdArray> {
+        if shape.iter().product::<usize>() != self.values.len() {
+            return None;
+        }
+        Some(NdArray { shape: shape.to_vec(), values: self.values.clone() })
+    }
+
+    /// Flattens a nested JSON array into `values`, recording the length of each level in `shape`.
+    fn flatten(
+        value: &Value,
+        depth: usize,
+        shape: &mut Vec<usize>,
+        values: &mut Vec<f64>,
+    ) -> Result<(), String> {
+        match value {
+            Value::Array(items) => {
+                match shape.get(depth) {
+                    Some(len) if *len != items.len() => {
+                        return Err(format!(
+                            "ragged array: expected {} elements at depth {}, found {}",
+                            len,
+                            depth,
+                            items.len()
+                        ))
+                    }
+                    Some(_) => {}
+                    None if depth == shape.len() => shape.push(items.len()),
+                    None => return Err(format!("ragged array at depth {}", depth)),
+                }
+                items.iter().try_for_each(|item| Self::flatten(item, depth + 1, shape, values))
+            }
+            // Missing values are written as the Zarr fill value.
+            Value::Null if depth == shape.len() && depth > 0 => {
+                values.push(f64::NAN);
+                Ok(())
+            }
+            Value::Number(n) if depth == shape.len() && depth > 0 => {
+                values.push(n.as_f64().ok_or_else(|| format!("{} is not a valid number", n))?);
+                Ok(())
+            }
+            other => Err(format!("unexpected value {} at depth {}", other, depth)),
+        }
+    }
+
+    /// Rebuilds the nested JSON array from the flat values.
+    fn nest(values: &[f64], shape: &[usize]) -> Value {
+        match shape.split_first() {
+            None => values.first().map_or(Value::Null, |v| {
+                serde_json::Number::from_f64(*v).map_or(Value::Null, Value::Number)
+            }),
+            Some((len, rest)) => {
+                let stride = rest.iter().product::<usize>();
+                Value::Array(
+                    (0..*len)
+                        .map(|i| Self::nest(&values[i * stride..(i + 1) * stride], rest))
+                        .collect(),
+                )
+            }
+        }
+    }
+}
+
+impl TryFrom<Value> for NdArray {
+    type Error = String;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        let mut shape = Vec::new();
+        let mut values = Vec::new();
+        NdArray::flatten(&value, 0, &mut shape, &mut values)?;
+
+        Ok(NdArray { shape, values })
+    }
+}
+
+impl From<NdArray> for Value {
+    fn from(array: NdArray) -> Self {
+        NdArray::nest(&array.values, &array.shape)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nested_array_round_trip() {
+        let json = serde_json::json!([[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+        let array: NdArray = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(array.shape, vec![2, 3]);
+        assert_eq!(array.values, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        assert_eq!(serde_json::to_value(&array).unwrap(), json);
+    }
+
+    #[test]
+    fn test_ragged_array_is_rejected() {
+        let json = serde_json::json!([[1.0, 2.0], [3.0]]);
+        assert!(serde_json::from_value::<NdArray>(json).is_err());
+    }
+}
@@ -0,0 +1,181 @@
+use crate::zarr::nd_array::NdArray;
+use anyhow::{bail, Context, Result};
+use serde_json::{json, Map, Value};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// A Zarr v3 store on the local filesystem.
+///
+/// Arrays are written uncompressed as a single chunk of little-endian `float64`
+/// values, which keeps the store readable by any Zarr v3 implementation.
+pub struct ZarrStore {
+    root: PathBuf,
+}
+
+impl ZarrStore {
+    /// Creates a new store at `root` and writes the metadata of its root group.
+    ///
+    /// A store already at `root`, e.g. from an earlier conversion, is refused unless
+    /// `overwrite` is set: it is then replaced so that none of its chunks are left behind.
+    /// Other non-empty directories are always refused.
+    pub fn create(root: &Path, overwrite: bool) -> Result<Self> {
+        if root.is_dir() && fs::read_dir(root)?.next().is_some() {
+            if !root.join("zarr.json").is_file() {
+                bail!("'{}' is not empty and is not a Zarr store", root.display());
+            }
+            if !overwrite {
+                bail!("'{}' is already a Zarr store, it is only replaced if asked", root.display());
+            }
+            fs::remove_dir_all(root)
+                .with_context(|| format!("Failed to remove the Zarr store '{}'", root.display()))?;
+        }
+        let store = ZarrStore { root: root.to_path_buf() };
+        store.create_group("", Map::new())?;
+
+        Ok(store)
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Creates a group at `path` (relative to the store root) with the given attributes.
+    pub fn create_group(&self, path: &str, attributes: Map<String, Value>) -> Result<()> {
+        let metadata = json!({
+            "zarr_format": 3,
+            "node_type": "group",
+            "attributes": attributes,
+        });
+        self.write_metadata(path, &metadata)
+    }
+
+    /// Writes `array` at `path` (relative to the store root) with the given attributes.
+    pub fn write_array(
+        &self,
+        path: &str,
+        array: &NdArray,
+        attributes: Map<String, Value>,
+    ) -> Result<()> {
+        // Zarr does not allow empty chunks, an empty array is stored as metadata only.
+        let chunk_shape: Vec<usize> = array.shape.iter().map(|len| (*len).max(1)).collect();
+        let metadata = json!({
+            "zarr_format": 3,
+            "node_type": "array",
+            "shape": array.shape,
+            "data_type": "float64",
+            "chunk_grid": {
+                "name": "regular",
+                "configuration": { "chunk_shape": chunk_shape },
+            },
+            "chunk_key_encoding": {
+                "name": "default",
+                "configuration": { "separator": "/" },
+            },
+            "fill_value": "NaN",
+            "codecs": [
+                { "name": "bytes", "configuration": { "endian": "little" } },
+            ],
+            "attributes": attributes,
+        });
+        self.write_metadata(path, &metadata)?;
+
+        if array.values.is_empty() {
+            return Ok(());
+        }
+
+        // With a single chunk, the chunk key is "c/0/0/..." with one index per dimension.
+        let mut chunk_path = self.node_path(path).join("c");
+        for _ in 1..array.shape.len() {
+            chunk_path.push("0");
+        }
+        fs::create_dir_all(&chunk_path)
+            .with_context(|| format!("Failed to create Zarr chunk directory for '{}'", path))?;
+        chunk_path.push("0");
+
+        let bytes: Vec<u8> = array.values.iter().flat_map(|v| v.to_le_bytes()).collect();
+        fs::write(&chunk_path, bytes)
+            .with_context(|| format!("Failed to write Zarr chunk '{}'", chunk_path.display()))
+    }
+
+    fn node_path(&self, path: &str) -> PathBuf {
+        path.split('/').filter(|s| !s.is_empty()).fold(self.root.clone(), |p, s| p.join(s))
+    }
+
+    fn write_metadata(&self, path: &str, metadata: &Value) -> Result<()> {
+        let node_path = self.node_path(path);
+        fs::create_dir_all(&node_path)
+            .with_context(|| format!("Failed to create Zarr node '{}'", node_path.display()))?;
+
+        let metadata_path = node_path.join("zarr.json");
+        fs::write(&metadata_path, serde_json::to_string_pretty(metadata)?)
+            .with_context(|| format!("Failed to write '{}'", metadata_path.display()))
+    }
+}
+
+/// Turns a free-form label into a valid Zarr node name.
+pub fn node_name(label: &str) -> String {
+    let name: String = label
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '.' { c } else { '_' })
+        .collect();
+
+    // Names starting with "__" are reserved by the Zarr specification.
+    match name.trim_start_matches('_') {
+        "" => "_".to_string(),
+        trimmed => trimmed.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_array() -> Result<()> {
+        let root = std::env::temp_dir().join(format!("catplus-zarr-{}", uuid::Uuid::new_v4()));
+        let store = ZarrStore::create(&root, false)?;
+        let array = NdArray { shape: vec![3], values: vec![0.5, 1.0, -2.0] };
+        store.write_array("cube/measure", &array, Map::new())?;
+
+        let metadata: Value =
+            serde_json::from_str(&fs::read_to_string(root.join("cube/measure/zarr.json"))?)?;
+        assert_eq!(metadata["shape"], json!([3]));
+
+        let bytes = fs::read(root.join("cube/measure/c/0"))?;
+        assert_eq!(bytes.len(), 3 * 8);
+        assert_eq!(f64::from_le_bytes(bytes[16..24].try_into()?), -2.0);
+
+        fs::remove_dir_all(root)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_replaces_store() -> Result<()> {
+        let root = std::env::temp_dir().join(format!("catplus-zarr-{}", uuid::Uuid::new_v4()));
+        let store = ZarrStore::create(&root, false)?;
+        let array = NdArray { shape: vec![2], values: vec![0.5, 1.0] };
+        store.write_array("cube_0/measure", &array, Map::new())?;
+        store.write_array("cube_1/measure", &array, Map::new())?;
+
+        // An existing store is kept unless it is to be overwritten.
+        let error = ZarrStore::create(&root, false).err().unwrap();
+        assert!(error.to_string().contains("is already a Zarr store"), "{}", error);
+        assert!(root.join("cube_1/measure/c/0").is_file());
+
+        let store = ZarrStore::create(&root, true)?;
+        store.write_array("cube_0/measure", &array, Map::new())?;
+        assert!(root.join("cube_0/measure/c/0").is_file());
+        assert!(!root.join("cube_1").exists());
+
+        // Directories that are not a store are left untouched.
+        let other = root.join("cube_0");
+        fs::remove_file(other.join("zarr.json")).ok();
+        assert!(ZarrStore::create(&other, true).is_err());
+        assert!(other.join("measure/c/0").is_file());
+
+        fs::remove_dir_all(root)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,86 @@
+use crate::{
+    models::agilent::{CubeData, CubeStructure},
+    units::unit::Unit,
+    zarr::store::{node_name, ZarrStore},
+};
+use anyhow::Result;
+use serde_json::{Map, Value};
+
+/// WriteIntoZarr provides a trait to write the bulk data held by a type into a Zarr store.
+///
+/// Implementors are expected to remember where their data was written, so that the
+/// location can be emitted when the type is later inserted into a graph.
+pub trait WriteIntoZarr {
+    /// Writes the data of `&mut self` below the group at `path`.
+    fn write_into_zarr(&mut self, store: &ZarrStore, path: &str) -> Result<()>;
+}
+
+/// Default implementation for [Option<T>].
+impl<T> WriteIntoZarr for Option<T>
+where
+    T: WriteIntoZarr,
+{
+    fn write_into_zarr(&mut self, store: &ZarrStore, path: &str) -> Result<()> {
+        if let Some(v) = self {
+            v.write_into_zarr(store, path)?
+        }
+        Ok(())
+    }
+}
+
+/// Writes one array per measure and per dimension of a data cube into a new group at `path`.
+///
+/// Arrays are named after the concept of their measure or dimension, and carry
+/// the concept, unit and component datatype as Zarr attributes.
+pub fn write_data_cube(
+    store: &ZarrStore,
+    path: &str,
+    structure: &CubeStructure,
+    data: &CubeData,
+) -> Result<()> {
+    store.create_group(path, Map::new())?;
+    store.create_group(&format!("{}/measures", path), Map::new())?;
+    store.create_group(&format!("{}/dimensions", path), Map::new())?;
+
+    let dimension_shape: Vec<usize> = data.dimensions.iter().map(|d| d.values.len()).collect();
+
+    for (index, (measure, values)) in structure.measures.iter().zip(&data.measures).enumerate() {
+        // Measures are often given as a flat list, shape them along the dimensions when possible.
+        let values = values.reshape(&dimension_shape).unwrap_or_else(|| values.clone());
+        store.write_array(
+            &format!("{}/measures/{}", path, array_name(index, &measure.concept)),
+            &values,
+            component_attributes(&measure.concept, &measure.component_data_type, &measure.unit),
+        )?;
+    }
+    for (index, (dimension, values)) in
+        structure.dimensions.iter().zip(&data.dimensions).enumerate()
+    {
+        store.write_array(
+            &format!("{}/dimensions/{}", path, array_name(index, &dimension.concept)),
+            values,
+            component_attributes(
+                &dimension.concept,
+                &dimension.component_data_type,
+                &dimension.unit,
+            ),
+        )?;
+    }
+    Ok(())
+}
+
+fn array_name(index: usize, concept: &str) -> String {
+    format!("{}_{}", index, node_name(concept))
+}
+
+fn component_attributes(
+    concept: &str,
+    component_data_type: &str,
+    unit: &Unit,
+) -> Map<String, Value> {
+    let mut attributes = Map::new();
+    attributes.insert("concept".to_string(), concept.into());
+    attributes.insert("componentDatatype".to_string(), component_data_type.into());
+    attributes.insert("unit".to_string(), unit.iri().unwrap_or(unit.label()).into());
+    attributes
+}
@@ -0,0 +1,292 @@
+//! Derive macros for the Cat+ models.
+//!
+//! `#[derive(InsertIntoGraph)]` generates the `InsertIntoGraph` implementation of a struct
+//! from `#[rdf(...)]` attributes, instead of a hand-written predicate table, and
+//! `#[derive(FromGraph)]` generates the inverse conversion from the same attributes:
+//!
+//! ```ignore
+//! #[derive(InsertIntoGraph)]
+//! #[rdf(type = "cat::Plate")]
+//! pub struct Plate {
+//!     #[rdf(pred = "cat::containerID")]
+//!     pub container_id: String,
+//!     #[rdf(pred = "allores::AFX_0000622", datatype = "xsd::dateTime")]
+//!     pub start_time: Option<String>,
+//!     #[rdf(pred = "cat::hasBatch", reverse)]
+//!     pub actions: Vec<Action>,
+//!     #[rdf(flatten)]
+//!     pub details: Details,
+//!     #[rdf(skip)]
+//!     pub index: i64,
+//! }
+//! ```
+//!
+//! Supported attributes:
+//! - `type = "<path>"` (struct): adds an `rdf:type` triple.
+//! - `pred = "<path>"` (field): attaches the field to the node with this predicate.
+//! - `datatype = "<path>"` (field): the field (a `String` or `Option<String>`) is inserted
+//!   as a literal of the given datatype.
+//! - `reverse` (field): the triple is inserted from the field to the node instead.
+//! - `flatten` (field): the triples of the field are inserted on the node itself. The field
+//!   must be a struct deriving `InsertIntoGraph`, which implements `Flatten`, not a literal
+//!   (`String`, `f64`...): other types are rejected by the compiler.
+//! - `skip` (field): the field is not part of the graph. It is not inserted, and is set to
+//!   its `Default` when reading from a graph, so it does not survive a round trip.
+//!
+//! Fields without an `rdf` attribute are not inserted either, but `FromGraph` rejects them:
+//! they must be marked with `skip` to be explicitly left out of the inverse conversion.
+//!
+//! Paths are resolved at the call site, so namespaces (`cat`, `xsd`...) must be in scope.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    parse_macro_input, punctuated::Punctuated, token::Comma, Data, DeriveInput, Field, Fields,
+    LitStr, Path,
+};
+
+#[proc_macro_derive(InsertIntoGraph, attributes(rdf))]
+pub fn derive_insert_into_graph(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_insert_into_graph(&input).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+#[proc_macro_derive(FromGraph, attributes(rdf))]
+pub fn derive_from_graph(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_from_graph(&input).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+#[derive(Default)]
+struct RdfAttr {
+    rdf_type: Option<Path>,
+    pred: Option<Path>,
+    datatype: Option<Path>,
+    reverse: bool,
+    flatten: bool,
+    skip: bool,
+}
+
+impl RdfAttr {
+    fn parse(attrs: &[syn::Attribute]) -> syn::Result<Option<Self>> {
+        let mut found = None;
+        for attr in attrs.iter().filter(|a| a.path().is_ident("rdf")) {
+            let rdf = found.get_or_insert_with(RdfAttr::default);
+            attr.parse_nested_meta(|meta| {
+                let path = || -> syn::Result<Path> { meta.value()?.parse::<LitStr>()?.parse() };
+                if meta.path.is_ident("type") {
+                    rdf.rdf_type = Some(path()?);
+                } else if meta.path.is_ident("pred") {
+                    rdf.pred = Some(path()?);
+                } else if meta.path.is_ident("datatype") {
+                    rdf.datatype = Some(path()?);
+                } else if meta.path.is_ident("reverse") {
+                    rdf.reverse = true;
+                } else if meta.path.is_ident("flatten") {
+                    rdf.flatten = true;
+                } else if meta.path.is_ident("skip") {
+                    rdf.skip = true;
+                } else {
+                    return Err(meta.error("unsupported rdf attribute"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(found)
+    }
+
+    /// Checks the attributes of `field`: skipped fields have no other attribute, and flattened
+    /// fields have no predicate (their type is checked by the `Flatten` bound).
+    fn check_field(&self, field: &Field) -> syn::Result<()> {
+        if self.skip
+            && (self.pred.is_some() || self.datatype.is_some() || self.reverse || self.flatten)
+        {
+            return Err(syn::Error::new_spanned(
+                field,
+                "`skip` cannot be combined with other attributes",
+            ));
+        }
+        if !self.flatten {
+            return Ok(());
+        }
+        if self.pred.is_some() || self.datatype.is_some() || self.reverse {
+            return Err(syn::Error::new_spanned(
+                field,
+                "`flatten` cannot be combined with `pred`, `datatype` or `reverse`",
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn named_fields<'a>(
+    input: &'a DeriveInput,
+    derive: &str,
+) -> syn::Result<&'a Punctuated<Field, Comma>> {
+    match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => Ok(&fields.named),
+            _ => Err(syn::Error::new_spanned(&input.ident, "expected a struct with named fields")),
+        },
+        _ => Err(syn::Error::new_spanned(
+            &input.ident,
+            format!("{} can only be derived for structs", derive),
+        )),
+    }
+}
+
+fn expand_insert_into_graph(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let fields = named_fields(input, "InsertIntoGraph")?;
+
+    let krate = quote!(::catplus_common::graph::insert_into);
+    let mut entries = Vec::new();
+    let mut extra = Vec::new();
+
+    if let Some(rdf_type) = RdfAttr::parse(&input.attrs)?.and_then(|a| a.rdf_type) {
+        entries.push(quote! {
+            (
+                ::sophia::api::ns::rdf::type_,
+                &::sophia_api::term::Term::as_simple(&#rdf_type) as &dyn #krate::InsertIntoGraph,
+            )
+        });
+    }
+
+    for field in fields {
+        let Some(attr) = RdfAttr::parse(&field.attrs)? else { continue };
+        attr.check_field(field)?;
+        let ident = field.ident.as_ref().expect("named field");
+        if attr.skip {
+            continue;
+        }
+
+        if attr.flatten {
+            extra.push(quote! {
+                #krate::insert_flattened(&self.#ident, graph, iri.clone())?;
+            });
+            continue;
+        }
+
+        let Some(pred) = attr.pred else {
+            return Err(syn::Error::new_spanned(ident, "expected `pred` or `flatten`"));
+        };
+        let value = match attr.datatype {
+            Some(datatype) => quote! {
+                &#krate::TypedLiteral { value: &self.#ident, datatype: #datatype }
+            },
+            None => quote!(&self.#ident),
+        };
+
+        if attr.reverse {
+            extra.push(quote! {
+                #krate::InsertIntoGraph::attach_reverse_into(
+                    #value,
+                    graph,
+                    #krate::Link {
+                        source_iri: iri.clone(),
+                        pred: ::sophia_api::term::Term::as_simple(&#pred),
+                        target_iri: None,
+                    },
+                )?;
+            });
+        } else {
+            entries.push(quote!((#pred, #value as &dyn #krate::InsertIntoGraph)));
+        }
+    }
+
+    let table = (!entries.is_empty()).then(|| {
+        quote! {
+            for (pred, value) in [#(#entries),*] {
+                #krate::InsertIntoGraph::attach_into(
+                    value,
+                    graph,
+                    #krate::Link {
+                        source_iri: iri.clone(),
+                        pred: ::sophia_api::term::Term::as_simple(&pred),
+                        target_iri: None,
+                    },
+                )?;
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics #krate::InsertIntoGraph for #name #ty_generics #where_clause {
+            fn insert_into(
+                &self,
+                graph: &mut ::sophia::inmem::graph::LightGraph,
+                iri: ::sophia_api::term::SimpleTerm,
+            ) -> ::anyhow::Result<()> {
+                #table
+                #(#extra)*
+                Ok(())
+            }
+        }
+
+        impl #impl_generics #krate::Flatten for #name #ty_generics #where_clause {}
+    })
+}
+
+fn expand_from_graph(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let fields = named_fields(input, "FromGraph")?;
+
+    let krate = quote!(::catplus_common::graph::from_graph);
+    let mut values = Vec::new();
+
+    for field in fields {
+        let ident = field.ident.as_ref().expect("named field");
+        let ty = &field.ty;
+        let attr = RdfAttr::parse(&field.attrs)?;
+        if let Some(attr) = &attr {
+            attr.check_field(field)?;
+        }
+        let value = match attr {
+            None => {
+                return Err(syn::Error::new_spanned(
+                    ident,
+                    "missing `rdf` attribute, use `#[rdf(skip)]` for fields left out of the graph",
+                ))
+            }
+            Some(attr) if attr.skip => quote!(::std::default::Default::default()),
+            Some(attr) if attr.flatten => {
+                quote!(#krate::from_flattened::<#ty>(graph, iri)?)
+            }
+            Some(RdfAttr { pred: Some(pred), reverse, .. }) => quote! {
+                <#ty as #krate::FromGraph>::from_links(
+                    graph,
+                    iri,
+                    &::sophia_api::term::Term::as_simple(&#pred),
+                    #reverse,
+                )?
+            },
+            Some(_) => return Err(syn::Error::new_spanned(ident, "expected `pred` or `flatten`")),
+        };
+        values.push(quote!(#ident: #value));
+    }
+
+    let from_root = RdfAttr::parse(&input.attrs)?.and_then(|a| a.rdf_type).map(|rdf_type| {
+        quote! {
+            fn from_root(graph: &::sophia::inmem::graph::LightGraph) -> ::anyhow::Result<Self> {
+                let root = #krate::root_node(graph, &::sophia_api::term::Term::as_simple(&#rdf_type))?;
+                <Self as #krate::FromGraph>::from_graph(graph, &root)
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics #krate::FromGraph for #name #ty_generics #where_clause {
+            fn from_graph(
+                graph: &::sophia::inmem::graph::LightGraph,
+                iri: &::sophia_api::term::SimpleTerm,
+            ) -> ::anyhow::Result<Self> {
+                Ok(#name { #(#values),* })
+            }
+
+            #from_root
+        }
+    })
+}
@@ -0,0 +1,229 @@
+//! Conversion of many input files at once.
+use crate::{
+    convert::{
+        input_to_graph, serialize_graph, ConversionOptions, InputType, RdfFormat, RESOURCE_PREFIX,
+    },
+    validate::GraphValidator,
+};
+use anyhow::{Context, Result};
+use catplus_common::graph::merging::{merged_chemical_descriptions, Description};
+use rayon::prelude::*;
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// File name of the summary written in the output directory.
+pub const SUMMARY_FILE: &str = "summary.json";
+
+/// Outcome of the conversion of a single file.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Success,
+    Failure,
+}
+
+/// Description of each merged chemical of a converted file, see [merged_chemical_descriptions].
+type ChemicalDescriptions = BTreeMap<String, Description>;
+
+/// Result of the conversion of a single input file.
+#[derive(Serialize, Debug, Clone)]
+pub struct FileReport {
+    pub input: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<PathBuf>,
+    pub status: Status,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Problems that did not prevent the conversion, e.g. inconsistent chemical identifiers.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    /// Number of records left out by a lenient conversion.
+    #[serde(skip_serializing_if = "is_zero")]
+    pub skipped: usize,
+}
+
+fn is_zero(count: &usize) -> bool {
+    *count == 0
+}
+
+/// Machine-readable summary of a batch conversion.
+#[derive(Serialize, Debug, Clone)]
+pub struct BatchSummary {
+    pub total: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub files: Vec<FileReport>,
+}
+
+impl BatchSummary {
+    fn new(files: Vec<FileReport>) -> Self {
+        let succeeded = files.iter().filter(|file| file.status == Status::Success).count();
+        BatchSummary { total: files.len(), succeeded, failed: files.len() - succeeded, files }
+    }
+
+    /// Writes the summary as pretty-printed JSON.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self).context("Failed to serialize summary")?;
+        fs::write(path, json)
+            .with_context(|| format!("Failed to write summary '{}'", path.display()))
+    }
+}
+
+/// Lists the input files of a batch: the `.json` files of a directory, or the files
+/// matching a glob pattern (e.g. `data/**/*.json`), sorted by path.
+pub fn collect_inputs(input: &str) -> Result<Vec<PathBuf>> {
+    let input_path = Path::new(input);
+    let mut inputs = Vec::new();
+    if input_path.is_dir() {
+        for entry in fs::read_dir(input_path)
+            .with_context(|| format!("Failed to read input directory '{}'", input))?
+        {
+            let path = entry?.path();
+            if path.is_file() && path.extension().is_some_and(|ext| ext == "json") {
+                inputs.push(path);
+            }
+        }
+    } else {
+        for entry in
+            glob::glob(input).with_context(|| format!("Invalid glob pattern '{}'", input))?
+        {
+            let path = entry?;
+            if path.is_file() {
+                inputs.push(path);
+            }
+        }
+    }
+    inputs.sort();
+    Ok(inputs)
+}
+
+/// Converts every input file in parallel and writes the results into `output_dir`,
+/// named after the input file with the extension of `format`.
+///
+/// A file that fails to convert (or to validate, if there is a `validator`) is reported in the
+/// summary and does not stop the others.
+/// Input files that would be written to the same output file (e.g. `a/x.json` and
+/// `b/x.json`) are reported as failures instead of overwriting each other.
+///
+/// With merged chemicals, a chemical described differently than in an earlier input file
+/// is reported as a warning of the file.
+pub fn convert_batch(
+    inputs: &[PathBuf],
+    output_dir: &Path,
+    input_type: InputType,
+    format: &RdfFormat,
+    options: &ConversionOptions,
+    validator: Option<&GraphValidator>,
+) -> Result<BatchSummary> {
+    fs::create_dir_all(output_dir)
+        .with_context(|| format!("Failed to create output directory '{}'", output_dir.display()))?;
+
+    let outputs: Vec<PathBuf> =
+        inputs.iter().map(|input| output_path(input, output_dir, format)).collect();
+    let mut output_counts: HashMap<&Path, usize> = HashMap::new();
+    for output in &outputs {
+        *output_counts.entry(output).or_default() += 1;
+    }
+
+    let results: Vec<(FileReport, ChemicalDescriptions)> = inputs
+        .par_iter()
+        .zip(outputs.par_iter())
+        .map(|(input, output)| {
+            let result = if output_counts[output.as_path()] > 1 {
+                Err(anyhow::anyhow!(
+                    "Output file '{}' would be written by several input files",
+                    output.display()
+                ))
+            } else {
+                let options = options.for_input(input);
+                convert_file(input, output, input_type, format, &options, validator)
+            };
+            match result {
+                Ok((warnings, skipped, chemicals)) => (
+                    FileReport {
+                        input: input.clone(),
+                        output: Some(output.clone()),
+                        status: Status::Success,
+                        error: None,
+                        warnings,
+                        skipped,
+                    },
+                    chemicals,
+                ),
+                Err(error) => (
+                    FileReport {
+                        input: input.clone(),
+                        output: None,
+                        status: Status::Failure,
+                        error: Some(format!("{:#}", error)),
+                        warnings: Vec::new(),
+                        skipped: 0,
+                    },
+                    ChemicalDescriptions::new(),
+                ),
+            }
+        })
+        .collect();
+
+    let mut files = Vec::new();
+    let mut described: HashMap<String, (&Path, Description)> = HashMap::new();
+    for ((mut file, chemicals), input) in results.into_iter().zip(inputs) {
+        for (chemical, description) in chemicals {
+            match described.get(&chemical) {
+                Some((first, known)) if *known != description => file.warnings.push(format!(
+                    "Chemical <{}> is described differently in '{}'",
+                    chemical,
+                    first.display()
+                )),
+                Some(_) => {}
+                None => {
+                    described.insert(chemical, (input, description));
+                }
+            }
+        }
+        files.push(file);
+    }
+
+    Ok(BatchSummary::new(files))
+}
+
+fn convert_file(
+    input: &Path,
+    output: &Path,
+    input_type: InputType,
+    format: &RdfFormat,
+    options: &ConversionOptions,
+    validator: Option<&GraphValidator>,
+) -> Result<(Vec<String>, usize, ChemicalDescriptions)> {
+    let input_content = fs::read_to_string(input)
+        .with_context(|| format!("Failed to read input file '{}'", input.display()))?;
+    let context = || format!("Failed to convert JSON to RDF format '{:?}'", format);
+    let graph_builder =
+        input_to_graph(input_type, &input_content, options).with_context(context)?;
+    let serialized_graph = serialize_graph(&graph_builder, format).with_context(context)?;
+    let chemicals = match options.merge_chemicals {
+        Some(key) => merged_chemical_descriptions(&graph_builder.graph, key, RESOURCE_PREFIX)?,
+        None => ChemicalDescriptions::new(),
+    };
+
+    match validator {
+        Some(validator) => {
+            let report = validator.validate(&graph_builder.graph)?;
+            validator.write_output(output, &serialized_graph, &report)?;
+        }
+        None => fs::write(output, serialized_graph)
+            .with_context(|| format!("Failed to write to output file '{}'", output.display()))?,
+    }
+    Ok((graph_builder.warnings, graph_builder.skipped_items, chemicals))
+}
+
+fn output_path(input: &Path, output_dir: &Path, format: &RdfFormat) -> PathBuf {
+    let mut name = input.file_stem().unwrap_or(input.as_os_str()).to_os_string();
+    name.push(".");
+    name.push(format.extension());
+    output_dir.join(name)
+}
@@ -1,17 +1,214 @@
 use anyhow::{Context, Result};
-use catplus_common::graph::{graph_builder::GraphBuilder, insert_into::InsertIntoGraph};
-use serde::{de::DeserializeOwned, Deserialize};
+use catplus_common::{
+    chemistry::check::Severity,
+    error::{parse_json, parse_json_lenient, ConversionError, Records, SkippedItem},
+    graph::{
+        from_graph::FromGraph, graph_builder::GraphBuilder, insert_into::InsertIntoGraph,
+        merging::ChemicalKey, minting::IriMinting,
+    },
+    models::{
+        agilent::LiquidChromatographyAggregateDocumentWrapper,
+        hci::CampaignWrapper,
+        provenance::{ConversionActivity, SoftwareAgent},
+        synth::SynthBatch,
+    },
+    rdf::rdf_parser::parse_rdf_to_graph,
+    units::unit::UnitOptions,
+    zarr::{store::ZarrStore, write_into::WriteIntoZarr},
+};
+use serde::{Deserialize, Serialize};
+use sophia::inmem::graph::LightGraph;
+use std::path::Path;
 
-// Derive Deserialize and ValueEnum
-#[derive(Deserialize, Debug, clap::ValueEnum, Clone)]
-pub enum RdfFormat {
-    Turtle,
-    Jsonld,
+pub use catplus_common::rdf::rdf_format::RdfFormat;
+
+#[derive(Deserialize, Serialize, Debug, clap::ValueEnum, Clone, Copy, PartialEq, Eq)]
+pub enum InputType {
+    Synth,
+    HCI,
+    Agilent,
+    /// Detect the input type from the top-level keys of the document.
+    Auto,
+}
+
+/// Top-level keys identifying each input type, any of them is enough.
+const SIGNATURES: [(InputType, &[&str]); 3] = [
+    (InputType::Synth, &["batchID", "Actions"]),
+    (InputType::HCI, &["hasCampaign"]),
+    (InputType::Agilent, &["liquid chromatography aggregate document"]),
+];
+
+impl InputType {
+    /// Returns the input type of `input_content` if `self` is [InputType::Auto], `self` otherwise.
+    pub fn resolve(self, input_content: &str) -> Result<InputType> {
+        match self {
+            InputType::Auto => detect_input_type(input_content),
+            input_type => Ok(input_type),
+        }
+    }
+}
+
+/// Detects the input type of a JSON document from its top-level keys.
+pub fn detect_input_type(input_content: &str) -> Result<InputType> {
+    let document: serde_json::Value =
+        serde_json::from_str(input_content).context("Failed to parse JSON input")?;
+    let keys: Vec<&str> = match &document {
+        serde_json::Value::Object(map) => map.keys().map(String::as_str).collect(),
+        _ => Vec::new(),
+    };
+
+    let matches: Vec<InputType> = SIGNATURES
+        .iter()
+        .filter(|(_, signature)| signature.iter().any(|key| keys.contains(key)))
+        .map(|(input_type, _)| *input_type)
+        .collect();
+
+    match matches.as_slice() {
+        [input_type] => Ok(*input_type),
+        [] => anyhow::bail!(
+            "Could not recognise this document, expected one of the following top-level keys: {}",
+            describe_signatures()
+        ),
+        _ => anyhow::bail!(
+            "Could not recognise this document, it matches several input types ({:?}): {}",
+            matches,
+            describe_signatures()
+        ),
+    }
+}
+
+fn describe_signatures() -> String {
+    SIGNATURES
+        .iter()
+        .map(|(input_type, signature)| {
+            let keys: Vec<String> = signature.iter().map(|key| format!("'{}'", key)).collect();
+            format!("{} for {:?}", keys.join(" or "), input_type)
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Prefix of the IRIs minted for blank nodes.
+pub const RESOURCE_PREFIX: &str = "http://example.org/cat/resource/";
+
+/// Name of the graph in which the triples of a conversion are written, for TriG and N-Quads.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GraphName {
+    /// An explicit IRI.
+    Iri(String),
+    /// Derived from the name of the input file, e.g. `<prefix>graph/1-Synth`.
+    InputFile,
+    /// Derived from the batch ID of the input, e.g. `<prefix>graph/batch/23`.
+    BatchId,
+}
+
+impl std::str::FromStr for GraphName {
+    type Err = anyhow::Error;
+
+    /// Parses `file`, `batch` or an IRI.
+    fn from_str(value: &str) -> Result<Self> {
+        match value {
+            "file" => Ok(GraphName::InputFile),
+            "batch" => Ok(GraphName::BatchId),
+            iri => {
+                sophia::iri::Iri::new(iri)
+                    .with_context(|| format!("Expected 'file', 'batch' or an IRI: '{}'", iri))?;
+                Ok(GraphName::Iri(iri.to_string()))
+            }
+        }
+    }
+}
+
+/// Lineage of a conversion, recorded in the graph as a `prov:Activity` (see [ConversionActivity]).
+#[derive(Clone, Debug, Default)]
+pub struct Provenance {
+    /// Path of the input file, unknown if the input was not read from a file.
+    pub input_file: Option<String>,
+    /// Input type used for the conversion.
+    pub input_type: Option<InputType>,
+}
+
+/// Options controlling how the RDF graph is built.
+#[derive(Clone, Debug, Default)]
+pub struct ConversionOptions {
+    /// Strategy used to replace blank nodes with IRIs, blank nodes are kept if `None`.
+    pub minting: Option<IriMinting>,
+    /// Named graph of the triples, written to the default graph if `None`.
+    pub graph_name: Option<GraphName>,
+    /// Provenance of the conversion, not recorded if `None`.
+    pub provenance: Option<Provenance>,
+    /// Add the values of observations and measurements converted to SI units.
+    pub normalize_units: bool,
+    /// Check the identifiers of the chemicals: malformed identifiers fail the conversion,
+    /// inconsistent ones are reported as warnings of the graph.
+    pub check_chemicals: bool,
+    /// Merge the chemicals sharing this key into a single node, with an IRI derived from the
+    /// key so that the same chemical gets the same IRI in every converted file.
+    pub merge_chemicals: Option<ChemicalKey>,
+    /// Leave out the records of the input (e.g. actions, samples or peaks) that cannot be
+    /// parsed instead of failing, they are reported as warnings and counted in the graph.
+    pub lenient: bool,
+    /// How the units of the input are resolved to QUDT IRIs.
+    pub units: UnitOptions,
+    /// Replace a Zarr store left by an earlier conversion instead of failing.
+    pub overwrite_zarr: bool,
+}
+
+impl ConversionOptions {
+    /// Options that materialize blank nodes with random IRIs if `materialize` is set.
+    pub fn materialized(materialize: bool) -> Self {
+        ConversionOptions {
+            minting: materialize.then_some(IriMinting::Random),
+            ..ConversionOptions::default()
+        }
+    }
+
+    /// Options for the conversion of an input of type `input_type`, recorded in the provenance.
+    pub fn for_input_type(&self, input_type: InputType) -> Self {
+        let provenance = self
+            .provenance
+            .clone()
+            .map(|provenance| Provenance { input_type: Some(input_type), ..provenance });
+        ConversionOptions { provenance, ..self.clone() }
+    }
+
+    /// Options for the conversion of `input_file`, with [GraphName::InputFile] resolved to an IRI.
+    pub fn for_input(&self, input_file: &Path) -> Self {
+        let graph_name = match &self.graph_name {
+            Some(GraphName::InputFile) => {
+                let stem = input_file.file_stem().unwrap_or(input_file.as_os_str());
+                Some(GraphName::Iri(graph_iri(&[&stem.to_string_lossy()])))
+            }
+            graph_name => graph_name.clone(),
+        };
+        let provenance = self.provenance.clone().map(|provenance| Provenance {
+            input_file: Some(input_file.display().to_string()),
+            ..provenance
+        });
+        ConversionOptions { graph_name, provenance, ..self.clone() }
+    }
+}
+
+/// IRI of a named graph under [RESOURCE_PREFIX], with each path segment percent-encoded.
+fn graph_iri(segments: &[&str]) -> String {
+    let mut iri = format!("{}graph", RESOURCE_PREFIX);
+    for segment in segments {
+        iri.push('/');
+        for byte in segment.bytes() {
+            match byte {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                    iri.push(byte as char)
+                }
+                _ => iri.push_str(&format!("%{:02X}", byte)),
+            }
+        }
+    }
+    iri
 }
 
 /// Parses JSON and serializes the RDF graph to the specified format.
 ///
-/// This function can handle any struct that implements `serde::DeserializeOwned` and your `InsertIntoGraph` trait.
+/// This function can handle any struct that implements the `Records` and `InsertIntoGraph` traits.
 ///
 /// # Arguments
 /// - `input_content`: The JSON input as a string.
@@ -21,19 +218,261 @@ pub enum RdfFormat {
 /// A `Result` containing the serialized graph as a string or an error.
 pub fn json_to_rdf<T>(input_content: &str, format: &RdfFormat, materialize: bool) -> Result<String>
 where
-    T: DeserializeOwned + InsertIntoGraph, // Trait bounds
+    T: Records + InsertIntoGraph, // Trait bounds
+{
+    json_to_rdf_with_options::<T>(
+        input_content,
+        format,
+        &ConversionOptions::materialized(materialize),
+    )
+}
+
+/// Parses JSON and serializes the RDF graph to the specified format, as configured by `options`.
+///
+/// # Arguments
+/// - `input_content`: The JSON input as a string.
+/// - `format`: The desired serialization format.
+/// - `options`: How the graph is built.
+///
+/// # Returns
+/// A `Result` containing the serialized graph as a string or an error.
+pub fn json_to_rdf_with_options<T>(
+    input_content: &str,
+    format: &RdfFormat,
+    options: &ConversionOptions,
+) -> Result<String>
+where
+    T: Records + InsertIntoGraph,
 {
-    let data: T = parse_json(input_content).context("Failed to parse JSON input")?;
+    serialize_graph(&json_to_graph::<T>(input_content, options)?, format)
+}
 
+/// Parses JSON and builds its RDF graph, as configured by `options`.
+///
+/// # Arguments
+/// - `input_content`: The JSON input as a string.
+/// - `options`: How the graph is built.
+///
+/// # Returns
+/// A `Result` containing the graph, to be serialized with [serialize_graph], or a
+/// [ConversionError] locating the problem in the input.
+pub fn json_to_graph<T>(
+    input_content: &str,
+    options: &ConversionOptions,
+) -> Result<GraphBuilder, ConversionError>
+where
+    T: Records + InsertIntoGraph,
+{
+    options.units.scope(|| {
+        let (data, skipped): (T, _) = parse_input(input_content, options)?;
+
+        data_to_graph(&data, &skipped, input_content, options).map_err(ConversionError::Graph)
+    })
+}
+
+/// Parses JSON of the given input type and serializes the RDF graph to the specified format.
+///
+/// # Arguments
+/// - `input_type`: The type of the input, [InputType::Auto] is detected from the JSON keys.
+/// - `input_content`: The JSON input as a string.
+/// - `format`: The desired serialization format.
+/// - `options`: How the graph is built.
+///
+/// # Returns
+/// A `Result` containing the serialized graph as a string or an error.
+pub fn convert_input(
+    input_type: InputType,
+    input_content: &str,
+    format: &RdfFormat,
+    options: &ConversionOptions,
+) -> Result<String> {
+    serialize_graph(&input_to_graph(input_type, input_content, options)?, format)
+}
+
+/// Parses JSON of the given input type and builds its RDF graph, as configured by `options`.
+///
+/// # Arguments
+/// - `input_type`: The type of the input, [InputType::Auto] is detected from the JSON keys.
+/// - `input_content`: The JSON input as a string.
+/// - `options`: How the graph is built.
+///
+/// # Returns
+/// A `Result` containing the graph, to be serialized with [serialize_graph], or an error.
+pub fn input_to_graph(
+    input_type: InputType,
+    input_content: &str,
+    options: &ConversionOptions,
+) -> Result<GraphBuilder> {
+    let input_type = input_type.resolve(input_content)?;
+    let options = &options.for_input_type(input_type);
+    Ok(match input_type {
+        InputType::Synth => json_to_graph::<SynthBatch>(input_content, options)?,
+        InputType::HCI => json_to_graph::<CampaignWrapper>(input_content, options)?,
+        InputType::Agilent => {
+            json_to_graph::<LiquidChromatographyAggregateDocumentWrapper>(input_content, options)?
+        }
+        InputType::Auto => unreachable!("the input type is resolved"),
+    })
+}
+
+/// Parses JSON, writes its bulk data into a Zarr store and serializes the RDF graph to the specified format.
+///
+/// The graph links each node holding bulk data to the path of its arrays in the store.
+///
+/// # Arguments
+/// - `input_content`: The JSON input as a string.
+/// - `format`: The desired serialization format.
+/// - `options`: How the graph is built.
+/// - `zarr_root`: The directory in which the Zarr store is created.
+///
+/// # Returns
+/// A `Result` containing the serialized graph as a string or an error.
+pub fn json_to_rdf_with_zarr<T>(
+    input_content: &str,
+    format: &RdfFormat,
+    options: &ConversionOptions,
+    zarr_root: &Path,
+) -> Result<String>
+where
+    T: Records + InsertIntoGraph + WriteIntoZarr,
+{
+    serialize_graph(&json_to_graph_with_zarr::<T>(input_content, options, zarr_root)?, format)
+}
+
+/// Parses JSON, writes its bulk data into a Zarr store and builds its RDF graph.
+///
+/// # Arguments
+/// - `input_content`: The JSON input as a string.
+/// - `options`: How the graph is built.
+/// - `zarr_root`: The directory in which the Zarr store is created.
+///
+/// # Returns
+/// A `Result` containing the graph, to be serialized with [serialize_graph], or an error.
+pub fn json_to_graph_with_zarr<T>(
+    input_content: &str,
+    options: &ConversionOptions,
+    zarr_root: &Path,
+) -> Result<GraphBuilder>
+where
+    T: Records + InsertIntoGraph + WriteIntoZarr,
+{
+    options.units.scope(|| {
+        let (mut data, skipped): (T, _) = parse_input(input_content, options)?;
+
+        let store = ZarrStore::create(zarr_root, options.overwrite_zarr)
+            .with_context(|| format!("Failed to create Zarr store '{}'", zarr_root.display()))?;
+        data.write_into_zarr(&store, "").context("Failed to write Zarr store")?;
+
+        data_to_graph(&data, &skipped, input_content, options)
+    })
+}
+
+/// Parses JSON input, leaving out the records that cannot be parsed in lenient mode.
+fn parse_input<T>(
+    input_content: &str,
+    options: &ConversionOptions,
+) -> Result<(T, Vec<SkippedItem>), ConversionError>
+where
+    T: Records,
+{
+    match options.lenient {
+        true => parse_json_lenient(input_content),
+        false => Ok((parse_json(input_content)?, Vec::new())),
+    }
+}
+
+/// Builds the RDF graph of already parsed data, `skipped` are the items of the input left out
+/// by a lenient conversion.
+fn data_to_graph<T>(
+    data: &T,
+    skipped: &[SkippedItem],
+    input_content: &str,
+    options: &ConversionOptions,
+) -> Result<GraphBuilder>
+where
+    T: InsertIntoGraph,
+{
     let mut graph_builder = GraphBuilder::new();
-    graph_builder.insert(&data).context("Failed to build RDF graph")?;
+    graph_builder.insert(data).context("Failed to build RDF graph")?;
+    if options.lenient {
+        graph_builder
+            .insert_skipped_items(skipped.len())
+            .context("Failed to record skipped items")?;
+        graph_builder.warnings.extend(skipped.iter().map(ToString::to_string));
+    }
+    graph_builder.insert_inchi_keys().context("Failed to compute InChIKeys")?;
 
-    if materialize {
+    if let Some(key) = options.merge_chemicals {
+        graph_builder.merge_chemicals(key, RESOURCE_PREFIX).context("Failed to merge chemicals")?;
+    }
+
+    if options.normalize_units {
+        graph_builder.normalize_units().context("Failed to normalize units")?;
+    }
+
+    if options.check_chemicals {
+        let (errors, warnings): (Vec<_>, Vec<_>) = graph_builder
+            .check_chemicals()
+            .context("Failed to check chemicals")?
+            .into_iter()
+            .partition(|issue| issue.severity == Severity::Error);
+        if !errors.is_empty() {
+            let errors: Vec<String> = errors.iter().map(|error| format!("  {}", error)).collect();
+            anyhow::bail!("Invalid chemical identifiers:\n{}", errors.join("\n"));
+        }
+        graph_builder.warnings.extend(warnings.iter().map(ToString::to_string));
+    }
+
+    if let Some(minting) = options.minting {
         graph_builder
-            .materialize_blank_nodes(Some("http://example.org/cat/resource/"))
+            .mint_iris(RESOURCE_PREFIX, minting)
             .context("Failed to materialize blank nodes")?;
     }
 
+    // Recorded once the IRIs of the documents are minted, so that they do not depend on the
+    // time of the conversion. Only the nodes of the activity are left to mint.
+    if let Some(provenance) = &options.provenance {
+        let activity = ConversionActivity::new(
+            input_content,
+            provenance.input_file.clone(),
+            provenance.input_type.map(|input_type| format!("{:?}", input_type)),
+            SoftwareAgent {
+                name: env!("CARGO_PKG_NAME").to_string(),
+                software_version: env!("CARGO_PKG_VERSION").to_string(),
+            },
+        );
+        graph_builder.insert_provenance(&activity).context("Failed to record provenance")?;
+        if let Some(minting) = options.minting {
+            graph_builder
+                .mint_iris(RESOURCE_PREFIX, minting)
+                .context("Failed to materialize blank nodes")?;
+        }
+    }
+
+    if let Some(graph_name) = &options.graph_name {
+        let iri = match graph_name {
+            GraphName::Iri(iri) => iri.clone(),
+            GraphName::BatchId => match graph_builder.batch_id()? {
+                Some(batch_id) => graph_iri(&["batch", &batch_id]),
+                None => anyhow::bail!("No unique batch ID to name the graph after"),
+            },
+            GraphName::InputFile => {
+                anyhow::bail!("The graph cannot be named after the input file without its path")
+            }
+        };
+        graph_builder.set_graph_name(&iri)?;
+    }
+
+    Ok(graph_builder)
+}
+
+/// Serializes a graph built by [json_to_graph] or [input_to_graph] to the specified format.
+pub fn serialize_graph(graph_builder: &GraphBuilder, format: &RdfFormat) -> Result<String> {
+    if graph_builder.graph_name.is_some() && !matches!(format, RdfFormat::Trig | RdfFormat::NQuads)
+    {
+        anyhow::bail!("Named graphs can only be serialized to TriG or N-Quads");
+    }
+
     let serialized_graph = match format {
         RdfFormat::Jsonld => {
             graph_builder.serialize_to_jsonld().context("Failed to serialize to JSON-LD")?
@@ -41,15 +480,46 @@ where
         RdfFormat::Turtle => {
             graph_builder.serialize_to_turtle().context("Failed to serialize to Turtle")?
         }
+        RdfFormat::NTriples => {
+            graph_builder.serialize_to_ntriples().context("Failed to serialize to N-Triples")?
+        }
+        RdfFormat::NQuads => {
+            graph_builder.serialize_to_nquads().context("Failed to serialize to N-Quads")?
+        }
+        RdfFormat::Trig => {
+            graph_builder.serialize_to_trig().context("Failed to serialize to TriG")?
+        }
+        RdfFormat::RdfXml => {
+            graph_builder.serialize_to_rdfxml().context("Failed to serialize to RDF/XML")?
+        }
     };
 
     Ok(serialized_graph)
 }
 
-/// Parses a JSON string into a struct of type T.
-fn parse_json<T>(json_data: &str) -> Result<T>
+/// Parses RDF and serializes it back to CAT+ JSON, i.e. the inverse of [json_to_rdf].
+///
+/// # Arguments
+/// - `input_content`: The RDF input as a string.
+/// - `format`: The serialization format of the input.
+///
+/// # Returns
+/// A `Result` containing the JSON document as a string or an error.
+pub fn rdf_to_json<T>(input_content: &str, format: &RdfFormat) -> Result<String>
+where
+    T: FromGraph + Serialize,
+{
+    let graph = parse_rdf_to_graph(input_content, Some(format))?;
+
+    graph_to_json::<T>(&graph)
+}
+
+/// Rebuilds the data of type T from an RDF graph and serializes it to JSON.
+pub fn graph_to_json<T>(graph: &LightGraph) -> Result<String>
 where
-    T: DeserializeOwned, // Trait bound
+    T: FromGraph + Serialize,
 {
-    serde_json::from_str(json_data).map_err(|e| anyhow::Error::new(e))
+    let data = T::from_root(graph).context("Failed to read data from RDF graph")?;
+
+    serde_json::to_string_pretty(&data).context("Failed to serialize to JSON")
 }
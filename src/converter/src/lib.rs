@@ -1 +1,4 @@
+pub mod batch;
 pub mod convert;
+pub mod stream;
+pub mod validate;
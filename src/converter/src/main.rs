@@ -1,95 +1,403 @@
 use anyhow::{Context, Result};
-use catplus_common::models::{
-    agilent::LiquidChromatographyAggregateDocumentWrapper, hci::CampaignWrapper, synth::SynthBatch,
+use catplus_common::{
+    error::ConversionError,
+    graph::{merging::ChemicalKey, minting::IriMinting},
+    models::{
+        agilent::LiquidChromatographyAggregateDocumentWrapper, hci::CampaignWrapper,
+        synth::SynthBatch,
+    },
+    units::unit::UnitOptions,
 };
 use clap::Parser;
-use converter::convert::{json_to_rdf, RdfFormat};
-use serde::Deserialize;
+use converter::{
+    batch::{collect_inputs, convert_batch, SUMMARY_FILE},
+    convert::{
+        input_to_graph, json_to_graph_with_zarr, rdf_to_json, serialize_graph, ConversionOptions,
+        GraphName, InputType, Provenance, RdfFormat,
+    },
+    stream::stream_agilent_to_rdf,
+    validate::{report_path, GraphValidator},
+};
 use std::{
     fs::File,
-    io::{Read, Write},
+    io::{stdin, stdout, BufReader, BufWriter, Read, Write},
     path::Path,
 };
+use validation::shapes::ShapesSource;
 
-// Derive Deserialize and ValueEnum
-#[derive(Deserialize, Debug, clap::ValueEnum, Clone)]
-enum InputType {
-    Synth,
-    HCI,
-    Agilent,
-}
+/// Path of the input or output file meaning stdin or stdout.
+const STDIO: &str = "-";
 
 /// Converts CAT+ JSON input into RDF formats.
 ///
 /// This tool expects data similar to examples/1-Synth.json or examples/0-HCI.json
 /// This data is then transformed to RDF and
-/// serialized as Turtle (ttl) or JSON-LD (jsonld).
+/// serialized as Turtle (ttl), JSON-LD (jsonld), N-Triples (nt), N-Quads (nq), TriG or RDF/XML.
+/// With `--to-json`, RDF produced by this tool is converted back to JSON.
+/// With `--batch`, every file of a directory (or glob) is converted into an output directory.
+/// With `--stream`, large Agilent exports are converted without being loaded into memory.
+/// With `--shapes` or `--shacl-endpoint`, the graph is validated before it is written.
+/// With `-` as input or output file, the input is read from stdin or the output written to
+/// stdout, status messages are always written to stderr.
 #[derive(Parser, Debug)]
 struct Args {
-    /// Type of input data: "Synth", "HCI" or "Agilent".
+    /// Type of input data: "Synth", "HCI", "Agilent" or "Auto" to detect it from the JSON keys.
     #[arg(value_enum)]
     input_type: InputType,
 
-    /// Path to the input JSON file.
+    /// Path to the input JSON file, or `-` for stdin (with `--batch`, a directory or a glob
+    /// pattern).
     input_file: String,
 
-    /// Path to the output RDF file.
+    /// Path to the output RDF file, or `-` for stdout (with `--batch`, the output directory).
     output_file: String,
 
-    /// Type of input data: "Turtle" or "Jsonld".
+    /// RDF serialization format: "Turtle", "Jsonld", "NTriples", "NQuads", "Trig" or "RdfXml".
     #[arg(value_enum)]
     format: RdfFormat,
 
     /// Materialize blank nodes
     #[arg(long, default_value_t = false)]
     materialize: bool,
+
+    /// How IRIs are minted for blank nodes, implies `--materialize`.
+    /// "deterministic" derives stable IRIs from natural keys and content.
+    #[arg(long, value_enum, default_value = None)]
+    minting: Option<IriMinting>,
+
+    /// Directory of a Zarr store in which to write data cubes (Agilent only).
+    #[arg(long, default_value = None)]
+    zarr: Option<String>,
+
+    /// Replace the Zarr store left by an earlier conversion in the `--zarr` directory.
+    #[arg(long, default_value_t = false, requires = "zarr")]
+    overwrite_zarr: bool,
+
+    /// Write the triples in a named graph (TriG and N-Quads only): "file" to name it after
+    /// the input file, "batch" after the batch ID, or an explicit IRI.
+    #[arg(long, default_value = None)]
+    named_graph: Option<GraphName>,
+
+    /// Record the provenance of the conversion (input file and checksum, converter version,
+    /// time and input type) as a `prov:Activity` linked to the converted documents.
+    #[arg(long, default_value_t = false)]
+    provenance: bool,
+
+    /// Add the value of each observation and measurement converted to the SI unit of its
+    /// quantity kind (e.g. °C to K, min to s), linked with `cat:normalizedValue`.
+    #[arg(long, default_value_t = false)]
+    normalize_units: bool,
+
+    /// Check the CAS numbers, InChIs, SMILES and formulas of the chemicals: malformed
+    /// identifiers fail the conversion, disagreeing ones are printed as warnings.
+    #[arg(long, default_value_t = false)]
+    check_chemicals: bool,
+
+    /// Merge the chemicals sharing the same key, "id" (chemicalID) or "inchi", into a single
+    /// node named after the key, e.g. `<prefix>chemical/19`, so that a chemical has the same
+    /// IRI in every converted file.
+    #[arg(long, value_enum, default_value = None)]
+    merge_chemicals: Option<ChemicalKey>,
+
+    /// JSON file of additional units, an array of `{"label": "mbar", "iri": "unit:MilliBAR"}`
+    /// entries mapping UCUM expressions to QUDT IRIs (or CURIEs). Entries override the
+    /// built-in units.
+    #[arg(long, default_value = None)]
+    units: Option<String>,
+
+    /// Leave out the records that cannot be converted (e.g. an action, a sample, a measurement
+    /// document or a peak) instead of failing: they are printed as warnings, and their number
+    /// is recorded with `cat:skippedItems`.
+    #[arg(long, default_value_t = false)]
+    lenient: bool,
+
+    /// Keep units without known QUDT IRI as plain literals instead of failing the conversion.
+    #[arg(long, default_value_t = false)]
+    untyped_units: bool,
+
+    /// Validate the graph against these SHACL shapes before writing it, with the native
+    /// engine unless `--shacl-endpoint` is given. The shapes are a file (in any RDF format),
+    /// an HTTP(S) URL or a catplus-ontology release tag, downloaded shapes are cached.
+    #[arg(long, default_value = None)]
+    shapes: Option<ShapesSource>,
+
+    /// Validate the graph with the shacl-api server at this URL before writing it.
+    #[arg(long, default_value = None)]
+    shacl_endpoint: Option<String>,
+
+    /// Directory in which to write the graphs that do not conform to the shapes.
+    /// Without it, they are not written.
+    #[arg(long, default_value = None)]
+    quarantine: Option<String>,
+
+    /// Convert the RDF input file back to JSON, `format` is then the format of the input.
+    /// Only the units options apply to this conversion.
+    #[arg(
+        long,
+        default_value_t = false,
+        conflicts_with_all = [
+            "materialize",
+            "minting",
+            "zarr",
+            "named_graph",
+            "provenance",
+            "normalize_units",
+            "check_chemicals",
+            "merge_chemicals",
+            "lenient",
+            "shapes",
+            "shacl_endpoint",
+            "quarantine",
+        ]
+    )]
+    to_json: bool,
+
+    /// Convert all the JSON files of the input directory (or matching the input glob pattern)
+    /// in parallel, and write a summary of the results in the output directory.
+    #[arg(long, default_value_t = false, conflicts_with_all = ["zarr", "to_json"])]
+    batch: bool,
+
+    /// Convert the input while reading it, one measurement document at a time, for Agilent
+    /// exports too large to fit in memory (NTriples and Turtle only, without prefixes).
+    #[arg(
+        long,
+        default_value_t = false,
+        conflicts_with_all = ["batch", "to_json", "shapes", "shacl_endpoint", "quarantine"]
+    )]
+    stream: bool,
 }
 
 fn main() -> Result<()> {
     let args = Args::parse();
 
-    // Validate input file
-    let input_path = Path::new(&args.input_file);
-    if !input_path.exists() {
-        anyhow::bail!("Input file '{}' does not exist.", args.input_file);
+    if args.batch {
+        return run_batch(&args);
     }
-    if !input_path.is_file() {
-        anyhow::bail!("'{}' is not a valid file.", args.input_file);
+    if args.stream {
+        return run_stream(&args);
     }
 
-    // Read input file
+    // Read input file, or stdin
     let mut input_content = String::new();
-    File::open(input_path)
-        .with_context(|| format!("Failed to open input file '{}'", args.input_file))?
+    get_reader(&args.input_file)?
         .read_to_string(&mut input_content)
         .with_context(|| format!("Failed to read input file '{}'", args.input_file))?;
 
-    // Unified conversion function with type selection
-    let serialized_graph = match args.input_type {
-        InputType::Synth => {
-            json_to_rdf::<SynthBatch>(&input_content, &args.format, args.materialize)
-        }
-        InputType::HCI => {
-            json_to_rdf::<CampaignWrapper>(&input_content, &args.format, args.materialize)
-        }
-        InputType::Agilent => json_to_rdf::<LiquidChromatographyAggregateDocumentWrapper>(
+    if args.to_json {
+        let units = unit_options(&args)?;
+        let json = units
+            .scope(|| match args.input_type {
+                InputType::Synth => rdf_to_json::<SynthBatch>(&input_content, &args.format),
+                InputType::HCI => rdf_to_json::<CampaignWrapper>(&input_content, &args.format),
+                InputType::Agilent => rdf_to_json::<LiquidChromatographyAggregateDocumentWrapper>(
+                    &input_content,
+                    &args.format,
+                ),
+                InputType::Auto => {
+                    anyhow::bail!(
+                        "The input type cannot be detected with --to-json, please specify it."
+                    )
+                }
+            })
+            .with_context(|| format!("Failed to convert RDF format '{:?}' to JSON", args.format))?;
+
+        eprintln!("Conversion successful!");
+        return write_output(&args.output_file, &json);
+    }
+
+    let input_type = args.input_type.resolve(&input_content)?;
+
+    if args.zarr.is_some() && !matches!(input_type, InputType::Agilent) {
+        anyhow::bail!("Writing a Zarr store is only supported for Agilent input.");
+    }
+
+    let options = input_options(&args)?.for_input_type(input_type);
+
+    let validator = validator(&args)?;
+    if validator.is_some() && args.output_file == STDIO {
+        anyhow::bail!("Validation requires an output file, next to which the report is written.");
+    }
+
+    let context = || format!("Failed to convert JSON to RDF format '{:?}'", args.format);
+    let graph_builder = match &args.zarr {
+        Some(zarr) => json_to_graph_with_zarr::<LiquidChromatographyAggregateDocumentWrapper>(
             &input_content,
-            &args.format,
-            args.materialize,
+            &options,
+            Path::new(zarr),
         ),
+        None => input_to_graph(input_type, &input_content, &options),
+    }
+    .inspect_err(|error| print_excerpt(error, &input_content))
+    .with_context(context)?;
+    let serialized_graph = serialize_graph(&graph_builder, &args.format).with_context(context)?;
+    for warning in &graph_builder.warnings {
+        eprintln!("Warning: {}", warning);
+    }
+
+    match validator {
+        Some(validator) => {
+            let report = validator.validate(&graph_builder.graph)?;
+            let output_path = Path::new(&args.output_file);
+            validator.write_output(output_path, &serialized_graph, &report)?;
+            eprintln!("Conversion successful!");
+            eprintln!(
+                "Validated content written to '{}', validation report written to '{}'",
+                output_path.display(),
+                report_path(output_path).display()
+            );
+            Ok(())
+        }
+        None => {
+            eprintln!("Conversion successful!");
+            write_output(&args.output_file, &serialized_graph)
+        }
+    }
+}
+
+/// Builds the validator of the converted graphs, if validation is requested.
+fn validator(args: &Args) -> Result<Option<GraphValidator>> {
+    let validator =
+        GraphValidator::from_sources(args.shapes.as_ref(), args.shacl_endpoint.as_deref())?;
+    Ok(match (validator, &args.quarantine) {
+        (Some(validator), Some(quarantine)) => Some(validator.with_quarantine(quarantine.into())),
+        (None, Some(_)) => {
+            anyhow::bail!("A quarantine requires validation with --shapes or --shacl-endpoint.")
+        }
+        (validator, None) => validator,
+    })
+}
+
+fn run_batch(args: &Args) -> Result<()> {
+    let inputs = collect_inputs(&args.input_file)?;
+    if inputs.is_empty() {
+        anyhow::bail!("No input files found in '{}'.", args.input_file);
+    }
+
+    let output_dir = Path::new(&args.output_file);
+    let summary = convert_batch(
+        &inputs,
+        output_dir,
+        args.input_type,
+        &args.format,
+        &options(args)?,
+        validator(args)?.as_ref(),
+    )?;
+    let summary_path = output_dir.join(SUMMARY_FILE);
+    summary.write(&summary_path)?;
+
+    eprintln!(
+        "Converted {} of {} files, summary written to '{}'",
+        summary.succeeded,
+        summary.total,
+        summary_path.display()
+    );
+    if summary.failed > 0 {
+        anyhow::bail!("{} of {} files failed to convert.", summary.failed, summary.total);
     }
-    .with_context(|| format!("Failed to convert JSON to RDF format '{:?}'", &args.format))?;
+    Ok(())
+}
 
-    println!("Conversion successful!");
+fn run_stream(args: &Args) -> Result<()> {
+    if !matches!(args.input_type, InputType::Agilent) {
+        anyhow::bail!("Streaming conversion is only supported for Agilent input.");
+    }
+    let input = get_reader(&args.input_file)?;
+    let mut output = get_writer(&args.output_file)?;
 
-    // Write to output file
-    let output_path = Path::new(&args.output_file);
-    let mut output = File::create(output_path)
-        .with_context(|| format!("Failed to create output file '{}'", args.output_file))?;
+    let documents = stream_agilent_to_rdf(
+        input,
+        &mut output,
+        &args.format,
+        &input_options(args)?,
+        args.zarr.as_deref().map(Path::new),
+    )
+    .with_context(|| format!("Failed to convert JSON to RDF format '{:?}'", args.format))?;
     output
-        .write_all(serialized_graph.as_bytes())
+        .flush()
         .with_context(|| format!("Failed to write to output file '{}'", args.output_file))?;
 
-    println!("Processed content written to '{}'", output_path.display());
+    eprintln!("Conversion successful!");
+    eprintln!("{} measurement documents converted", documents);
+    if args.output_file != STDIO {
+        eprintln!("Processed content written to '{}'", args.output_file);
+    }
+    Ok(())
+}
+
+fn options(args: &Args) -> Result<ConversionOptions> {
+    Ok(ConversionOptions {
+        minting: args.minting.or(args.materialize.then_some(IriMinting::Random)),
+        graph_name: args.named_graph.clone(),
+        provenance: args.provenance.then(Provenance::default),
+        normalize_units: args.normalize_units,
+        check_chemicals: args.check_chemicals,
+        merge_chemicals: args.merge_chemicals,
+        lenient: args.lenient,
+        units: unit_options(args)?,
+        overwrite_zarr: args.overwrite_zarr,
+    })
+}
+
+fn unit_options(args: &Args) -> Result<UnitOptions> {
+    UnitOptions::load(args.units.as_deref().map(Path::new), args.untyped_units)
+}
+
+/// The options of the conversion of the single input of `args`, which has no path if it is
+/// read from stdin.
+fn input_options(args: &Args) -> Result<ConversionOptions> {
+    Ok(match args.input_file.as_str() {
+        STDIO => options(args)?,
+        input_file => options(args)?.for_input(Path::new(input_file)),
+    })
+}
+
+/// Prints the line of the input where a conversion error is located, if it is.
+fn print_excerpt(error: &anyhow::Error, input_content: &str) {
+    let excerpt = error
+        .chain()
+        .find_map(|error| error.downcast_ref::<ConversionError>())
+        .and_then(|error| error.excerpt(input_content));
+    if let Some(excerpt) = excerpt {
+        eprintln!("{}", excerpt);
+    }
+}
+
+fn write_output(output_file: &str, content: &str) -> Result<()> {
+    let mut output = get_writer(output_file)?;
+    output
+        .write_all(content.as_bytes())
+        .and_then(|()| output.flush())
+        .with_context(|| format!("Failed to write to output file '{}'", output_file))?;
+
+    if output_file != STDIO {
+        eprintln!("Processed content written to '{}'", output_file);
+    }
     Ok(())
 }
+
+/// Gets a reader of the input file, or of stdin if it is `-`.
+fn get_reader(input_file: &str) -> Result<Box<dyn Read>> {
+    if input_file == STDIO {
+        return Ok(Box::new(BufReader::new(stdin())));
+    }
+    let input_path = Path::new(input_file);
+    if !input_path.exists() {
+        anyhow::bail!("Input file '{}' does not exist.", input_file);
+    }
+    if !input_path.is_file() {
+        anyhow::bail!("'{}' is not a valid file.", input_file);
+    }
+    let input = File::open(input_path)
+        .with_context(|| format!("Failed to open input file '{}'", input_file))?;
+    Ok(Box::new(BufReader::new(input)))
+}
+
+/// Gets a writer to the output file, or to stdout if it is `-`.
+fn get_writer(output_file: &str) -> Result<Box<dyn Write>> {
+    if output_file == STDIO {
+        return Ok(Box::new(BufWriter::new(stdout())));
+    }
+    let output = File::create(output_file)
+        .with_context(|| format!("Failed to create output file '{}'", output_file))?;
+    Ok(Box::new(BufWriter::new(output)))
+}
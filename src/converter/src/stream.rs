@@ -0,0 +1,471 @@
+//! Streaming conversion of Agilent exports too large to be held in memory, e.g. LC-MS sequences
+//! with 3D mass spectrum cubes: measurement documents are deserialized one by one, and their
+//! triples are written out before the next one is read.
+use crate::convert::{ConversionOptions, RdfFormat, RESOURCE_PREFIX};
+use anyhow::{Context, Result};
+use catplus_common::{
+    error::json_error,
+    graph::{
+        graph_builder::GraphBuilder,
+        insert_into::{insert_position, InsertIntoGraph, Link},
+        minting::IriMinting,
+        namespaces::{allores, cat},
+        utils::generate_bnode_term,
+    },
+    models::agilent::{DeviceSystemDocument, MeasurementDocument},
+    rdf::rdf_serializers::StreamingSerializer,
+    zarr::{
+        store::{node_name, ZarrStore},
+        write_into::WriteIntoZarr,
+    },
+};
+use serde::de::{self, DeserializeSeed, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use sophia::{api::ns::rdf, inmem::graph::LightGraph};
+use sophia_api::{
+    graph::MutableGraph,
+    ns::NsTerm,
+    term::{SimpleTerm, Term},
+};
+use std::{fmt, io, path::Path};
+
+/// Converts an Agilent export read from `reader` to N-Triples or Turtle written to `writer`,
+/// one measurement document at a time, so that the memory used does not depend on the size of
+/// the input. The triples are the same as those of [crate::convert::json_to_graph], with
+/// other blank node identifiers.
+///
+/// If `zarr_root` is given, the data cubes are written into a Zarr store there, with the same
+/// layout as [crate::convert::json_to_graph_with_zarr].
+///
+/// Options that need the whole graph (deterministic IRIs, merged chemicals, provenance, named
+/// graphs and lenient mode) or report warnings (checked chemicals) are not supported.
+///
+/// # Returns
+/// The number of measurement documents converted, or an error, a
+/// [catplus_common::error::ConversionError] if the input cannot be parsed. The triples of the
+/// documents converted before an error are already written.
+pub fn stream_agilent_to_rdf<R: io::Read, W: io::Write>(
+    reader: R,
+    writer: W,
+    format: &RdfFormat,
+    options: &ConversionOptions,
+    zarr_root: Option<&Path>,
+) -> Result<usize> {
+    options.units.scope(|| stream(reader, writer, format, options, zarr_root))
+}
+
+fn stream<R: io::Read, W: io::Write>(
+    reader: R,
+    writer: W,
+    format: &RdfFormat,
+    options: &ConversionOptions,
+    zarr_root: Option<&Path>,
+) -> Result<usize> {
+    check_options(options)?;
+    let store = match zarr_root {
+        Some(root) => Some(
+            ZarrStore::create(root, options.overwrite_zarr)
+                .with_context(|| format!("Failed to create Zarr store '{}'", root.display()))?,
+        ),
+        None => None,
+    };
+    let mut emitter = Emitter {
+        serializer: StreamingSerializer::new(writer, format)?,
+        options,
+        store,
+        documents: 0,
+        error: None,
+    };
+
+    let mut deserializer = serde_json::Deserializer::from_reader(io::BufReader::new(reader));
+    let mut track = serde_path_to_error::Track::new();
+    let result = Export(&mut emitter)
+        .deserialize(serde_path_to_error::Deserializer::new(&mut deserializer, &mut track))
+        .and_then(|()| deserializer.end());
+    if let Err(error) = result {
+        return Err(match emitter.error.take() {
+            Some(error) => error,
+            None => {
+                json_error(serde_path_to_error::Error::new(track.path(), error), || None).into()
+            }
+        });
+    }
+
+    Ok(emitter.documents)
+}
+
+fn check_options(options: &ConversionOptions) -> Result<()> {
+    let unsupported = [
+        (options.minting == Some(IriMinting::Deterministic), "deterministic IRIs"),
+        (options.merge_chemicals.is_some(), "merging chemicals"),
+        (options.check_chemicals, "checking chemicals"),
+        (options.provenance.is_some(), "provenance"),
+        (options.graph_name.is_some(), "named graphs"),
+        (options.lenient, "lenient mode"),
+    ];
+    for (requested, option) in unsupported {
+        if requested {
+            anyhow::bail!("A streaming conversion does not support {}.", option);
+        }
+    }
+    Ok(())
+}
+
+/// Writes the graphs of the parts of the input as they are read.
+struct Emitter<'a, W: io::Write> {
+    serializer: StreamingSerializer<W>,
+    options: &'a ConversionOptions,
+    store: Option<ZarrStore>,
+    /// Number of measurement documents written.
+    documents: usize,
+    /// Error that stopped the conversion, reported instead of the deserialization error it causes.
+    error: Option<anyhow::Error>,
+}
+
+impl<W: io::Write> Emitter<'_, W> {
+    /// Writes the graph built by `build`, with the same processing as a whole input.
+    fn emit(&mut self, build: impl FnOnce(&mut LightGraph) -> Result<()>) -> Result<()> {
+        let mut graph_builder = GraphBuilder::new();
+        build(&mut graph_builder.graph).context("Failed to build RDF graph")?;
+        graph_builder.insert_inchi_keys().context("Failed to compute InChIKeys")?;
+        if self.options.normalize_units {
+            graph_builder.normalize_units().context("Failed to normalize units")?;
+        }
+        // Random IRIs only depend on the blank node identifiers, which are the same in every part.
+        if let Some(minting) = self.options.minting {
+            graph_builder
+                .mint_iris(RESOURCE_PREFIX, minting)
+                .context("Failed to materialize blank nodes")?;
+        }
+        self.serializer.serialize_graph(&graph_builder.graph)
+    }
+
+    /// Writes the data cubes of a measurement document at `path` in the Zarr store, if any,
+    /// then its triples, attached to its liquid chromatography document `parent` as its
+    /// `index`th measurement document.
+    fn measurement(
+        &mut self,
+        mut measurement: MeasurementDocument,
+        parent: &SimpleTerm<'static>,
+        path: &str,
+        index: usize,
+    ) -> Result<()> {
+        if let Some(store) = &self.store {
+            store.create_group(path, Default::default())?;
+            measurement.write_into_zarr(store, path).context("Failed to write Zarr store")?;
+        }
+        self.emit(|graph| {
+            let iri = measurement.get_uri();
+            insert_position(graph, &iri, index);
+            let attach = Link { target_iri: Some(iri), ..link(parent, allores::AFR_0002374) };
+            measurement.attach_into(graph, attach)
+        })?;
+        self.documents += 1;
+        Ok(())
+    }
+
+    /// Keeps `error` to report it, and returns a deserialization error to stop reading.
+    fn fail<E: de::Error>(&mut self, error: anyhow::Error) -> E {
+        let deserialization_error = E::custom(format!("{:#}", error));
+        self.error = Some(error);
+        deserialization_error
+    }
+}
+
+fn link<'a>(source: &SimpleTerm<'a>, pred: NsTerm<'a>) -> Link<'a, 'a, 'a> {
+    Link { source_iri: source.clone(), pred: pred.into_term(), target_iri: None }
+}
+
+/// The top-level object of an export.
+struct Export<'e, 'a, W: io::Write>(&'e mut Emitter<'a, W>);
+
+impl<'de, W: io::Write> DeserializeSeed<'de> for Export<'_, '_, W> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, W: io::Write> Visitor<'de> for Export<'_, '_, W> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "an Agilent export")
+    }
+
+    fn visit_map<M: MapAccess<'de>>(self, mut map: M) -> Result<(), M::Error> {
+        let mut found = false;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "liquid chromatography aggregate document" => {
+                    map.next_value_seed(AggregateDocument(&mut *self.0))?;
+                    found = true;
+                }
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        if !found {
+            return Err(de::Error::missing_field("liquid chromatography aggregate document"));
+        }
+        Ok(())
+    }
+}
+
+/// A `liquid chromatography aggregate document`.
+struct AggregateDocument<'e, 'a, W: io::Write>(&'e mut Emitter<'a, W>);
+
+impl<'de, W: io::Write> DeserializeSeed<'de> for AggregateDocument<'_, '_, W> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, W: io::Write> Visitor<'de> for AggregateDocument<'_, '_, W> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a liquid chromatography aggregate document")
+    }
+
+    fn visit_map<M: MapAccess<'de>>(self, mut map: M) -> Result<(), M::Error> {
+        let emitter = self.0;
+        let aggregate = generate_bnode_term();
+        emitter
+            .emit(|graph| {
+                graph.insert(&aggregate, rdf::type_, allores::AFR_0002524)?;
+                Ok(())
+            })
+            .map_err(|error| emitter.fail(error))?;
+
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "liquid chromatography document" => {
+                    map.next_value_seed(LiquidChromatographyDocuments {
+                        emitter: &mut *emitter,
+                        aggregate: &aggregate,
+                    })?;
+                }
+                "device system document" => {
+                    let document: Option<DeviceSystemDocument> = map.next_value()?;
+                    emitter
+                        .emit(|graph| {
+                            document.attach_into(graph, link(&aggregate, allores::AFR_0002526))
+                        })
+                        .map_err(|error| emitter.fail(error))?;
+                }
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The `liquid chromatography document` array of an aggregate document.
+struct LiquidChromatographyDocuments<'e, 'a, W: io::Write> {
+    emitter: &'e mut Emitter<'a, W>,
+    aggregate: &'e SimpleTerm<'static>,
+}
+
+impl<'de, W: io::Write> DeserializeSeed<'de> for LiquidChromatographyDocuments<'_, '_, W> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_option(self)
+    }
+}
+
+impl<'de, W: io::Write> Visitor<'de> for LiquidChromatographyDocuments<'_, '_, W> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "an array of liquid chromatography documents")
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+
+    fn visit_seq<S: SeqAccess<'de>>(self, mut seq: S) -> Result<(), S::Error> {
+        let mut index = 0;
+        while seq
+            .next_element_seed(LiquidChromatographyDocument {
+                emitter: &mut *self.emitter,
+                aggregate: self.aggregate,
+                index,
+            })?
+            .is_some()
+        {
+            index += 1;
+        }
+        Ok(())
+    }
+}
+
+/// A `liquid chromatography document`, the `index`th of its aggregate document.
+struct LiquidChromatographyDocument<'e, 'a, W: io::Write> {
+    emitter: &'e mut Emitter<'a, W>,
+    aggregate: &'e SimpleTerm<'static>,
+    index: usize,
+}
+
+impl<'de, W: io::Write> DeserializeSeed<'de> for LiquidChromatographyDocument<'_, '_, W> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, W: io::Write> Visitor<'de> for LiquidChromatographyDocument<'_, '_, W> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a liquid chromatography document")
+    }
+
+    fn visit_map<M: MapAccess<'de>>(self, mut map: M) -> Result<(), M::Error> {
+        let emitter = self.emitter;
+        let document = generate_bnode_term();
+        let path = format!("/liquid_chromatography_{}", self.index);
+        emitter
+            .emit(|graph| {
+                graph.insert(self.aggregate, cat::hasLiquidChromatography, &document)?;
+                graph.insert(&document, rdf::type_, allores::AFR_0002525)?;
+                insert_position(graph, &document, self.index);
+                Ok(())
+            })
+            .and_then(|()| match &emitter.store {
+                Some(store) => store.create_group(&path, Default::default()),
+                None => Ok(()),
+            })
+            .map_err(|error| emitter.fail(error))?;
+
+        let mut analyst = None;
+        let mut found = false;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "analyst" => analyst = Some(map.next_value::<String>()?),
+                // Not materialized in the graph, its measurement documents are attached to the
+                // liquid chromatography document (see LiquidChromatographyDocument).
+                "measurement aggregate document" => {
+                    map.next_value_seed(MeasurementAggregateDocument {
+                        emitter: &mut *emitter,
+                        parent: &document,
+                        path: &path,
+                    })?;
+                    found = true;
+                }
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        let analyst = analyst.ok_or_else(|| de::Error::missing_field("analyst"))?;
+        if !found {
+            return Err(de::Error::missing_field("measurement aggregate document"));
+        }
+        emitter
+            .emit(|graph| analyst.attach_into(graph, link(&document, allores::AFR_0001116)))
+            .map_err(|error| emitter.fail(error))
+    }
+}
+
+/// A `measurement aggregate document`, whose measurement documents are written one by one.
+struct MeasurementAggregateDocument<'e, 'a, W: io::Write> {
+    emitter: &'e mut Emitter<'a, W>,
+    parent: &'e SimpleTerm<'static>,
+    path: &'e str,
+}
+
+impl<'de, W: io::Write> DeserializeSeed<'de> for MeasurementAggregateDocument<'_, '_, W> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, W: io::Write> Visitor<'de> for MeasurementAggregateDocument<'_, '_, W> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a measurement aggregate document")
+    }
+
+    fn visit_map<M: MapAccess<'de>>(self, mut map: M) -> Result<(), M::Error> {
+        let mut found = false;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "measurement document" => {
+                    map.next_value_seed(MeasurementDocuments {
+                        emitter: &mut *self.emitter,
+                        parent: self.parent,
+                        path: self.path,
+                    })?;
+                    found = true;
+                }
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        if !found {
+            return Err(de::Error::missing_field("measurement document"));
+        }
+        Ok(())
+    }
+}
+
+/// The `measurement document` array of a measurement aggregate document.
+struct MeasurementDocuments<'e, 'a, W: io::Write> {
+    emitter: &'e mut Emitter<'a, W>,
+    parent: &'e SimpleTerm<'static>,
+    path: &'e str,
+}
+
+impl<'de, W: io::Write> DeserializeSeed<'de> for MeasurementDocuments<'_, '_, W> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, W: io::Write> Visitor<'de> for MeasurementDocuments<'_, '_, W> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "an array of measurement documents")
+    }
+
+    fn visit_seq<S: SeqAccess<'de>>(self, mut seq: S) -> Result<(), S::Error> {
+        let mut index = 0;
+        while let Some(measurement) = seq.next_element::<MeasurementDocument>()? {
+            let path = format!(
+                "{}/{}_{}",
+                self.path,
+                index,
+                node_name(&measurement.measurement_identifier)
+            );
+            self.emitter
+                .measurement(measurement, self.parent, &path, index)
+                .map_err(|error| self.emitter.fail(error))?;
+            index += 1;
+        }
+        Ok(())
+    }
+}
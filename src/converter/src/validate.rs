@@ -0,0 +1,143 @@
+//! Validation of converted graphs before they are written.
+use anyhow::{Context, Result};
+use catplus_common::rdf::rdf_serializers::serialize_graph_to_turtle;
+use sophia::inmem::graph::LightGraph;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use validation::{
+    core::{ShaclEngine, ShapesInfo, ValidationReport},
+    engines::{native::NativeShaclEngine, shacl_api::ShaclApiEndpoint},
+    shapes::{ShapesCache, ShapesSource},
+};
+
+/// Validates converted graphs with a SHACL engine, and only writes the conforming ones
+/// to their output.
+pub struct GraphValidator {
+    engine: Box<dyn ShaclEngine + Send + Sync>,
+    shapes: Option<LightGraph>,
+    shapes_info: Option<ShapesInfo>,
+    quarantine: Option<PathBuf>,
+}
+
+impl GraphValidator {
+    /// Validates graphs with `engine` against `shapes`, or the default shapes of the engine.
+    pub fn new(engine: Box<dyn ShaclEngine + Send + Sync>, shapes: Option<LightGraph>) -> Self {
+        GraphValidator { engine, shapes, shapes_info: None, quarantine: None }
+    }
+
+    /// Validates graphs against `shapes` (resolved with the default [ShapesCache]) with the
+    /// native engine, or with the shacl-api server at `endpoint` if given. Returns `None`
+    /// without shapes or endpoint.
+    ///
+    /// Downloading the shapes and checking that the endpoint is available block, so this must
+    /// not be called from an async runtime.
+    pub fn from_sources(
+        shapes: Option<&ShapesSource>,
+        endpoint: Option<&str>,
+    ) -> Result<Option<Self>> {
+        let shapes = match shapes {
+            Some(source) => Some(ShapesCache::new(ShapesCache::default_dir()).resolve(source)?),
+            None => None,
+        };
+
+        let engine: Box<dyn ShaclEngine + Send + Sync> = match (endpoint, &shapes) {
+            (Some(endpoint), _) => {
+                let shacl_api = ShaclApiEndpoint::new(endpoint.to_string());
+                if !shacl_api.is_available() {
+                    anyhow::bail!("SHACL API is not available at {}", endpoint);
+                }
+                Box::new(shacl_api)
+            }
+            (None, Some(_)) => Box::new(NativeShaclEngine::new()),
+            (None, None) => return Ok(None),
+        };
+
+        Ok(Some(match shapes {
+            Some(shapes) => {
+                GraphValidator::new(engine, Some(shapes.graph)).with_shapes_info(shapes.info)
+            }
+            None => GraphValidator::new(engine, None),
+        }))
+    }
+
+    /// Writes non-conforming graphs into the `quarantine` directory instead of discarding them.
+    pub fn with_quarantine(self, quarantine: PathBuf) -> Self {
+        GraphValidator { quarantine: Some(quarantine), ..self }
+    }
+
+    /// Records where the shapes come from in the validation reports.
+    pub fn with_shapes_info(self, shapes_info: ShapesInfo) -> Self {
+        GraphValidator { shapes_info: Some(shapes_info), ..self }
+    }
+
+    pub fn validate(&self, graph: &LightGraph) -> Result<ValidationReport> {
+        let report = self
+            .engine
+            .validate(graph, self.shapes.as_ref())
+            .map_err(|e| anyhow::anyhow!("Validation failed: {}", e))?;
+        match &self.shapes_info {
+            Some(shapes_info) => report
+                .with_shapes(shapes_info.clone())
+                .map_err(|e| anyhow::anyhow!("Failed to record the shapes in the report: {}", e)),
+            None => Ok(report),
+        }
+    }
+
+    /// Writes `serialized_graph` to `output` and its validation report next to it
+    /// (see [report_path]) if the graph conforms.
+    ///
+    /// Otherwise the graph and its report are written into the quarantine directory if there
+    /// is one, or only the report is written next to `output`, and an error is returned.
+    pub fn write_output(
+        &self,
+        output: &Path,
+        serialized_graph: &str,
+        report: &ValidationReport,
+    ) -> Result<()> {
+        let report_turtle =
+            serialize_graph_to_turtle(&report.graph).context("Failed to serialize report")?;
+
+        if report.conforms {
+            write_file(output, serialized_graph)?;
+            return write_file(&report_path(output), &report_turtle);
+        }
+
+        match &self.quarantine {
+            Some(quarantine) => {
+                fs::create_dir_all(quarantine).with_context(|| {
+                    format!("Failed to create quarantine directory '{}'", quarantine.display())
+                })?;
+                let quarantined = quarantine.join(output.file_name().unwrap_or_default());
+                write_file(&quarantined, serialized_graph)?;
+                write_file(&report_path(&quarantined), &report_turtle)?;
+                anyhow::bail!(
+                    "The graph does not conform to the shapes ({} results), it was written to '{}'",
+                    report.results.len(),
+                    quarantined.display()
+                )
+            }
+            None => {
+                write_file(&report_path(output), &report_turtle)?;
+                anyhow::bail!(
+                    "The graph does not conform to the shapes ({} results), see '{}'",
+                    report.results.len(),
+                    report_path(output).display()
+                )
+            }
+        }
+    }
+}
+
+/// Path of the validation report of `output`, e.g. `1-Synth.ttl.validation.ttl`.
+pub fn report_path(output: &Path) -> PathBuf {
+    let mut name = output.file_name().unwrap_or_default().to_os_string();
+    name.push(".validation.ttl");
+    output.with_file_name(name)
+}
+
+fn write_file(path: &Path, content: &str) -> Result<()> {
+    fs::write(path, content)
+        .with_context(|| format!("Failed to write to output file '{}'", path.display()))
+}
@@ -1,9 +1,18 @@
 use catplus_common::{
-    models::agilent::LiquidChromatographyAggregateDocumentWrapper,
-    rdf::rdf_parser::parse_turtle_to_graph,
+    error::ConversionError,
+    models::agilent::{Dimension, LiquidChromatographyAggregateDocumentWrapper, Measure},
+    rdf::rdf_parser::{parse_rdf_to_graph, parse_turtle_to_graph},
 };
-use converter::convert::{json_to_rdf, RdfFormat};
+use converter::{
+    convert::{
+        json_to_graph, json_to_rdf, json_to_rdf_with_zarr, rdf_to_json, ConversionOptions,
+        RdfFormat,
+    },
+    stream::stream_agilent_to_rdf,
+};
+use serde_json::{json, Value};
 use sophia_isomorphism::isomorphic_graphs;
+use std::fs;
 
 #[test]
 fn test_materialize_blank_nodes() {
@@ -256,14 +265,17 @@ fn test_convert_liquid_chromatography() {
 
     [] a allores:AFR_0002524;
     cat:hasLiquidChromatography [ a allores:AFR_0002525;
+        schema:position 0;
         allores:AFR_0001116 "Swisscat (swisscat)";
         allores:AFR_0002374 [ a allores:AFR_0002375;
+            schema:position 1;
             allores:AFR_0001121 "DAD1B";
             allores:AFR_0002083 [ a cat:SampleDocument;
                 allores:AFR_0001118 "0659d110-49d0-4e98-8f3a-1aaf9c4ec0d9";
                 obo:IAO_0000590 "1-4 PYRIDYL PIPERAZINE-2024-04-12 10-23-04+02-00-20.dx"];
             allores:AFR_0002526 [ a cat:DeviceSystemDocument;
                 allores:AFR_0002722 [ a allores:AFR_0002567;
+                    schema:position 0;
                     allores:AFR_0001119 "DEAC617961";
                     allores:AFR_0001258 "Agilent";
                     allores:AFR_0001259 "D.07.38 [0001]";
@@ -283,22 +295,26 @@ fn test_convert_liquid_chromatography() {
                 obo:IAO_0000009 "DAD1B,Sig=254,4  Ref=off";
                 qb:structure [ a cat:CubeStructure;
                     cat:dimension [ a cat:Dimension;
+                        schema:position 0;
                         allodc:componentDataType "double";
                         qudt:unit unit:SEC;
                         <http://www.w3.org/2000/01/rdf-schema#label> "retention time"];
                     cat:measure [ a allorole:AFRL_0000157;
+                        schema:position 0;
                         allodc:componentDataType "double";
                         qudt:unit qudtext:MilliAbsorbanceUnit;
                         <http://www.w3.org/2000/01/rdf-schema#label> "absorbance"]]];
             allores:AFR_0002659 [ a cat:ProcessedDataDocument;
                 allores:AFR_0000432 [ a cat:PeakList]]],
             [ a allores:AFR_0002375;
+            schema:position 0;
             allores:AFR_0001121 "DAD1A";
             allores:AFR_0002083 [ a cat:SampleDocument;
                 allores:AFR_0001118 "0659d110-49d0-4e98-8f3a-1aaf9c4ec0d9";
                 obo:IAO_0000590 "1-4 PYRIDYL PIPERAZINE-2024-04-12 10-23-04+02-00-20.dx"];
             allores:AFR_0002526 [ a cat:DeviceSystemDocument;
                 allores:AFR_0002722 [ a allores:AFR_0002567;
+                    schema:position 0;
                     allores:AFR_0001119 "DEAC617961";
                     allores:AFR_0001258 "Agilent";
                     allores:AFR_0001259 "D.07.38 [0001]";
@@ -318,16 +334,19 @@ fn test_convert_liquid_chromatography() {
                 obo:IAO_0000009 "DAD1A,Sig=215,4  Ref=off";
                 qb:structure [ a cat:CubeStructure;
                     cat:dimension [ a cat:Dimension;
+                        schema:position 0;
                         allodc:componentDataType "double";
                         qudt:unit unit:SEC;
                         <http://www.w3.org/2000/01/rdf-schema#label> "retention time"];
                     cat:measure [ a allorole:AFRL_0000157;
+                        schema:position 0;
                         allodc:componentDataType "double";
                         qudt:unit qudtext:MilliAbsorbanceUnit;
                         <http://www.w3.org/2000/01/rdf-schema#label> "absorbance"]]];
             allores:AFR_0002659 [ a cat:ProcessedDataDocument;
                 allores:AFR_0000432 [ a cat:PeakList;
                     cat:Peak [ a allores:AFR_0000413;
+                        schema:position 0;
                         allores:AFR_0000948 [ a cat:Measurement;
                             qudt:unit qudtext:MilliAbsorbanceUnit;
                             qudt:value "3058.31"^^xsd:double];
@@ -426,6 +445,7 @@ fn test_convert_device_system_document() {
     allores:AFR_0002526 [ a cat:DeviceSystemDocument;
         allores:AFR_0001976 "a7155146-e1d0-41be-99bf-eb2e55f9766e";
         allores:AFR_0002722 [ a allores:AFR_0002567;
+            schema:position 0;
             allores:AFR_0001119 "DEAGZ02881";
             allores:AFR_0001258 "Agilent";
             allores:AFR_0001259 "B.07.38 [0003]";
@@ -433,6 +453,7 @@ fn test_convert_device_system_document() {
             allores:AFR_0002568 "Pump";
             obo:IAO_0000017 "G7104C"],
             [ a allores:AFR_0002567;
+            schema:position 1;
             allores:AFR_0001119 "DEAGW00219";
             allores:AFR_0001258 "Agilent";
             allores:AFR_0001259 "D.07.38 [0003]";
@@ -447,3 +468,325 @@ fn test_convert_device_system_document() {
     let graphs_match = isomorphic_graphs(&result_graph, &expected_graph);
     assert_eq!(graphs_match.unwrap(), true);
 }
+
+#[test]
+fn test_write_chromatogram_to_zarr() {
+    let output_format = RdfFormat::Turtle;
+    let json_data = r#"
+    {
+        "liquid chromatography aggregate document": {
+            "liquid chromatography document": [
+                {
+                    "analyst": "Swisscat (swisscat)",
+                    "measurement aggregate document": {
+                        "measurement document": [
+                            {
+                                "measurement identifier": "DAD1A",
+                                "device control aggregate document": {
+                                    "device control document": []
+                                },
+                                "sample document": {
+                                    "sample identifier": "0659d110-49d0-4e98-8f3a-1aaf9c4ec0d9",
+                                    "written name": "1-4 PYRIDYL PIPERAZINE-2024-04-12 10-23-04+02-00-20.dx"
+                                },
+                                "injection document": {
+                                    "autosampler injection volume setting (chromatography)": {
+                                        "value": 5,
+                                        "unit": "mm^3"
+                                    },
+                                    "injection identifier": "2024-04-12 10-23-04+02-00-20.dx",
+                                    "injection time": "2024-04-12T08:23:47.113+00:00"
+                                },
+                                "detection type": "single channel",
+                                "chromatogram data cube": {
+                                    "label": "DAD1A,Sig=215,4  Ref=off",
+                                    "cube-structure": {
+                                        "dimensions": [
+                                            {
+                                                "@componentDatatype": "double",
+                                                "concept": "retention time",
+                                                "unit": "s"
+                                            }
+                                        ],
+                                        "measures": [
+                                            {
+                                                "@componentDatatype": "double",
+                                                "concept": "absorbance",
+                                                "unit": "mAU"
+                                            }
+                                        ]
+                                    },
+                                    "data": {
+                                        "measures": [[-0.870228, -0.5, 0.25]],
+                                        "dimensions": [[0.2, 0.4, 0.6]]
+                                    },
+                                    "identifier": "DAD1A"
+                                }
+                            }
+                        ]
+                    }
+                }
+            ]
+        }
+    }
+    "#;
+    let zarr_root = std::env::temp_dir().join(format!("catplus-zarr-{}", uuid::Uuid::new_v4()));
+    let result = json_to_rdf_with_zarr::<LiquidChromatographyAggregateDocumentWrapper>(
+        json_data,
+        &output_format,
+        &ConversionOptions::default(),
+        &zarr_root,
+    );
+    let result_ttl = result.unwrap();
+
+    let cube_path = "/liquid_chromatography_0/0_DAD1A/chromatogram_data_cube";
+    assert!(result_ttl.contains(cube_path));
+
+    let cube_dir = zarr_root.join(cube_path.trim_start_matches('/'));
+    assert!(zarr_root.join("zarr.json").is_file());
+    assert!(cube_dir.join("zarr.json").is_file());
+    let measure = fs::read(cube_dir.join("measures/0_absorbance/c/0")).unwrap();
+    let dimension = fs::read(cube_dir.join("dimensions/0_retention_time/c/0")).unwrap();
+    assert_eq!(measure.len(), 3 * 8);
+    assert_eq!(f64::from_le_bytes(dimension[8..16].try_into().unwrap()), 0.4);
+
+    fs::remove_dir_all(zarr_root).unwrap();
+}
+
+/// An export with a device system document and `count` measurement documents.
+#[test]
+fn test_round_trip_json() {
+    let mut export: Value = serde_json::from_str(&agilent_export(3)).unwrap();
+    let device = json!({
+        "device identifier": "Sampler",
+        "device type": "Autosampler",
+        "model number": "G7167A",
+        "product manufacturer": "Agilent",
+        "equipment serial number": "DEAGW00219",
+        "firmware version": "D.07.38 [0003]",
+        "@index": 2
+    });
+    export["liquid chromatography aggregate document"]["device system document"]["device document"]
+        .as_array_mut()
+        .unwrap()
+        .push(device);
+    let json_data = export.to_string();
+
+    let turtle = json_to_rdf::<LiquidChromatographyAggregateDocumentWrapper>(
+        &json_data,
+        &RdfFormat::Turtle,
+        false,
+    )
+    .unwrap();
+    let result =
+        rdf_to_json::<LiquidChromatographyAggregateDocumentWrapper>(&turtle, &RdfFormat::Turtle)
+            .unwrap();
+
+    // The arrays keep their order, but the data cubes (written to Zarr) and the `@index` of the
+    // documents are not part of the graph.
+    let normalize = |json: &str| {
+        let model: LiquidChromatographyAggregateDocumentWrapper =
+            serde_json::from_str(json).unwrap();
+        without_keys(serde_json::to_value(model).unwrap(), &["data", "@index"])
+    };
+    let result = normalize(&result);
+    let documents = &result["liquid chromatography aggregate document"]
+        ["liquid chromatography document"][0]["measurement aggregate document"]
+        ["measurement document"];
+    let identifiers: Vec<_> = (0..3).map(|i| &documents[i]["measurement identifier"]).collect();
+    assert_eq!(identifiers, ["DAD0", "DAD1", "DAD2"]);
+    assert_eq!(result, normalize(&json_data));
+}
+
+fn without_keys(value: Value, keys: &[&str]) -> Value {
+    match value {
+        Value::Array(items) => items.into_iter().map(|item| without_keys(item, keys)).collect(),
+        Value::Object(map) => map
+            .into_iter()
+            .filter(|(key, _)| !keys.contains(&key.as_str()))
+            .map(|(key, item)| (key, without_keys(item, keys)))
+            .collect(),
+        other => other,
+    }
+}
+
+fn agilent_export(count: usize) -> String {
+    let measurements: Vec<_> = (0..count)
+        .map(|index| {
+            json!({
+                "measurement identifier": format!("DAD{}", index),
+                "device control aggregate document": { "device control document": [] },
+                "sample document": {
+                    "sample identifier": format!("sample-{}", index),
+                    "written name": "1-4 PYRIDYL PIPERAZINE"
+                },
+                "injection document": {
+                    "autosampler injection volume setting (chromatography)": {
+                        "value": 5,
+                        "unit": "mm^3"
+                    },
+                    "injection identifier": format!("injection-{}", index),
+                    "injection time": "2024-04-12T08:23:47.113+00:00"
+                },
+                "detection type": "single channel",
+                "chromatogram data cube": {
+                    "label": "DAD1A,Sig=215,4  Ref=off",
+                    "cube-structure": {
+                        "dimensions": [
+                            { "@componentDatatype": "double", "concept": "retention time", "unit": "s" }
+                        ],
+                        "measures": [
+                            { "@componentDatatype": "double", "concept": "absorbance", "unit": "mAU" }
+                        ]
+                    },
+                    "data": {
+                        "measures": [[-0.870228, -0.5, 0.25]],
+                        "dimensions": [[0.2, 0.4, 0.6]]
+                    },
+                    "identifier": "DAD1A"
+                }
+            })
+        })
+        .collect();
+    json!({
+        "liquid chromatography aggregate document": {
+            "liquid chromatography document": [
+                {
+                    "analyst": "Swisscat (swisscat)",
+                    "measurement aggregate document": { "measurement document": measurements }
+                }
+            ],
+            "device system document": {
+                "asset management identifier": "a7155146-e1d0-41be-99bf-eb2e55f9766e",
+                "device document": [
+                    {
+                        "device identifier": "LC Pump",
+                        "device type": "Pump",
+                        "model number": "G7104C",
+                        "product manufacturer": "Agilent",
+                        "equipment serial number": "DEAGZ02881",
+                        "firmware version": "B.07.38 [0003]",
+                        "@index": 1
+                    }
+                ]
+            }
+        }
+    })
+    .to_string()
+}
+
+#[test]
+fn test_stream_liquid_chromatography() {
+    let export = agilent_export(3);
+    let options = ConversionOptions::default();
+    let expected_graph =
+        json_to_graph::<LiquidChromatographyAggregateDocumentWrapper>(&export, &options)
+            .unwrap()
+            .graph;
+
+    for format in [RdfFormat::NTriples, RdfFormat::Turtle] {
+        let mut output = Vec::new();
+        let documents =
+            stream_agilent_to_rdf(export.as_bytes(), &mut output, &format, &options, None).unwrap();
+        assert_eq!(documents, 3);
+
+        let output = String::from_utf8(output).unwrap();
+        let graph = parse_rdf_to_graph(&output, Some(&format)).unwrap();
+        assert!(isomorphic_graphs(&graph, &expected_graph).unwrap(), "{:?}: {}", format, output);
+    }
+
+    let error =
+        stream_agilent_to_rdf(export.as_bytes(), Vec::new(), &RdfFormat::Jsonld, &options, None)
+            .unwrap_err();
+    assert!(error.to_string().contains("cannot be written as a stream"));
+
+    let checked = ConversionOptions { check_chemicals: true, ..ConversionOptions::default() };
+    let error =
+        stream_agilent_to_rdf(export.as_bytes(), Vec::new(), &RdfFormat::NTriples, &checked, None)
+            .unwrap_err();
+    assert!(error.to_string().contains("does not support checking chemicals"), "{}", error);
+}
+
+#[test]
+fn test_stream_to_zarr() {
+    let zarr_root = std::env::temp_dir().join(format!("catplus-zarr-{}", uuid::Uuid::new_v4()));
+    let mut output = Vec::new();
+    stream_agilent_to_rdf(
+        agilent_export(2).as_bytes(),
+        &mut output,
+        &RdfFormat::NTriples,
+        &ConversionOptions::default(),
+        Some(&zarr_root),
+    )
+    .unwrap();
+
+    let cube_path = "/liquid_chromatography_0/1_DAD1/chromatogram_data_cube";
+    assert!(String::from_utf8(output).unwrap().contains(cube_path));
+    let cube_dir = zarr_root.join(cube_path.trim_start_matches('/'));
+    let measure = fs::read(cube_dir.join("measures/0_absorbance/c/0")).unwrap();
+    assert_eq!(measure.len(), 3 * 8);
+
+    // The store of an earlier conversion is only replaced if asked.
+    let stream = |options: &ConversionOptions| {
+        stream_agilent_to_rdf(
+            agilent_export(1).as_bytes(),
+            Vec::new(),
+            &RdfFormat::NTriples,
+            options,
+            Some(&zarr_root),
+        )
+    };
+    let error = stream(&ConversionOptions::default()).unwrap_err();
+    assert!(format!("{:#}", error).contains("is already a Zarr store"), "{:#}", error);
+    assert!(cube_dir.join("measures/0_absorbance/c/0").is_file());
+    stream(&ConversionOptions { overwrite_zarr: true, ..ConversionOptions::default() }).unwrap();
+    assert!(!cube_dir.exists());
+
+    fs::remove_dir_all(zarr_root).unwrap();
+}
+
+#[test]
+fn test_stream_invalid_measurement() {
+    // The second measurement document has no detection type.
+    let export = agilent_export(2);
+    let detection_type = r#""detection type":"single channel","#;
+    let position = export.rfind(detection_type).unwrap();
+    let export = format!("{}{}", &export[..position], &export[position + detection_type.len()..]);
+    let mut output = Vec::new();
+    let error = stream_agilent_to_rdf(
+        export.as_bytes(),
+        &mut output,
+        &RdfFormat::NTriples,
+        &ConversionOptions::default(),
+        None,
+    )
+    .unwrap_err();
+
+    match error.downcast_ref::<ConversionError>() {
+        Some(ConversionError::Schema { message, location }) => {
+            assert_eq!(message, "missing field `detection type`");
+            assert_eq!(
+                location.pointer,
+                "/liquid chromatography aggregate document/liquid chromatography document/0\
+                 /measurement aggregate document/measurement document/1"
+            );
+        }
+        _ => panic!("unexpected error: {:?}", error),
+    }
+    // The documents read before the error are already written.
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("\"DAD0\"") && !output.contains("\"DAD1\""), "{}", output);
+}
+
+#[test]
+fn test_wavelength_unit() {
+    let dimension = r#"{"@componentDatatype": "double", "concept": "wavelength", "unit": "UNIT"}"#;
+    // Agilent writes wavelengths in nm as `nM`, which is only accepted for dimensions.
+    for label in ["nM", "nm"] {
+        let parsed: Dimension = serde_json::from_str(&dimension.replace("UNIT", label)).unwrap();
+        assert_eq!(parsed.unit.iri(), Some("http://qudt.org/vocab/unit/NanoM"));
+        assert_eq!(parsed.unit.label(), label);
+    }
+    let measure = dimension.replace("UNIT", "nM");
+    assert!(serde_json::from_str::<Measure>(&measure).is_err());
+}
@@ -0,0 +1,242 @@
+use catplus_common::{graph::merging::ChemicalKey, units::unit::UnitOptions};
+use converter::{
+    batch::{collect_inputs, convert_batch, Status},
+    convert::{ConversionOptions, InputType, RdfFormat},
+};
+use std::fs;
+
+const SYNTH: &str = r#"
+    {
+        "batchID": "23",
+        "Actions": [
+            {
+                "actionName": "filtrateAction",
+                "startTime": "2024-07-25T12:15:23",
+                "endingTime": "2024-07-25T12:16:50",
+                "methodName": "filtrate",
+                "equipmentName": "Chemspeed SWING XL",
+                "subEquipmentName": "Filtration unit",
+                "containerID": "1",
+                "containerBarcode": "1"
+            }
+        ]
+    }
+"#;
+
+#[test]
+fn test_convert_batch() {
+    let root = std::env::temp_dir().join(format!("catplus-batch-{}", uuid::Uuid::new_v4()));
+    let input_dir = root.join("input");
+    let output_dir = root.join("output");
+    fs::create_dir_all(&input_dir).unwrap();
+    fs::write(input_dir.join("synth.json"), SYNTH).unwrap();
+    fs::write(input_dir.join("broken.json"), r#"{ "batchID": "#).unwrap();
+    fs::write(input_dir.join("unknown.json"), r#"{ "unknown": 1 }"#).unwrap();
+    fs::write(input_dir.join("notes.txt"), "not an input").unwrap();
+
+    let inputs = collect_inputs(input_dir.to_str().unwrap()).unwrap();
+    assert_eq!(inputs.len(), 3);
+
+    let summary = convert_batch(
+        &inputs,
+        &output_dir,
+        InputType::Auto,
+        &RdfFormat::Turtle,
+        &ConversionOptions::default(),
+        None,
+    )
+    .unwrap();
+
+    assert_eq!((summary.total, summary.succeeded, summary.failed), (3, 1, 2));
+    let synth = summary.files.iter().find(|f| f.input.ends_with("synth.json")).unwrap();
+    assert_eq!(synth.status, Status::Success);
+    assert!(output_dir.join("synth.ttl").is_file());
+    let unknown = summary.files.iter().find(|f| f.input.ends_with("unknown.json")).unwrap();
+    assert_eq!(unknown.status, Status::Failure);
+    assert!(unknown.error.as_ref().unwrap().contains("Could not recognise this document"));
+    assert!(!output_dir.join("broken.ttl").exists());
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_collect_inputs_glob() {
+    let root = std::env::temp_dir().join(format!("catplus-batch-{}", uuid::Uuid::new_v4()));
+    for dir in ["a", "b"] {
+        fs::create_dir_all(root.join(dir)).unwrap();
+        fs::write(root.join(dir).join("synth.json"), SYNTH).unwrap();
+    }
+
+    let pattern = format!("{}/*/*.json", root.display());
+    let inputs = collect_inputs(&pattern).unwrap();
+    assert_eq!(inputs.len(), 2);
+
+    // Both inputs would be written to the same output file.
+    let summary = convert_batch(
+        &inputs,
+        &root.join("output"),
+        InputType::Synth,
+        &RdfFormat::Turtle,
+        &ConversionOptions::default(),
+        None,
+    )
+    .unwrap();
+    assert_eq!(summary.failed, 2);
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_convert_batch_lenient() {
+    let root = std::env::temp_dir().join(format!("catplus-batch-{}", uuid::Uuid::new_v4()));
+    let input_dir = root.join("input");
+    let output_dir = root.join("output");
+    fs::create_dir_all(&input_dir).unwrap();
+    // The second action has no start time.
+    let synth = SYNTH.replace(
+        "\n        ]",
+        r#", {
+                "actionName": "filtrateAction",
+                "endingTime": "2024-07-25T12:18:02",
+                "methodName": "filtrate",
+                "equipmentName": "Chemspeed SWING XL",
+                "subEquipmentName": "Filtration unit",
+                "containerID": "2",
+                "containerBarcode": "2"
+            }
+        ]"#,
+    );
+    fs::write(input_dir.join("synth.json"), synth).unwrap();
+    let inputs = collect_inputs(input_dir.to_str().unwrap()).unwrap();
+
+    let convert = |lenient: bool| {
+        let options = ConversionOptions { lenient, ..ConversionOptions::default() };
+        convert_batch(&inputs, &output_dir, InputType::Synth, &RdfFormat::Turtle, &options, None)
+            .unwrap()
+    };
+    assert_eq!(convert(false).failed, 1);
+
+    let summary = convert(true);
+    let report = &summary.files[0];
+    assert_eq!((report.status, report.skipped), (Status::Success, 1));
+    assert_eq!(report.warnings, ["Skipped '/Actions/1': missing field `startTime`"]);
+    let turtle = fs::read_to_string(output_dir.join("synth.ttl")).unwrap();
+    assert!(turtle.contains("skippedItems 1"), "{}", turtle);
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_convert_batch_units() {
+    let root = std::env::temp_dir().join(format!("catplus-batch-{}", uuid::Uuid::new_v4()));
+    let input_dir = root.join("input");
+    let output_dir = root.join("output");
+    fs::create_dir_all(&input_dir).unwrap();
+    // mg/dL is not in the built-in table, several files are converted on the worker threads.
+    let synth = SYNTH.replace(
+        r#""containerBarcode": "1""#,
+        r#""containerBarcode": "1", "speedShaker": { "value": 12, "unit": "mg/dL" }"#,
+    );
+    for name in ["a", "b", "c", "d"] {
+        fs::write(input_dir.join(format!("{}.json", name)), &synth).unwrap();
+    }
+    let units_file = root.join("units.json");
+    fs::write(&units_file, r#"[{"label": "mg/dL", "iri": "unit:MilliGM-PER-DeciL"}]"#).unwrap();
+    let inputs = collect_inputs(input_dir.to_str().unwrap()).unwrap();
+
+    let convert = |units: UnitOptions| {
+        let options = ConversionOptions { units, ..ConversionOptions::default() };
+        convert_batch(&inputs, &output_dir, InputType::Synth, &RdfFormat::Turtle, &options, None)
+            .unwrap()
+    };
+    assert_eq!(convert(UnitOptions::default()).failed, 4);
+
+    let summary = convert(UnitOptions::load(Some(&units_file), false).unwrap());
+    assert_eq!(summary.succeeded, 4);
+    for name in ["a", "b", "c", "d"] {
+        let turtle = fs::read_to_string(output_dir.join(format!("{}.ttl", name))).unwrap();
+        assert!(turtle.contains("MilliGM-PER-DeciL"), "{}", turtle);
+    }
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_convert_batch_merged_chemicals() {
+    let synth = |batch_id: &str, mass: f64| {
+        format!(
+            r#"{{
+                "batchID": "{batch_id}",
+                "Actions": [
+                    {{
+                        "actionName": "AddAction",
+                        "equipmentName": "Chemspeed SWING XL",
+                        "subEquipmentName": "GDU-V",
+                        "startTime": "2024-07-25T12:01:29",
+                        "endingTime": "2024-07-25T12:01:35",
+                        "methodName": "addition",
+                        "dispenseType": "volume",
+                        "hasSample": {{
+                            "containerID": "18",
+                            "containerBarcode": "18",
+                            "vialID": "17",
+                            "vialType": "storage vial",
+                            "role": "reagent",
+                            "expectedDatum": {{ "value": 2, "unit": "mg" }},
+                            "hasSample": [{{
+                                "sampleID": "1",
+                                "role": "reagent",
+                                "internalBarCode": "1",
+                                "physicalState": "Liquid",
+                                "hasChemical": {{
+                                    "chemicalID": "134",
+                                    "chemicalName": "4-methoxybenzaldehyde",
+                                    "molecularMass": {{ "value": {mass}, "unit": "g/mol" }},
+                                    "smiles": "COC1=CC=C(C=C1)C=O",
+                                    "Inchi": "InChI=1S/C8H8O2/c1-10-8-4-2-7(6-9)3-5-8/h2-6H,1H3",
+                                    "molecularFormula": "C8H8O2"
+                                }}
+                            }}]
+                        }}
+                    }}
+                ]
+            }}"#
+        )
+    };
+    let root = std::env::temp_dir().join(format!("catplus-batch-{}", uuid::Uuid::new_v4()));
+    let input_dir = root.join("input");
+    fs::create_dir_all(&input_dir).unwrap();
+    fs::write(input_dir.join("a.json"), synth("23", 136.15)).unwrap();
+    fs::write(input_dir.join("b.json"), synth("24", 136.15)).unwrap();
+    fs::write(input_dir.join("c.json"), synth("25", 136.2)).unwrap();
+    let inputs = collect_inputs(input_dir.to_str().unwrap()).unwrap();
+
+    let options = ConversionOptions {
+        merge_chemicals: Some(ChemicalKey::Id),
+        ..ConversionOptions::default()
+    };
+    let summary = convert_batch(
+        &inputs,
+        &root.join("output"),
+        InputType::Synth,
+        &RdfFormat::Turtle,
+        &options,
+        None,
+    )
+    .unwrap();
+
+    assert_eq!(summary.succeeded, 3);
+    assert!(summary.files[0].warnings.is_empty(), "{:?}", summary.files[0].warnings);
+    assert!(summary.files[1].warnings.is_empty(), "{:?}", summary.files[1].warnings);
+    // The molecular mass of the chemical differs from the one of the first file.
+    let warnings = &summary.files[2].warnings;
+    assert_eq!(warnings.len(), 1, "{:?}", warnings);
+    assert!(
+        warnings[0].contains("<http://example.org/cat/resource/chemical/134>"),
+        "{:?}",
+        warnings
+    );
+    assert!(warnings[0].contains("a.json"), "{:?}", warnings);
+
+    fs::remove_dir_all(root).unwrap();
+}
@@ -0,0 +1,31 @@
+use converter::convert::{detect_input_type, InputType};
+
+#[test]
+fn test_detect_input_type() {
+    let synth = r#"{ "batchID": "23", "Actions": [] }"#;
+    let hci = r#"{ "hasCampaign": { "campaignName": "Caffeine Synthesis" } }"#;
+    let agilent = r#"{ "liquid chromatography aggregate document": {} }"#;
+
+    assert_eq!(detect_input_type(synth).unwrap(), InputType::Synth);
+    assert_eq!(detect_input_type(hci).unwrap(), InputType::HCI);
+    assert_eq!(detect_input_type(agilent).unwrap(), InputType::Agilent);
+    assert_eq!(InputType::Auto.resolve(hci).unwrap(), InputType::HCI);
+    assert_eq!(InputType::Synth.resolve(hci).unwrap(), InputType::Synth);
+}
+
+#[test]
+fn test_unrecognised_document() {
+    let error = detect_input_type(r#"{ "unknown": 1 }"#).unwrap_err().to_string();
+
+    assert!(error.contains("Could not recognise this document"));
+    for key in ["batchID", "Actions", "hasCampaign", "liquid chromatography aggregate document"] {
+        assert!(error.contains(key), "missing '{}' in: {}", key, error);
+    }
+}
+
+#[test]
+fn test_ambiguous_document() {
+    let error = detect_input_type(r#"{ "batchID": "23", "hasCampaign": {} }"#).unwrap_err();
+
+    assert!(error.to_string().contains("several input types"));
+}
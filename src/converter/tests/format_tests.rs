@@ -0,0 +1,226 @@
+use catplus_common::{
+    models::synth::SynthBatch,
+    rdf::rdf_parser::{parse_rdf_to_graph, parse_rdfxml_to_graph, parse_turtle_to_graph},
+};
+use converter::convert::{
+    json_to_rdf, json_to_rdf_with_options, rdf_to_json, ConversionOptions, GraphName, RdfFormat,
+};
+use sophia::inmem::dataset::LightDataset;
+use sophia_api::{parser::QuadParser, prelude::*, source::QuadSource, term::SimpleTerm};
+use sophia_isomorphism::isomorphic_graphs;
+use sophia_turtle::parser::{nq::NQuadsParser, trig::TriGParser};
+use std::path::Path;
+
+const SYNTH: &str = r#"
+    {
+        "batchID": "23",
+        "Actions": [
+            {
+                "actionName": "filtrateAction",
+                "startTime": "2024-07-25T12:15:23",
+                "endingTime": "2024-07-25T12:16:50",
+                "methodName": "filtrate",
+                "equipmentName": "Chemspeed SWING XL & co",
+                "subEquipmentName": "Filtration unit",
+                "containerID": "1",
+                "containerBarcode": "1"
+            }
+        ]
+    }
+"#;
+
+#[test]
+fn test_serialize_to_ntriples() {
+    let turtle = json_to_rdf::<SynthBatch>(SYNTH, &RdfFormat::Turtle, false).unwrap();
+    let ntriples = json_to_rdf::<SynthBatch>(SYNTH, &RdfFormat::NTriples, false).unwrap();
+
+    // N-Triples is a subset of Turtle.
+    let turtle_graph = parse_turtle_to_graph(&turtle).unwrap();
+    let ntriples_graph = parse_turtle_to_graph(&ntriples).unwrap();
+    assert!(isomorphic_graphs(&turtle_graph, &ntriples_graph).unwrap());
+    assert!(ntriples.lines().all(|line| line.ends_with('.')));
+}
+
+#[test]
+fn test_serialize_to_nquads_and_trig() {
+    let nquads = json_to_rdf::<SynthBatch>(SYNTH, &RdfFormat::NQuads, false).unwrap();
+    let trig = json_to_rdf::<SynthBatch>(SYNTH, &RdfFormat::Trig, false).unwrap();
+
+    // Triples are written in the default graph, without a graph name.
+    let nquads_graph = parse_turtle_to_graph(&nquads).unwrap();
+    let trig_graph = parse_turtle_to_graph(&trig).unwrap();
+    assert!(isomorphic_graphs(&nquads_graph, &trig_graph).unwrap());
+    assert!(trig.contains("PREFIX cat: <http://example.org/cat#>"));
+}
+
+#[test]
+fn test_serialize_to_rdfxml() {
+    let rdfxml = json_to_rdf::<SynthBatch>(SYNTH, &RdfFormat::RdfXml, false).unwrap();
+
+    assert!(rdfxml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<rdf:RDF"));
+    assert!(rdfxml.contains(
+        "<type xmlns=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\" rdf:resource=\"http://example.org/cat#FiltrateAction\"/>"
+    ));
+    assert!(rdfxml.contains(
+        "<AFR_0001723 xmlns=\"http://purl.allotrope.org/ontologies/result#\">Chemspeed SWING XL &amp; co</AFR_0001723>"
+    ));
+    assert!(rdfxml.contains(
+        "<AFX_0000622 xmlns=\"http://purl.allotrope.org/ontologies/result#\" rdf:datatype=\"http://www.w3.org/2001/XMLSchema#dateTime\">2024-07-25T12:15:23</AFX_0000622>"
+    ));
+    assert!(rdfxml.contains("<hasPlate xmlns=\"http://example.org/cat#\" rdf:nodeID=\"b"));
+    assert_eq!(rdfxml.matches("<rdf:Description").count(), 3);
+    assert!(rdfxml.ends_with("</rdf:RDF>"));
+
+    let turtle = json_to_rdf::<SynthBatch>(SYNTH, &RdfFormat::Turtle, false).unwrap();
+    let graph = parse_rdf_to_graph(&rdfxml, Some(&RdfFormat::RdfXml)).unwrap();
+    assert!(isomorphic_graphs(&graph, &parse_turtle_to_graph(&turtle).unwrap()).unwrap());
+}
+
+fn graph_names(dataset: &LightDataset) -> Vec<Option<String>> {
+    let mut names: Vec<Option<String>> = dataset
+        .quads()
+        .map(|quad| quad.unwrap().g().map(|g: &SimpleTerm| g.iri().unwrap().as_str().to_string()))
+        .collect();
+    names.dedup();
+    names
+}
+
+#[test]
+fn test_named_graph() {
+    let options =
+        ConversionOptions { graph_name: Some(GraphName::BatchId), ..ConversionOptions::default() };
+    let trig = json_to_rdf_with_options::<SynthBatch>(SYNTH, &RdfFormat::Trig, &options).unwrap();
+    let mut dataset = LightDataset::new();
+    TriGParser::default().parse_str(&trig).add_to_dataset(&mut dataset).unwrap();
+    assert_eq!(
+        graph_names(&dataset),
+        vec![Some("http://example.org/cat/resource/graph/batch/23".to_string())]
+    );
+
+    let options = ConversionOptions { graph_name: Some(GraphName::InputFile), ..options }
+        .for_input(Path::new("data/run 1.json"));
+    let nquads =
+        json_to_rdf_with_options::<SynthBatch>(SYNTH, &RdfFormat::NQuads, &options).unwrap();
+    let mut dataset = LightDataset::new();
+    NQuadsParser::default().parse_str(&nquads).add_to_dataset(&mut dataset).unwrap();
+    assert_eq!(
+        graph_names(&dataset),
+        vec![Some("http://example.org/cat/resource/graph/run%201".to_string())]
+    );
+}
+
+#[test]
+fn test_named_graph_requires_quads() {
+    let options = ConversionOptions {
+        graph_name: Some("http://example.org/graph".parse().unwrap()),
+        ..ConversionOptions::default()
+    };
+    let error =
+        json_to_rdf_with_options::<SynthBatch>(SYNTH, &RdfFormat::Turtle, &options).unwrap_err();
+    assert!(error.to_string().contains("TriG or N-Quads"));
+    assert!("not an iri".parse::<GraphName>().is_err());
+}
+
+#[test]
+fn test_parse_all_formats() {
+    let turtle = json_to_rdf::<SynthBatch>(SYNTH, &RdfFormat::Turtle, false).unwrap();
+    let expected_graph = parse_turtle_to_graph(&turtle).unwrap();
+
+    for format in [
+        RdfFormat::Turtle,
+        RdfFormat::Jsonld,
+        RdfFormat::NTriples,
+        RdfFormat::NQuads,
+        RdfFormat::Trig,
+        RdfFormat::RdfXml,
+    ] {
+        let serialized = json_to_rdf::<SynthBatch>(SYNTH, &format, false).unwrap();
+        let graph = parse_rdf_to_graph(&serialized, Some(&format)).unwrap();
+        assert!(isomorphic_graphs(&graph, &expected_graph).unwrap(), "{:?}", format);
+        let detected_graph = parse_rdf_to_graph(&serialized, None).unwrap();
+        assert!(isomorphic_graphs(&detected_graph, &expected_graph).unwrap(), "{:?}", format);
+    }
+
+    let options = ConversionOptions {
+        graph_name: Some("http://example.org/graph".parse().unwrap()),
+        ..ConversionOptions::default()
+    };
+    let nquads =
+        json_to_rdf_with_options::<SynthBatch>(SYNTH, &RdfFormat::NQuads, &options).unwrap();
+    let graph = parse_rdf_to_graph(&nquads, None).unwrap();
+    assert!(isomorphic_graphs(&graph, &expected_graph).unwrap());
+}
+
+#[test]
+fn test_parse_rdfxml() {
+    let rdfxml = r##"<?xml version="1.0"?>
+        <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#"
+                 xmlns:ex="http://example.org/"
+                 xml:base="http://example.org/base/">
+          <ex:Plate rdf:about="plate1" ex:label="Plate &amp; co">
+            <ex:name xml:lang="en">First plate</ex:name>
+            <ex:count rdf:datatype="http://www.w3.org/2001/XMLSchema#integer">3</ex:count>
+            <ex:well rdf:parseType="Resource">
+              <ex:position>A1</ex:position>
+            </ex:well>
+            <ex:owner rdf:nodeID="owner"/>
+            <ex:wells rdf:parseType="Collection">
+              <rdf:Description rdf:about="#w1"/>
+              <rdf:Description rdf:about="#w2"/>
+            </ex:wells>
+            <ex:tags>
+              <rdf:Bag><rdf:li>a</rdf:li><rdf:li>b</rdf:li></rdf:Bag>
+            </ex:tags>
+          </ex:Plate>
+          <rdf:Description rdf:nodeID="owner" ex:name="Lab"/>
+        </rdf:RDF>"##;
+    let expected_ttl = r#"
+        PREFIX rdf: <http://www.w3.org/1999/02/22-rdf-syntax-ns#>
+        PREFIX xsd: <http://www.w3.org/2001/XMLSchema#>
+        PREFIX ex: <http://example.org/>
+
+        <http://example.org/base/plate1> a ex:Plate;
+            ex:label "Plate & co";
+            ex:name "First plate"@en;
+            ex:count 3;
+            ex:well [ ex:position "A1" ];
+            ex:owner _:owner;
+            ex:wells (<http://example.org/base/#w1> <http://example.org/base/#w2>);
+            ex:tags [ a rdf:Bag; rdf:_1 "a"; rdf:_2 "b" ].
+        _:owner ex:name "Lab".
+    "#;
+    let graph = parse_rdfxml_to_graph(rdfxml).unwrap();
+    let expected_graph = parse_turtle_to_graph(expected_ttl).unwrap();
+    assert!(isomorphic_graphs(&graph, &expected_graph).unwrap());
+    assert!(isomorphic_graphs(&parse_rdf_to_graph(rdfxml, None).unwrap(), &expected_graph).unwrap());
+}
+
+#[test]
+fn test_detect_format_fallback() {
+    // N-Triples and N-Quads starting with what looks like an XML element, and Turtle starting
+    // with what looks like a JSON array.
+    for (input, expected) in [
+        ("<urn:a> <urn:b> <urn:c> .\n", "<urn:a> <urn:b> <urn:c> ."),
+        ("<urn:a> <urn:b> <urn:c> <urn:g> .\n", "<urn:a> <urn:b> <urn:c> ."),
+        ("[] <urn:b> <urn:c> .\n", "[] <urn:b> <urn:c> ."),
+    ] {
+        let graph = parse_rdf_to_graph(input, None).unwrap();
+        let expected_graph = parse_turtle_to_graph(expected).unwrap();
+        assert!(isomorphic_graphs(&graph, &expected_graph).unwrap(), "{}", input);
+    }
+
+    // Invalid input that looks like XML is reported as RDF/XML.
+    let error = parse_rdf_to_graph("<rdf:RDF><oops", None).unwrap_err();
+    assert!(error.to_string().contains("RDF/XML"), "{}", error);
+}
+
+#[test]
+fn test_rdf_to_json_from_any_format() {
+    let rdfxml = json_to_rdf::<SynthBatch>(SYNTH, &RdfFormat::RdfXml, false).unwrap();
+    let json = rdf_to_json::<SynthBatch>(&rdfxml, &RdfFormat::RdfXml).unwrap();
+
+    let expected: serde_json::Value = serde_json::from_str(SYNTH).unwrap();
+    let result: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(result["batchID"], expected["batchID"]);
+    assert_eq!(result["Actions"][0]["equipmentName"], expected["Actions"][0]["equipmentName"]);
+}
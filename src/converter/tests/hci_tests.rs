@@ -1,5 +1,5 @@
 use catplus_common::{models::hci::CampaignWrapper, rdf::rdf_parser::parse_turtle_to_graph};
-use converter::convert::{json_to_rdf, RdfFormat};
+use converter::convert::{json_to_graph, json_to_rdf, rdf_to_json, ConversionOptions, RdfFormat};
 use sophia_isomorphism::isomorphic_graphs;
 
 #[test]
@@ -141,6 +141,7 @@ fn test_convert_campaign() {
             cat:casNumber "74-88-4";
             cat:swissCatNumber "SwissCAT-6328";
             purl:identifier "25";
+            schema:position 2;
             allores:AFR_0001952 "CH3I";
             allores:AFR_0002292 "methyl iodide";
             allores:AFR_0002294 [ a cat:Observation;
@@ -148,6 +149,7 @@ fn test_convert_campaign() {
                 qudt:value "141.939"^^xsd:double];
             allores:AFR_0002295 "CI";
             allores:AFR_0002296 "InChI=1S/CH3I/c1-2/h1H3";
+            cat:inchiKey "INQOMBQAUSQDDS-UHFFFAOYSA-N";
             obo:PATO_0001019 [ a cat:Observation;
                 qudt:unit unit:GM-PER-MilliL;
                 qudt:value "2.28"^^xsd:double];
@@ -156,6 +158,7 @@ fn test_convert_campaign() {
             cat:casNumber "67-56-1";
             cat:swissCatNumber "SwissCAT-887";
             purl:identifier "79";
+            schema:position 3;
             allores:AFR_0001952 "CH4O";
             allores:AFR_0002292 "methanol";
             allores:AFR_0002294 [ a cat:Observation;
@@ -163,6 +166,7 @@ fn test_convert_campaign() {
                 qudt:value "32.042"^^xsd:double];
             allores:AFR_0002295 "CO";
             allores:AFR_0002296 "InChI=1S/CH4O/c1-2/h2H,1H3";
+            cat:inchiKey "OKKJLVBELUTLKV-UHFFFAOYSA-N";
             obo:PATO_0001019 [ a cat:Observation;
                 qudt:unit unit:GM-PER-MilliL;
                 qudt:value "0.79"^^xsd:double];
@@ -171,6 +175,7 @@ fn test_convert_campaign() {
             cat:casNumber "83-67-0";
             cat:swissCatNumber "SwissCAT-5429";
             purl:identifier "36";
+            schema:position 1;
             allores:AFR_0001952 "C7H8N4O2";
             allores:AFR_0002292 "theobromine";
             allores:AFR_0002294 [ a cat:Observation;
@@ -178,6 +183,7 @@ fn test_convert_campaign() {
                 qudt:value "180.16"^^xsd:double];
             allores:AFR_0002295 "CN1C=NC2=C1C(=O)NC(=O)N2C";
             allores:AFR_0002296 "InChI=1S/C7H8N4O2/c1-10-3-8-5-4(10)6(12)9-7(13)11(5)2/h3H,1-2H3,(H,9,12,13)";
+            cat:inchiKey "YAPQBXQYLJRXSA-UHFFFAOYSA-N";
             obo:PATO_0001019 [ a cat:Observation;
                 qudt:unit unit:GM-PER-MilliL;
                 qudt:value "1.522"^^xsd:double];
@@ -186,6 +192,7 @@ fn test_convert_campaign() {
             cat:casNumber "124-41-4";
             cat:swissCatNumber "SwissCAT-10942334";
             purl:identifier "19";
+            schema:position 0;
             allores:AFR_0001952 "CH3NaO";
             allores:AFR_0002292 "Sodium methoxide";
             allores:AFR_0002294 [ a cat:Observation;
@@ -193,6 +200,7 @@ fn test_convert_campaign() {
                 qudt:value "54.024"^^xsd:double];
             allores:AFR_0002295 "C[O-].[Na+]";
             allores:AFR_0002296 "InChI=1S/CH3O.Na/c1-2;/h1H3;/q-1;+1";
+            cat:inchiKey "WQDUMFSSJAZKTM-UHFFFAOYSA-N";
             obo:PATO_0001019 [ a cat:Observation;
                 qudt:unit unit:GM-PER-MilliL;
                 qudt:value "1.3"^^xsd:double];
@@ -212,3 +220,98 @@ fn test_convert_campaign() {
     let graphs_match = isomorphic_graphs(&result_graph, &expected_graph);
     assert_eq!(graphs_match.unwrap(), true);
 }
+
+#[test]
+fn test_round_trip_json() {
+    let json_data = r#"
+        {
+            "hasCampaign": {
+                "campaignName": "Caffeine Synthesis",
+                "description": "1-step N-methylation of theobromine to caffeine",
+                "objective": "High caffeine yield at the end",
+                "campaignClass": "Standard Research",
+                "type": "optimization",
+                "reference": "Substitution reaction - SN2",
+                "hasBatch": {
+                    "batchID": "23",
+                    "batchName": "20240516",
+                    "reactionType": "N-methylation"
+                },
+                "hasChemical": [
+                    {
+                        "chemicalID": "19",
+                        "chemicalName": "Sodium methoxide",
+                        "CASNumber": "124-41-4",
+                        "molecularMass": {
+                            "value": 54.024,
+                            "unit": "g/mol"
+                        },
+                        "smiles": "C[O-].[Na+]",
+                        "Inchi": "InChI=1S/CH3O.Na/c1-2;/h1H3;/q-1;+1",
+                        "molecularFormula": "CH3NaO"
+                    }
+                ]
+            }
+        }
+    "#;
+    let turtle = json_to_rdf::<CampaignWrapper>(json_data, &RdfFormat::Turtle, true).unwrap();
+    let result = rdf_to_json::<CampaignWrapper>(&turtle, &RdfFormat::Turtle).unwrap();
+
+    let expected: CampaignWrapper = serde_json::from_str(json_data).unwrap();
+    let result: CampaignWrapper = serde_json::from_str(&result).unwrap();
+    assert_eq!(serde_json::to_value(result).unwrap(), serde_json::to_value(expected).unwrap());
+}
+
+#[test]
+fn test_check_chemicals() {
+    let json_data = r#"
+        {
+            "hasCampaign": {
+                "campaignName": "Caffeine Synthesis",
+                "description": "1-step N-methylation of theobromine to caffeine",
+                "objective": "High caffeine yield at the end",
+                "campaignClass": "Standard Research",
+                "type": "optimization",
+                "reference": "Substitution reaction - SN2",
+                "hasBatch": {
+                    "batchID": "23",
+                    "batchName": "20240516",
+                    "reactionType": "N-methylation"
+                },
+                "hasChemical": [
+                    {
+                        "chemicalID": "36",
+                        "chemicalName": "theobromine",
+                        "CASNumber": "CAS_NUMBER",
+                        "molecularMass": {
+                            "value": 180.160,
+                            "unit": "g/mol"
+                        },
+                        "smiles": "CN1C=NC2=C1C(=O)NC(=O)N2C",
+                        "Inchi": "InChI=1S/C7H8N4O2/c1-10-3-8-5-4(10)6(12)9-7(13)11(5)2/h3H,1-2H3,(H,9,12,13)",
+                        "molecularFormula": "MOLECULAR_FORMULA"
+                    }
+                ]
+            }
+        }
+    "#;
+    let options = ConversionOptions { check_chemicals: true, ..ConversionOptions::default() };
+    let convert = |cas_number: &str, formula: &str| {
+        let json_data =
+            json_data.replace("CAS_NUMBER", cas_number).replace("MOLECULAR_FORMULA", formula);
+        json_to_graph::<CampaignWrapper>(&json_data, &options)
+    };
+
+    assert!(convert("83-67-0", "C7H8N4O2").unwrap().warnings.is_empty());
+
+    let warnings = convert("83-67-0", "C8H10N4O2").unwrap().warnings;
+    assert_eq!(warnings.len(), 3, "{:?}", warnings);
+    assert!(warnings[0].contains("does not match the formula C7H8N4O2 of the InChI"));
+
+    let error = format!("{:#}", convert("83-76-0", "C7H8N4O2").err().unwrap());
+    assert!(
+        error.contains("Chemical '36': Invalid check digit in CAS number '83-76-0'"),
+        "{}",
+        error
+    );
+}
@@ -1,6 +1,17 @@
-use catplus_common::{models::synth::SynthBatch, rdf::rdf_parser::parse_turtle_to_graph};
-use converter::convert::{json_to_rdf, RdfFormat};
+use catplus_common::{
+    error::ConversionError,
+    graph::{merging::ChemicalKey, minting::IriMinting},
+    models::synth::SynthBatch,
+    rdf::rdf_parser::{parse_rdf_to_graph, parse_turtle_to_graph},
+    units::unit::UnitOptions,
+};
+use converter::convert::{
+    convert_input, json_to_graph, json_to_rdf, json_to_rdf_with_options, rdf_to_json,
+    serialize_graph, ConversionOptions, InputType, Provenance, RdfFormat,
+};
+use sophia::api::{ns::rdf, prelude::*};
 use sophia_isomorphism::isomorphic_graphs;
+use std::path::Path;
 
 #[test]
 fn test_convert_filtrate_action() {
@@ -44,6 +55,7 @@ fn test_convert_filtrate_action() {
         PREFIX obo: <http://purl.obolibrary.org/obo/>
 
         [] a cat:FiltrateAction;
+        schema:position 0;
         cat:hasBatch [ a cat:Batch;
             purl:identifier "23"];
         cat:hasPlate [ a cat:Plate;
@@ -112,6 +124,7 @@ fn test_convert_pressure_action() {
         PREFIX obo: <http://purl.obolibrary.org/obo/>
 
         [] a cat:SetPressureAction;
+        schema:position 0;
         cat:hasBatch [ a cat:Batch;
             purl:identifier "23"];
         cat:hasPlate [ a cat:Plate;
@@ -202,6 +215,7 @@ fn test_convert_set_temperature_action() {
         PREFIX obo: <http://purl.obolibrary.org/obo/>
 
         [] a cat:SetTemperatureAction;
+        schema:position 0;
         cat:hasBatch [ a cat:Batch;
             purl:identifier "23"];
         cat:hasPlate [ a cat:Plate;
@@ -361,6 +375,7 @@ fn test_convert_add_action() {
         PREFIX obo: <http://purl.obolibrary.org/obo/>
 
         [] a cat:AddAction;
+        schema:position 0;
         cat:dispenseType "volume";
         cat:hasBatch [ a cat:Batch;
             purl:identifier "23"];
@@ -372,6 +387,7 @@ fn test_convert_add_action() {
                 cat:containerBarcode "18";
                 cat:containerID "18"];
             cat:hasSample [ a cat:Sample;
+                schema:position 0;
                 cat:expectedDatum [ a cat:Observation;
                     qudt:unit unit:MilliGM;
                     qudt:value "5"^^xsd:double];
@@ -402,6 +418,7 @@ fn test_convert_add_action() {
             cat:vialShape "storage vial";
             allores:AFR_0002464 "17"];
         cat:hasWell [ a cat:Well;
+            schema:position 1;
             cat:hasPlate [ a cat:Plate;
                 cat:containerID "1"];
             allores:AFR_0002240 "B1";
@@ -412,6 +429,7 @@ fn test_convert_add_action() {
                 qudt:unit unit:MilliGM;
                 qudt:value "0.034"^^xsd:double]],
             [ a cat:Well;
+            schema:position 0;
             cat:hasPlate [ a cat:Plate;
                 cat:containerID "1"];
             allores:AFR_0002240 "A1";
@@ -507,6 +525,7 @@ fn test_convert_shake_action() {
         PREFIX obo: <http://purl.obolibrary.org/obo/>
 
         [] a cat:ShakeAction;
+        schema:position 0;
         cat:hasBatch [ a cat:Batch;
             purl:identifier "23"];
         cat:hasPlate [ a cat:Plate;
@@ -593,6 +612,7 @@ fn test_convert_set_vacuum_action() {
         PREFIX obo: <http://purl.obolibrary.org/obo/>
 
         [] a cat:SetVacuumAction;
+        schema:position 0;
         cat:hasBatch [ a cat:Batch;
             purl:identifier "23"];
         cat:hasPlate [ a cat:Plate;
@@ -616,3 +636,511 @@ fn test_convert_set_vacuum_action() {
     let graphs_match = isomorphic_graphs(&result_graph, &expected_graph);
     assert_eq!(graphs_match.unwrap(), true);
 }
+
+#[test]
+fn test_deterministic_iris() {
+    let output_format = RdfFormat::Turtle;
+    let json_data = r#"
+        {
+            "batchID": "23",
+            "Actions": [
+                {
+                    "actionName": "setTemperatureAction",
+                    "speedShaker": {
+                        "value": 152,
+                        "unit": "rpm"
+                    },
+                    "temperatureTumbleStirrer": {
+                        "value": 25,
+                        "unit": "°C"
+                    },
+                    "startTime": "2024-07-25T12:03:31",
+                    "endingTime": "2024-07-25T12:04:05",
+                    "methodName": "set_temperature",
+                    "equipmentName": "Chemspeed SWING XL",
+                    "subEquipmentName": "heater",
+                    "containerID": "18",
+                    "containerBarcode": "18"
+                }
+            ]
+        }
+    "#;
+    let options = ConversionOptions {
+        minting: Some(IriMinting::Deterministic),
+        ..ConversionOptions::default()
+    };
+    let first =
+        json_to_rdf_with_options::<SynthBatch>(json_data, &output_format, &options).unwrap();
+    let second =
+        json_to_rdf_with_options::<SynthBatch>(json_data, &output_format, &options).unwrap();
+
+    let first_graph = parse_turtle_to_graph(&first).unwrap();
+    let second_graph = parse_turtle_to_graph(&second).unwrap();
+    assert!(isomorphic_graphs(&first_graph, &second_graph).unwrap());
+
+    assert!(first.contains("http://example.org/cat/resource/batch/23"));
+    assert!(first.contains("http://example.org/cat/resource/plate/18"));
+    assert!(!first.contains("_:"));
+}
+
+#[test]
+fn test_deterministic_iris_of_identical_records() {
+    let action = r#"
+        {
+            "actionName": "shakeAction",
+            "speedTumbleStirrer": {
+                "value": 600,
+                "unit": "rpm",
+                "errorMargin": {
+                    "value": 1,
+                    "unit": "rpm"
+                }
+            },
+            "startTime": "2024-07-25T12:03:31",
+            "endingTime": "2024-07-25T12:15:20",
+            "methodName": "shake",
+            "equipmentName": "Chemspeed SWING XL",
+            "subEquipmentName": "Tumble stirrer",
+            "containerID": "1",
+            "containerBarcode": "1"
+        }
+    "#;
+    let json_data =
+        format!(r#"{{ "batchID": "23", "Actions": [{}, {}, {}] }}"#, action, action, action);
+    let deterministic = ConversionOptions {
+        minting: Some(IriMinting::Deterministic),
+        ..ConversionOptions::default()
+    };
+
+    // Identical actions and their observations are kept apart, while the batch and plate
+    // they share are merged through their natural keys.
+    let first = json_to_graph::<SynthBatch>(&json_data, &deterministic).unwrap().graph;
+    let count = |class: &str| {
+        let class = Iri::new_unchecked(format!("http://example.org/cat#{}", class));
+        first.triples_matching(Any, [rdf::type_], [class]).count()
+    };
+    assert_eq!(count("ShakeAction"), 3);
+    assert_eq!(count("Observation"), 3);
+    assert_eq!(count("errorMargin"), 3);
+    assert_eq!(count("Batch"), 1);
+    assert_eq!(count("Plate"), 1);
+
+    let second = json_to_graph::<SynthBatch>(&json_data, &deterministic).unwrap().graph;
+    assert!(isomorphic_graphs(&first, &second).unwrap());
+}
+
+#[test]
+fn test_round_trip_json() {
+    let json_data = r#"
+        {
+            "batchID": "23",
+            "Actions": [
+                {
+                    "actionName": "AddAction",
+                    "equipmentName": "Chemspeed SWING XL",
+                    "subEquipmentName": "GDU-V",
+                    "hasWell": [
+                        {
+                            "position": "A1",
+                            "containerID": "1",
+                            "quantity": {
+                                "value": 0.024,
+                                "unit": "mg",
+                                "errorMargin": {
+                                    "value": 0.001,
+                                    "unit": "mg"
+                                }
+                            }
+                        },
+                        {
+                            "position": "B1",
+                            "containerID": "1",
+                            "quantity": {
+                                "value": 0.034,
+                                "unit": "mg"
+                            }
+                        }
+                    ],
+                    "startTime": "2024-07-25T12:01:29",
+                    "endingTime": "2024-07-25T12:01:35",
+                    "methodName": "addition",
+                    "dispenseState": "Liquid",
+                    "dispenseType": "volume",
+                    "containerID": "1",
+                    "containerBarcode": "1"
+                },
+                {
+                    "actionName": "setPressureAction",
+                    "pressureMeasurement": {
+                        "value": 5,
+                        "unit": "bar"
+                    },
+                    "startTime": "2024-07-25T12:03:31",
+                    "endingTime": "2024-07-25T12:04:05",
+                    "methodName": "set_pressure",
+                    "equipmentName": "Chemspeed SWING XL",
+                    "subEquipmentName": "MTP_Pressure",
+                    "containerID": "18",
+                    "containerBarcode": "18"
+                }
+            ]
+        }
+    "#;
+    let turtle = json_to_rdf::<SynthBatch>(json_data, &RdfFormat::Turtle, false).unwrap();
+    let result = rdf_to_json::<SynthBatch>(&turtle, &RdfFormat::Turtle).unwrap();
+
+    // Compare the normalized models (e.g. numbers are floats once parsed).
+    let expected: SynthBatch = serde_json::from_str(json_data).unwrap();
+    let result: SynthBatch = serde_json::from_str(&result).unwrap();
+    assert_eq!(serde_json::to_value(result).unwrap(), serde_json::to_value(expected).unwrap());
+}
+
+#[test]
+fn test_provenance() {
+    let json_data = r#"{ "batchID": "23", "Actions": [] }"#;
+    let options = ConversionOptions {
+        provenance: Some(Provenance::default()),
+        ..ConversionOptions::default()
+    }
+    .for_input(Path::new("examples/1-Synth.json"));
+    let result = convert_input(InputType::Auto, json_data, &RdfFormat::Turtle, &options).unwrap();
+
+    let expected_ttl = r#"
+        PREFIX rdf: <http://www.w3.org/1999/02/22-rdf-syntax-ns#>
+        PREFIX xsd: <http://www.w3.org/2001/XMLSchema#>
+        PREFIX cat: <http://example.org/cat#>
+        PREFIX schema: <https://schema.org/>
+        PREFIX prov: <http://www.w3.org/ns/prov#>
+        PREFIX purl: <http://purl.allotrope.org/ontologies/>
+
+        [] a cat:Batch;
+            purl:identifier "23";
+            prov:wasGeneratedBy [ a prov:Activity;
+                prov:startedAtTime "TIME"^^xsd:dateTime;
+                prov:used [ a prov:Entity;
+                    schema:name "examples/1-Synth.json";
+                    schema:sha256 "0ed13b3648ea44e7e49985371442c868916ffb2575bb6cae3850fac2841ed9af";
+                    cat:inputType "Synth"];
+                prov:wasAssociatedWith [ a prov:SoftwareAgent;
+                    schema:name "converter";
+                    schema:softwareVersion "VERSION"]].
+    "#;
+    let time_start = result.find("prov:startedAtTime \"").unwrap() + 20;
+    let time = &result[time_start..time_start + 24];
+    let expected_ttl =
+        expected_ttl.replace("TIME", time).replace("VERSION", env!("CARGO_PKG_VERSION"));
+
+    let expected_graph = parse_turtle_to_graph(&expected_ttl).unwrap();
+    let result_graph = parse_turtle_to_graph(&result).unwrap();
+    assert!(isomorphic_graphs(&result_graph, &expected_graph).unwrap(), "{}", result);
+}
+
+#[test]
+fn test_provenance_with_deterministic_iris() {
+    let json_data = r#"
+        {
+            "batchID": "23",
+            "Actions": [
+                {
+                    "actionName": "filtrateAction",
+                    "startTime": "2024-07-25T12:15:23",
+                    "endingTime": "2024-07-25T12:16:50",
+                    "methodName": "filtrate",
+                    "equipmentName": "Chemspeed SWING XL",
+                    "subEquipmentName": "Filtration unit",
+                    "containerID": "1",
+                    "containerBarcode": "1"
+                }
+            ]
+        }
+    "#;
+    let deterministic = ConversionOptions {
+        minting: Some(IriMinting::Deterministic),
+        ..ConversionOptions::default()
+    };
+    let with_provenance =
+        ConversionOptions { provenance: Some(Provenance::default()), ..deterministic.clone() };
+    let triples = |graph: &sophia::inmem::graph::LightGraph| {
+        graph.triples().map(|triple| format!("{:?}", triple.unwrap())).collect::<Vec<_>>()
+    };
+
+    // Each conversion is a new activity, but the IRIs of the converted data stay the same.
+    let data = json_to_graph::<SynthBatch>(json_data, &deterministic).unwrap().graph;
+    for _ in 0..2 {
+        let graph = json_to_graph::<SynthBatch>(json_data, &with_provenance).unwrap().graph;
+        assert_eq!(graph.triples().count(), data.triples().count() + 10);
+        let graph_triples = triples(&graph);
+        for triple in triples(&data) {
+            assert!(graph_triples.contains(&triple), "{}", triple);
+        }
+        std::thread::sleep(std::time::Duration::from_millis(2));
+    }
+}
+
+#[test]
+fn test_normalize_units() {
+    let json_data = r#"
+        {
+            "batchID": "23",
+            "Actions": [
+                {
+                    "actionName": "setTemperatureAction",
+                    "temperatureShaker": {
+                        "value": 25,
+                        "unit": "°C",
+                        "errorMargin": {
+                            "value": 1,
+                            "unit": "°C"
+                        }
+                    },
+                    "startTime": "2024-07-25T12:00:00",
+                    "endingTime": "2024-07-25T12:00:02",
+                    "methodName": "set_temperature",
+                    "equipmentName": "Chemspeed SWING XL",
+                    "subEquipmentName": "heater",
+                    "containerID": "1",
+                    "containerBarcode": "1"
+                }
+            ]
+        }
+    "#;
+    let options = ConversionOptions { normalize_units: true, ..ConversionOptions::default() };
+    let result =
+        json_to_rdf_with_options::<SynthBatch>(json_data, &RdfFormat::Turtle, &options).unwrap();
+    let expected_ttl = r#"
+        PREFIX xsd: <http://www.w3.org/2001/XMLSchema#>
+        PREFIX cat: <http://example.org/cat#>
+        PREFIX schema: <https://schema.org/>
+        PREFIX unit: <http://qudt.org/vocab/unit/>
+        PREFIX allores: <http://purl.allotrope.org/ontologies/result#>
+        PREFIX qudt: <http://qudt.org/schema/qudt/>
+        PREFIX purl: <http://purl.allotrope.org/ontologies/>
+
+        [] a cat:SetTemperatureAction;
+        schema:position 0;
+        cat:hasBatch [ a cat:Batch;
+            purl:identifier "23"];
+        cat:hasPlate [ a cat:Plate;
+            cat:containerBarcode "1";
+            cat:containerID "1"];
+        cat:subEquipmentName "heater";
+        cat:temperatureShakerShape [ a cat:Observation;
+            cat:errorMargin [ a cat:errorMargin;
+                qudt:unit unit:DEG-C;
+                qudt:value "1"^^xsd:double];
+            qudt:unit unit:DEG-C;
+            qudt:value "25"^^xsd:double;
+            cat:normalizedValue [ a qudt:QuantityValue;
+                qudt:unit unit:K;
+                qudt:value "KELVIN"^^xsd:double]];
+        allores:AFR_0001606 "set_temperature";
+        allores:AFR_0001723 "Chemspeed SWING XL";
+        allores:AFR_0002423 "2024-07-25T12:00:02"^^xsd:dateTime;
+        allores:AFX_0000622 "2024-07-25T12:00:00"^^xsd:dateTime.
+    "#
+    .replace("KELVIN", &(25.0 + 273.15_f64).to_string());
+    let expected_graph = parse_turtle_to_graph(&expected_ttl).unwrap();
+    let result_graph = parse_turtle_to_graph(&result).unwrap();
+    assert!(isomorphic_graphs(&result_graph, &expected_graph).unwrap(), "{}", result);
+
+    // The normalized values are not part of the JSON document.
+    let json = rdf_to_json::<SynthBatch>(&result, &RdfFormat::Turtle).unwrap();
+    assert!(json.contains("\"unit\": \"°C\""));
+}
+
+#[test]
+fn test_convert_ucum_units() {
+    let json_data = r#"
+        {
+            "batchID": "23",
+            "Actions": [
+                {
+                    "actionName": "setPressureAction",
+                    "pressureMeasurement": {
+                        "value": 250,
+                        "unit": "UNIT"
+                    },
+                    "startTime": "2024-07-25T12:00:00",
+                    "endingTime": "2024-07-25T12:00:02",
+                    "methodName": "set_pressure",
+                    "equipmentName": "Chemspeed SWING XL",
+                    "subEquipmentName": "pressure unit",
+                    "containerID": "1",
+                    "containerBarcode": "1"
+                }
+            ]
+        }
+    "#;
+    let result =
+        json_to_rdf::<SynthBatch>(&json_data.replace("UNIT", "mbar"), &RdfFormat::Turtle, false)
+            .unwrap();
+    assert!(result.contains("unit:MilliBAR"), "{}", result);
+
+    let error =
+        json_to_rdf::<SynthBatch>(&json_data.replace("UNIT", "furlong"), &RdfFormat::Turtle, false)
+            .unwrap_err();
+    assert!(format!("{:#}", error).contains("Invalid unit 'furlong'"), "{:#}", error);
+
+    let options = ConversionOptions::default();
+    match json_to_graph::<SynthBatch>(&json_data.replace("UNIT", "furlong"), &options) {
+        Err(ConversionError::UnknownUnit { unit, location, .. }) => {
+            assert_eq!(unit, "furlong");
+            assert_eq!(location.pointer, "/Actions/0/pressureMeasurement/unit");
+        }
+        result => panic!("Expected an unknown unit, got {:?}", result.map(|_| ())),
+    }
+}
+
+#[test]
+fn test_merge_chemicals() {
+    let sample = |sample_id: &str, chemical_id: &str, name: &str| {
+        format!(
+            r#"{{
+                "sampleID": "{sample_id}",
+                "role": "reagent",
+                "internalBarCode": "{sample_id}",
+                "physicalState": "Liquid",
+                "hasChemical": {{
+                    "chemicalID": "{chemical_id}",
+                    "chemicalName": "{name}",
+                    "molecularMass": {{ "value": 136.15, "unit": "g/mol" }},
+                    "smiles": "COC1=CC=C(C=C1)C=O",
+                    "Inchi": "InChI=1S/C8H8O2/c1-10-8-4-2-7(6-9)3-5-8/h2-6H,1H3",
+                    "molecularFormula": "C8H8O2"
+                }}
+            }}"#
+        )
+    };
+    let batch = |batch_id: &str, samples: &[String]| {
+        format!(
+            r#"{{
+                "batchID": "{batch_id}",
+                "Actions": [
+                    {{
+                        "actionName": "AddAction",
+                        "equipmentName": "Chemspeed SWING XL",
+                        "subEquipmentName": "GDU-V",
+                        "startTime": "2024-07-25T12:01:29",
+                        "endingTime": "2024-07-25T12:01:35",
+                        "methodName": "addition",
+                        "dispenseType": "volume",
+                        "hasSample": {{
+                            "containerID": "18",
+                            "containerBarcode": "18",
+                            "vialID": "17",
+                            "vialType": "storage vial",
+                            "role": "reagent",
+                            "expectedDatum": {{ "value": 2, "unit": "mg" }},
+                            "hasSample": [{}]
+                        }}
+                    }}
+                ]
+            }}"#,
+            samples.join(",")
+        )
+    };
+    let chemicals = |ntriples: &str| {
+        let mut chemicals: Vec<String> = ntriples
+            .lines()
+            .filter(|line| line.contains("#type> <http://purl.obolibrary.org/obo/CHEBI_25367>"))
+            .map(|line| line.split(' ').next().unwrap().to_string())
+            .collect();
+        chemicals.sort();
+        chemicals
+    };
+
+    let first = batch(
+        "23",
+        &[sample("1", "134", "4-methoxybenzaldehyde"), sample("2", "134", "4-methoxybenzaldehyde")],
+    );
+    let options = ConversionOptions {
+        merge_chemicals: Some(ChemicalKey::Id),
+        ..ConversionOptions::default()
+    };
+    let graph = json_to_graph::<SynthBatch>(&first, &options).unwrap();
+    let ntriples = serialize_graph(&graph, &RdfFormat::NTriples).unwrap();
+    assert_eq!(chemicals(&ntriples), ["<http://example.org/cat/resource/chemical/134>"]);
+    // The molecular mass of the dropped chemical is dropped with it.
+    assert_eq!(ntriples.matches("\"136.15\"").count(), 1, "{}", ntriples);
+    assert!(graph.warnings.is_empty(), "{:?}", graph.warnings);
+    let without_merging = json_to_rdf::<SynthBatch>(&first, &RdfFormat::NTriples, false).unwrap();
+    assert_eq!(chemicals(&without_merging).len(), 2);
+
+    // The same chemical converted from another file gets the same IRI.
+    let second = batch("24", &[sample("3", "134", "4-methoxybenzaldehyde")]);
+    let second_ntriples =
+        json_to_rdf_with_options::<SynthBatch>(&second, &RdfFormat::NTriples, &options).unwrap();
+    assert_eq!(chemicals(&second_ntriples), ["<http://example.org/cat/resource/chemical/134>"]);
+
+    // Loaded together, both files describe the chemical once, with the same molecular mass.
+    let graph = parse_rdf_to_graph(&format!("{}{}", ntriples, second_ntriples), None).unwrap();
+    let chemical = IriRef::new_unchecked("http://example.org/cat/resource/chemical/134");
+    let description: Vec<_> = graph.triples_matching([chemical], Any, Any).collect();
+    assert_eq!(description.len(), 8, "{:?}", description);
+    let masses = graph
+        .triples()
+        .filter(|triple| triple.as_ref().unwrap()[2].lexical_form().as_deref() == Some("136.15"));
+    assert_eq!(masses.count(), 1);
+
+    let third =
+        batch("25", &[sample("4", "134", "anisaldehyde"), sample("5", "135", "p-anisaldehyde")]);
+    let options = ConversionOptions {
+        merge_chemicals: Some(ChemicalKey::Inchi),
+        ..ConversionOptions::default()
+    };
+    let graph = json_to_graph::<SynthBatch>(&third, &options).unwrap();
+    let ntriples = serialize_graph(&graph, &RdfFormat::NTriples).unwrap();
+    assert_eq!(
+        chemicals(&ntriples),
+        ["<http://example.org/cat/resource/chemical/inchikey/ZRSNZINYAWTAHE-UHFFFAOYSA-N>"]
+    );
+    assert_eq!(
+        graph.warnings,
+        ["Chemicals with InChIKey 'ZRSNZINYAWTAHE-UHFFFAOYSA-N' have different descriptions, \
+          only the first one is kept"]
+    );
+}
+
+#[test]
+fn test_unit_options() {
+    let json_data = r#"
+        {
+            "batchID": "23",
+            "Actions": [
+                {
+                    "actionName": "setPressureAction",
+                    "pressureMeasurement": {
+                        "value": 250,
+                        "unit": "furlong"
+                    },
+                    "startTime": "2024-07-25T12:00:00",
+                    "endingTime": "2024-07-25T12:00:02",
+                    "methodName": "set_pressure",
+                    "equipmentName": "Chemspeed SWING XL",
+                    "subEquipmentName": "pressure unit",
+                    "containerID": "1",
+                    "containerBarcode": "1"
+                }
+            ]
+        }
+    "#;
+    let units_file =
+        std::env::temp_dir().join(format!("catplus-units-{}.json", uuid::Uuid::new_v4()));
+    std::fs::write(&units_file, r#"[{"label": "furlong", "iri": "http://example.org/Furlong"}]"#)
+        .unwrap();
+    let convert = |units: UnitOptions| {
+        let options = ConversionOptions { units, ..ConversionOptions::default() };
+        convert_input(InputType::Synth, json_data, &RdfFormat::Turtle, &options)
+    };
+
+    let result = convert(UnitOptions::load(Some(&units_file), false).unwrap()).unwrap();
+    assert!(result.contains("<http://example.org/Furlong>"), "{}", result);
+    std::fs::remove_file(&units_file).unwrap();
+
+    let result = convert(UnitOptions::load(None, true).unwrap()).unwrap();
+    assert!(result.contains("qudt:unit \"furlong\""), "{}", result);
+
+    // The options of a conversion do not leak into the next ones.
+    let error = convert(UnitOptions::default()).unwrap_err();
+    assert!(format!("{:#}", error).contains("Invalid unit 'furlong'"), "{:#}", error);
+}
@@ -0,0 +1,170 @@
+use catplus_common::{models::synth::SynthBatch, rdf::rdf_parser::parse_turtle_to_graph};
+use converter::{
+    batch::{convert_batch, Status},
+    convert::{json_to_graph, serialize_graph, ConversionOptions, InputType, RdfFormat},
+    validate::{report_path, GraphValidator},
+};
+use std::fs;
+use validation::{
+    core::{Severity, ShaclEngine},
+    engines::native::NativeShaclEngine,
+    report::{render_report, ReportFormat},
+    shapes::{ShapesCache, ShapesSource, ONTOLOGY_REPOSITORY, SHAPES_FILE},
+};
+
+const SHAPES: &str = r#"
+    PREFIX sh: <http://www.w3.org/ns/shacl#>
+    PREFIX cat: <http://example.org/cat#>
+    PREFIX purl: <http://purl.allotrope.org/ontologies/>
+
+    cat:BatchShape a sh:NodeShape;
+        sh:targetClass cat:Batch;
+        sh:property [ sh:path purl:identifier; sh:pattern "^[0-9]+$" ].
+"#;
+
+fn validator() -> GraphValidator {
+    let shapes = parse_turtle_to_graph(SHAPES).unwrap();
+    GraphValidator::new(Box::new(NativeShaclEngine::new()), Some(shapes))
+}
+
+fn temp_dir() -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("catplus-validate-{}", uuid::Uuid::new_v4()))
+}
+
+#[test]
+fn test_write_conforming_graph() {
+    let root = temp_dir();
+    fs::create_dir_all(&root).unwrap();
+    let graph_builder =
+        json_to_graph::<SynthBatch>(r#"{ "batchID": "23", "Actions": [] }"#, &Default::default())
+            .unwrap();
+    let serialized_graph = serialize_graph(&graph_builder, &RdfFormat::Turtle).unwrap();
+
+    let validator = validator();
+    let report = validator.validate(&graph_builder.graph).unwrap();
+    assert!(report.conforms);
+
+    let output = root.join("batch.ttl");
+    validator.write_output(&output, &serialized_graph, &report).unwrap();
+    assert_eq!(fs::read_to_string(&output).unwrap(), serialized_graph);
+    assert_eq!(report_path(&output), root.join("batch.ttl.validation.ttl"));
+    assert!(report_path(&output).is_file());
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_quarantine_non_conforming_graph() {
+    let root = temp_dir();
+    fs::create_dir_all(&root).unwrap();
+    let graph_builder =
+        json_to_graph::<SynthBatch>(r#"{ "batchID": "A23", "Actions": [] }"#, &Default::default())
+            .unwrap();
+    let serialized_graph = serialize_graph(&graph_builder, &RdfFormat::Turtle).unwrap();
+    let output = root.join("batch.ttl");
+
+    // Without a quarantine, only the report is written.
+    let validator = validator();
+    let report = validator.validate(&graph_builder.graph).unwrap();
+    assert!(!report.conforms);
+    let error = validator.write_output(&output, &serialized_graph, &report).unwrap_err();
+    assert!(error.to_string().contains("does not conform"));
+    assert!(!output.exists());
+    assert!(report_path(&output).is_file());
+
+    let quarantine = root.join("quarantine");
+    let validator = validator.with_quarantine(quarantine.clone());
+    assert!(validator.write_output(&output, &serialized_graph, &report).is_err());
+    assert!(!output.exists());
+    assert!(quarantine.join("batch.ttl").is_file());
+    assert!(quarantine.join("batch.ttl.validation.ttl").is_file());
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn test_validate_batch() {
+    let root = temp_dir();
+    let input_dir = root.join("input");
+    fs::create_dir_all(&input_dir).unwrap();
+    fs::write(input_dir.join("valid.json"), r#"{ "batchID": "23", "Actions": [] }"#).unwrap();
+    fs::write(input_dir.join("invalid.json"), r#"{ "batchID": "A23", "Actions": [] }"#).unwrap();
+    let inputs = vec![input_dir.join("invalid.json"), input_dir.join("valid.json")];
+
+    let summary = convert_batch(
+        &inputs,
+        &root.join("output"),
+        InputType::Synth,
+        &RdfFormat::Turtle,
+        &ConversionOptions::default(),
+        Some(&validator()),
+    )
+    .unwrap();
+
+    assert_eq!(summary.files[0].status, Status::Failure);
+    assert!(summary.files[0].error.as_ref().unwrap().contains("does not conform"));
+    assert_eq!(summary.files[1].status, Status::Success);
+    assert!(root.join("output/valid.ttl").is_file());
+    assert!(!root.join("output/invalid.ttl").exists());
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+/// Validates converter output against the published shapes of the catplus-ontology, read from
+/// `$CATPLUS_SHAPES` (a file, URL or release tag) or the latest release.
+#[test]
+#[ignore = "downloads the catplus-ontology shapes, run with --ignored"]
+fn test_catplus_shapes() {
+    let source = std::env::var("CATPLUS_SHAPES").unwrap_or_else(|_| {
+        format!("{}/releases/latest/download/{}", ONTOLOGY_REPOSITORY, SHAPES_FILE)
+    });
+    let cache = ShapesCache::new(ShapesCache::default_dir());
+    let shapes = cache.resolve(&source.parse().unwrap()).unwrap();
+    let json_data = r#"
+        {
+            "batchID": "23",
+            "Actions": [
+                {
+                    "actionName": "filtrateAction",
+                    "startTime": "2024-07-25T12:15:23",
+                    "endingTime": "2024-07-25T12:16:50",
+                    "methodName": "filtrate",
+                    "equipmentName": "Chemspeed SWING XL",
+                    "subEquipmentName": "Filtration unit",
+                    "containerID": "1",
+                    "containerBarcode": "1"
+                }
+            ]
+        }
+    "#;
+    let graph_builder = json_to_graph::<SynthBatch>(json_data, &Default::default()).unwrap();
+
+    let report =
+        NativeShaclEngine::new().validate(&graph_builder.graph, Some(&shapes.graph)).unwrap();
+    assert!(
+        report.conforms,
+        "{}",
+        render_report(&report, ReportFormat::Human, Severity::Info, false).unwrap()
+    );
+}
+
+#[test]
+fn test_validator_from_sources() {
+    assert!(GraphValidator::from_sources(None, None).unwrap().is_none());
+
+    let root = temp_dir();
+    fs::create_dir_all(&root).unwrap();
+    let shapes_file = root.join("shapes.ttl");
+    fs::write(&shapes_file, SHAPES).unwrap();
+    let source = ShapesSource::File(shapes_file.clone());
+    let validator = GraphValidator::from_sources(Some(&source), None).unwrap().unwrap();
+
+    let graph_builder =
+        json_to_graph::<SynthBatch>(r#"{ "batchID": "ID", "Actions": [] }"#, &Default::default())
+            .unwrap();
+    let report = validator.validate(&graph_builder.graph).unwrap();
+    assert!(!report.conforms);
+    assert_eq!(report.shapes.unwrap().source, shapes_file.display().to_string());
+
+    fs::remove_dir_all(root).unwrap();
+}
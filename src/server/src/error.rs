@@ -0,0 +1,92 @@
+//! Errors of the HTTP API, returned as JSON bodies.
+use catplus_common::error::{ConversionError, Location};
+use hyper::{header, Response, StatusCode};
+use serde::Serialize;
+
+/// An error of a request, returned as a JSON body such as
+///
+/// ```json
+/// {
+///   "status": 422,
+///   "error": "unknown_unit",
+///   "message": "Invalid unit 'furlong': unknown unit symbol 'furlong' at '/Actions/0/pressureMeasurement/unit' (line 6, column 70)",
+///   "location": { "pointer": "/Actions/0/pressureMeasurement/unit", "line": 6, "column": 70 }
+/// }
+/// ```
+#[derive(Debug, Serialize)]
+pub struct ApiError {
+    /// HTTP status code of the response.
+    #[serde(serialize_with = "serialize_status")]
+    pub status: StatusCode,
+    /// Kind of the error, e.g. `syntax`, `schema`, `unknown_unit` or `not_acceptable`.
+    pub error: &'static str,
+    pub message: String,
+    /// Where the error is in the input, if it can be located.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<Location>,
+}
+
+fn serialize_status<S: serde::Serializer>(status: &StatusCode, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_u16(status.as_u16())
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, error: &'static str, message: impl Into<String>) -> Self {
+        ApiError { status, error, message: message.into(), location: None }
+    }
+
+    /// An unexpected failure of the server.
+    pub fn internal(error: anyhow::Error) -> Self {
+        ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "internal", format!("{:#}", error))
+    }
+
+    /// A failure to convert the input: invalid JSON is a bad request, valid JSON that cannot
+    /// be converted is unprocessable.
+    pub fn conversion(error: anyhow::Error) -> Self {
+        let Some(conversion_error) =
+            error.chain().find_map(|error| error.downcast_ref::<ConversionError>())
+        else {
+            // The input type is detected before the conversion, from the parsed JSON.
+            return match error.chain().find_map(|e| e.downcast_ref::<serde_json::Error>()) {
+                Some(json_error) => ApiError {
+                    location: Some(Location {
+                        pointer: String::new(),
+                        line: json_error.line(),
+                        column: json_error.column(),
+                    }),
+                    ..ApiError::new(StatusCode::BAD_REQUEST, "syntax", json_error.to_string())
+                },
+                None => ApiError::new(
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    "conversion",
+                    format!("{:#}", error),
+                ),
+            };
+        };
+
+        let (status, kind) = match conversion_error {
+            ConversionError::Syntax { .. } => (StatusCode::BAD_REQUEST, "syntax"),
+            ConversionError::Schema { .. } => (StatusCode::UNPROCESSABLE_ENTITY, "schema"),
+            ConversionError::UnknownUnit { .. } => {
+                (StatusCode::UNPROCESSABLE_ENTITY, "unknown_unit")
+            }
+            ConversionError::UnknownAction { .. } => {
+                (StatusCode::UNPROCESSABLE_ENTITY, "unknown_action")
+            }
+            ConversionError::Graph(_) => (StatusCode::UNPROCESSABLE_ENTITY, "graph"),
+        };
+        ApiError {
+            location: conversion_error.location(),
+            ..ApiError::new(status, kind, conversion_error.to_string())
+        }
+    }
+
+    pub fn into_response(self) -> Response<String> {
+        let body = serde_json::to_string_pretty(&self).unwrap_or_else(|_| self.message.clone());
+        Response::builder()
+            .status(self.status)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(body + "\n")
+            .expect("a valid response")
+    }
+}
@@ -0,0 +1,4 @@
+pub mod error;
+pub mod negotiation;
+pub mod serve;
+pub mod service;
@@ -0,0 +1,87 @@
+use anyhow::{Context, Result};
+use catplus_common::{graph::minting::IriMinting, units::unit::UnitOptions};
+use clap::Parser;
+use converter::{convert::ConversionOptions, validate::GraphValidator};
+use server::{serve::serve, service::Service};
+use std::{net::SocketAddr, path::Path, sync::Arc};
+use tokio::net::TcpListener;
+use validation::shapes::ShapesSource;
+
+/// Serves the conversion of CAT+ JSON input to RDF, and the validation of RDF, over HTTP.
+///
+/// `POST /convert/{synth|hci|agilent|auto}` converts the JSON body, to the RDF format
+/// negotiated with the `Accept` header: Turtle (text/turtle, the default), JSON-LD
+/// (application/ld+json), N-Triples (application/n-triples), N-Quads, TriG or RDF/XML.
+/// `POST /validate` validates the RDF body with the SHACL engine given by `--shapes` or
+/// `--shacl-endpoint`. `GET /health` reports that the server is up.
+/// Errors are returned as JSON bodies with their status, kind, message and location.
+#[derive(Parser, Debug)]
+struct Args {
+    /// Address to listen on.
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    address: SocketAddr,
+
+    /// How IRIs are minted for blank nodes, blank nodes are kept by default.
+    #[arg(long, value_enum, default_value = None)]
+    minting: Option<IriMinting>,
+
+    /// Add the value of each observation and measurement converted to the SI unit of its
+    /// quantity kind, linked with `cat:normalizedValue`.
+    #[arg(long, default_value_t = false)]
+    normalize_units: bool,
+
+    /// JSON file of additional units, see the `converter`.
+    #[arg(long, default_value = None)]
+    units: Option<String>,
+
+    /// Keep units without known QUDT IRI as plain literals instead of failing the conversion.
+    #[arg(long, default_value_t = false)]
+    untyped_units: bool,
+
+    /// SHACL shapes of `POST /validate`, validated with the native engine unless
+    /// `--shacl-endpoint` is given: a file, an HTTP(S) URL or a catplus-ontology release tag.
+    #[arg(long, default_value = None)]
+    shapes: Option<ShapesSource>,
+
+    /// Validate with the shacl-api server at this URL.
+    #[arg(long, default_value = None)]
+    shacl_endpoint: Option<String>,
+
+    /// Largest request body accepted, in MB.
+    #[arg(long, default_value_t = 256)]
+    max_body_size: usize,
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+
+    let options = ConversionOptions {
+        minting: args.minting,
+        normalize_units: args.normalize_units,
+        units: UnitOptions::load(args.units.as_deref().map(Path::new), args.untyped_units)?,
+        ..ConversionOptions::default()
+    };
+    // Built before the runtime is started, as downloading the shapes and checking that the
+    // SHACL API is available block.
+    let validator =
+        GraphValidator::from_sources(args.shapes.as_ref(), args.shacl_endpoint.as_deref())?;
+    let service =
+        Service::new(options, validator).with_max_body_size(args.max_body_size * 1024 * 1024);
+
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .context("Failed to start the runtime")?
+        .block_on(run(args.address, service))
+}
+
+async fn run(address: SocketAddr, service: Service) -> Result<()> {
+    let listener = TcpListener::bind(address)
+        .await
+        .with_context(|| format!("Failed to listen on {}", address))?;
+    eprintln!("Listening on http://{}", listener.local_addr()?);
+    tokio::select! {
+        result = serve(listener, Arc::new(service)) => result,
+        _ = tokio::signal::ctrl_c() => Ok(()),
+    }
+}
@@ -0,0 +1,112 @@
+//! Content negotiation with the `Accept` header of requests.
+use crate::error::ApiError;
+use hyper::{header, HeaderMap, StatusCode};
+
+/// Chooses the representation of a response among `offers`, pairs of a media type and a
+/// value, from the `Accept` header of the request (see RFC 9110, section 12.5.1).
+///
+/// The offer with the highest quality wins, ties go to the first offer, which is also the
+/// default when there is no `Accept` header. Media ranges such as `text/*` and `*/*` match
+/// any offer of their type.
+///
+/// # Returns
+/// The media type and value of the chosen offer, or a `406 Not Acceptable` error listing the
+/// offers if none is accepted.
+pub fn negotiate<T: Copy>(
+    headers: &HeaderMap,
+    offers: &[(&'static str, T)],
+) -> Result<(&'static str, T), ApiError> {
+    let ranges: Vec<(String, f32)> = headers
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(media_range)
+        .collect();
+    if ranges.is_empty() {
+        return offers.first().copied().ok_or_else(|| not_acceptable(offers));
+    }
+
+    let mut best: Option<(f32, (&'static str, T))> = None;
+    for offer in offers {
+        // The most specific range matching the offer gives its quality.
+        let quality = ranges
+            .iter()
+            .filter_map(|(range, quality)| {
+                specificity(range, offer.0).map(|specificity| (specificity, *quality))
+            })
+            .max_by_key(|(specificity, _)| *specificity)
+            .map_or(0.0, |(_, quality)| quality);
+        if quality > 0.0 && best.is_none_or(|(best_quality, _)| quality > best_quality) {
+            best = Some((quality, *offer));
+        }
+    }
+    best.map(|(_, offer)| offer).ok_or_else(|| not_acceptable(offers))
+}
+
+/// Parses a media range of an `Accept` header, e.g. `text/turtle;q=0.8`, to its lowercase
+/// media type and quality.
+fn media_range(range: &str) -> Option<(String, f32)> {
+    let mut parameters = range.split(';');
+    let media_type = parameters.next()?.trim().to_lowercase();
+    if media_type.is_empty() {
+        return None;
+    }
+    let quality = parameters
+        .filter_map(|parameter| parameter.trim().strip_prefix("q="))
+        .find_map(|quality| quality.trim().parse::<f32>().ok())
+        .unwrap_or(1.0);
+    Some((media_type, quality))
+}
+
+/// How specifically `range` matches `media_type`: 2 for the same type, 1 for `type/*` and 0
+/// for `*/*`, `None` if it does not match.
+fn specificity(range: &str, media_type: &str) -> Option<u8> {
+    match range {
+        "*/*" => Some(0),
+        range if range == media_type => Some(2),
+        range => {
+            let main_type = range.strip_suffix("/*")?;
+            media_type.split('/').next().filter(|t| *t == main_type).map(|_| 1)
+        }
+    }
+}
+
+fn not_acceptable<T>(offers: &[(&'static str, T)]) -> ApiError {
+    let media_types: Vec<&str> = offers.iter().map(|(media_type, _)| *media_type).collect();
+    ApiError::new(
+        StatusCode::NOT_ACCEPTABLE,
+        "not_acceptable",
+        format!(
+            "None of the accepted media types is available, use one of: {}",
+            media_types.join(", ")
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::header::HeaderValue;
+
+    const OFFERS: [(&str, u8); 3] =
+        [("text/turtle", 0), ("application/ld+json", 1), ("application/n-triples", 2)];
+
+    fn accept(value: &str) -> Result<u8, StatusCode> {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, HeaderValue::from_str(value).unwrap());
+        negotiate(&headers, &OFFERS).map(|(_, offer)| offer).map_err(|error| error.status)
+    }
+
+    #[test]
+    fn test_negotiate() {
+        assert_eq!(negotiate(&HeaderMap::new(), &OFFERS).unwrap(), ("text/turtle", 0));
+        assert_eq!(accept("application/n-triples"), Ok(2));
+        assert_eq!(accept("*/*"), Ok(0));
+        assert_eq!(accept("application/*"), Ok(1));
+        assert_eq!(accept("text/turtle;q=0.5, application/ld+json"), Ok(1));
+        assert_eq!(accept("application/*;q=0.2, application/n-triples;q=0.9, */*;q=0.1"), Ok(2));
+        assert_eq!(accept("*/*, text/turtle;q=0"), Ok(1));
+        assert_eq!(accept("text/html"), Err(StatusCode::NOT_ACCEPTABLE));
+    }
+}
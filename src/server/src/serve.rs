@@ -0,0 +1,62 @@
+//! The HTTP/1.1 server of a [Service].
+use crate::{error::ApiError, service::Service};
+use anyhow::Result;
+use http_body_util::{BodyExt, Full, LengthLimitError, Limited};
+use hyper::{
+    body::{Bytes, Incoming},
+    server::conn::http1,
+    service::service_fn,
+    Request, Response, StatusCode,
+};
+use hyper_util::rt::TokioIo;
+use std::{convert::Infallible, sync::Arc};
+use tokio::net::TcpListener;
+
+/// Answers the requests received on `listener` with `service`, until an error stops accepting
+/// connections. Each connection is served by its own task, and conversions and validations
+/// run on blocking threads so that they do not hold up the other connections.
+pub async fn serve(listener: TcpListener, service: Arc<Service>) -> Result<()> {
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let service = service.clone();
+        tokio::spawn(async move {
+            let handler = service_fn(move |request| respond(service.clone(), request));
+            if let Err(error) =
+                http1::Builder::new().serve_connection(TokioIo::new(stream), handler).await
+            {
+                eprintln!("Error while serving a connection: {}", error);
+            }
+        });
+    }
+}
+
+async fn respond(
+    service: Arc<Service>,
+    request: Request<Incoming>,
+) -> Result<Response<Full<Bytes>>, Infallible> {
+    let (parts, body) = request.into_parts();
+    let response = match Limited::new(body, service.max_body_size()).collect().await {
+        Ok(body) => {
+            let body = body.to_bytes();
+            let (method, path, headers) =
+                (parts.method.clone(), parts.uri.path().to_string(), parts.headers);
+            tokio::task::spawn_blocking(move || service.handle(&method, &path, &headers, &body))
+                .await
+                .unwrap_or_else(|error| ApiError::internal(error.into()).into_response())
+        }
+        Err(error) if error.is::<LengthLimitError>() => ApiError::new(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            "payload_too_large",
+            format!("The body is larger than {} bytes", service.max_body_size()),
+        )
+        .into_response(),
+        Err(error) => ApiError::new(
+            StatusCode::BAD_REQUEST,
+            "body",
+            format!("Failed to read the body: {}", error),
+        )
+        .into_response(),
+    };
+    eprintln!("{} {} {}", parts.method, parts.uri.path(), response.status());
+    Ok(response.map(|body| Full::new(Bytes::from(body))))
+}
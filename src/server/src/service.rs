@@ -0,0 +1,180 @@
+//! The endpoints of the HTTP API, independent of the server that receives the requests.
+use crate::{error::ApiError, negotiation::negotiate};
+use anyhow::Context;
+use catplus_common::rdf::{rdf_format::RdfFormat, rdf_parser::parse_rdf_to_graph};
+use clap::ValueEnum;
+use converter::{
+    convert::{input_to_graph, serialize_graph, ConversionOptions, InputType},
+    validate::GraphValidator,
+};
+use hyper::{header, HeaderMap, Method, Response, StatusCode};
+use serde_json::json;
+use validation::{
+    core::Severity,
+    report::{render_report, ReportFormat},
+};
+
+/// Representations of validation reports, the JSON summary by default.
+const REPORT_FORMATS: [(&str, ReportFormat); 4] = [
+    ("application/json", ReportFormat::Json),
+    ("text/turtle", ReportFormat::Turtle),
+    ("text/plain", ReportFormat::Human),
+    ("application/xml", ReportFormat::Junit),
+];
+
+/// Default largest request body, in bytes.
+const MAX_BODY_SIZE: usize = 256 * 1024 * 1024;
+
+/// Converts and validates CAT+ data:
+///
+/// - `POST /convert/{synth|hci|agilent|auto}` converts the JSON body to RDF, in the format
+///   negotiated with the `Accept` header (Turtle by default).
+/// - `POST /validate` validates the RDF body, in the format of its `Content-Type` (detected
+///   if there is none), and returns the report in the format negotiated with the `Accept`
+///   header (JSON by default).
+/// - `GET /health` reports that the service is up, and whether it can validate.
+///
+/// Errors are JSON bodies (see [ApiError]).
+pub struct Service {
+    options: ConversionOptions,
+    validator: Option<GraphValidator>,
+    max_body_size: usize,
+}
+
+impl Service {
+    /// A service converting with `options`, and validating with `validator` if there is one.
+    pub fn new(options: ConversionOptions, validator: Option<GraphValidator>) -> Self {
+        Service { options, validator, max_body_size: MAX_BODY_SIZE }
+    }
+
+    /// Rejects the requests with a body larger than `max_body_size` bytes.
+    pub fn with_max_body_size(self, max_body_size: usize) -> Self {
+        Service { max_body_size, ..self }
+    }
+
+    pub fn max_body_size(&self) -> usize {
+        self.max_body_size
+    }
+
+    /// Answers a request for `path` with its already read `body`.
+    pub fn handle(
+        &self,
+        method: &Method,
+        path: &str,
+        headers: &HeaderMap,
+        body: &[u8],
+    ) -> Response<String> {
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+        let response = match (method, segments.as_slice()) {
+            (&Method::GET, ["health"]) => self.health(),
+            (&Method::POST, ["convert", input_type]) => self.convert(input_type, headers, body),
+            (&Method::POST, ["validate"]) => self.validate(headers, body),
+            (_, ["health"] | ["convert", _] | ["validate"]) => Err(ApiError::new(
+                StatusCode::METHOD_NOT_ALLOWED,
+                "method_not_allowed",
+                format!("{} is not allowed on '{}'", method, path),
+            )),
+            _ => Err(not_found(path)),
+        };
+        response.unwrap_or_else(ApiError::into_response)
+    }
+
+    fn health(&self) -> Result<Response<String>, ApiError> {
+        let body = json!({
+            "status": "ok",
+            "version": env!("CARGO_PKG_VERSION"),
+            "validation": self.validator.is_some(),
+        });
+        Ok(response("application/json", body.to_string() + "\n"))
+    }
+
+    fn convert(
+        &self,
+        input_type: &str,
+        headers: &HeaderMap,
+        body: &[u8],
+    ) -> Result<Response<String>, ApiError> {
+        let input_type = InputType::from_str(input_type, true).map_err(|_| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                "not_found",
+                format!("Unknown input type '{}', use synth, hci, agilent or auto", input_type),
+            )
+        })?;
+        let offers: Vec<(&str, RdfFormat)> = RdfFormat::value_variants()
+            .iter()
+            .map(|format| (format.media_type(), *format))
+            .collect();
+        let (media_type, format) = negotiate(headers, &offers)?;
+        let input_content = utf8(body)?;
+
+        let graph_builder = input_to_graph(input_type, input_content, &self.options)
+            .map_err(ApiError::conversion)?;
+        let serialized_graph = serialize_graph(&graph_builder, &format)
+            .with_context(|| format!("Failed to serialize to {:?}", format))
+            .map_err(ApiError::internal)?;
+        Ok(response(media_type, serialized_graph))
+    }
+
+    fn validate(&self, headers: &HeaderMap, body: &[u8]) -> Result<Response<String>, ApiError> {
+        let Some(validator) = &self.validator else {
+            return Err(ApiError::new(
+                StatusCode::NOT_IMPLEMENTED,
+                "no_validation",
+                "No SHACL engine is configured, start the server with --shapes or --shacl-endpoint",
+            ));
+        };
+        let (media_type, report_format) = negotiate(headers, &REPORT_FORMATS)?;
+        let input_format = match headers.get(header::CONTENT_TYPE) {
+            Some(content_type) => {
+                Some(content_type.to_str().ok().and_then(RdfFormat::from_media_type).ok_or_else(
+                    || {
+                        ApiError::new(
+                            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                            "unsupported_media_type",
+                            format!("Unsupported RDF media type {:?}", content_type),
+                        )
+                    },
+                )?)
+            }
+            None => None,
+        };
+
+        let graph = parse_rdf_to_graph(utf8(body)?, input_format.as_ref()).map_err(|error| {
+            ApiError::new(StatusCode::BAD_REQUEST, "invalid_rdf", format!("{:#}", error))
+        })?;
+        let report = validator.validate(&graph).map_err(ApiError::internal)?;
+        let rendered = render_report(&report, report_format, Severity::Info, false)
+            .map_err(ApiError::internal)?;
+        Ok(response(media_type, rendered))
+    }
+}
+
+fn response(media_type: &str, body: String) -> Response<String> {
+    Response::builder()
+        .header(header::CONTENT_TYPE, media_type)
+        .body(body)
+        .expect("a valid response")
+}
+
+fn utf8(body: &[u8]) -> Result<&str, ApiError> {
+    std::str::from_utf8(body).map_err(|error| {
+        ApiError::new(
+            StatusCode::BAD_REQUEST,
+            "encoding",
+            format!("The body is not UTF-8: {}", error),
+        )
+    })
+}
+
+fn not_found(path: &str) -> ApiError {
+    ApiError::new(
+        StatusCode::NOT_FOUND,
+        "not_found",
+        format!(
+            "Nothing at '{}', use GET /health, POST /convert/{{synth|hci|agilent|auto}} or \
+             POST /validate",
+            path
+        ),
+    )
+}
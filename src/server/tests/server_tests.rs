@@ -0,0 +1,290 @@
+use catplus_common::rdf::rdf_parser::parse_turtle_to_graph;
+use converter::{convert::ConversionOptions, validate::GraphValidator};
+use hyper::{header, HeaderMap, Method, Response, StatusCode};
+use serde_json::Value;
+use server::{serve::serve, service::Service};
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpListener,
+    path::Path,
+    process::{Child, Command, Stdio},
+    sync::Arc,
+    thread,
+};
+use validation::engines::native::NativeShaclEngine;
+
+const SHAPES: &str = r#"
+    PREFIX sh: <http://www.w3.org/ns/shacl#>
+    PREFIX cat: <http://example.org/cat#>
+    PREFIX purl: <http://purl.allotrope.org/ontologies/>
+
+    cat:BatchShape a sh:NodeShape;
+        sh:targetClass cat:Batch;
+        sh:property [ sh:path purl:identifier; sh:pattern "^[0-9]+$" ].
+"#;
+
+const SYNTH: &str = r#"
+    {
+        "batchID": "23",
+        "Actions": [
+            {
+                "actionName": "setPressureAction",
+                "pressureMeasurement": {
+                    "value": 250,
+                    "unit": "UNIT"
+                },
+                "startTime": "2024-07-25T12:00:00",
+                "endingTime": "2024-07-25T12:00:02",
+                "methodName": "set_pressure",
+                "equipmentName": "Chemspeed SWING XL",
+                "subEquipmentName": "pressure unit",
+                "containerID": "1",
+                "containerBarcode": "1"
+            }
+        ]
+    }
+"#;
+
+const BATCH: &str = r#"
+    PREFIX cat: <http://example.org/cat#>
+    PREFIX purl: <http://purl.allotrope.org/ontologies/>
+
+    <http://example.org/batch> a cat:Batch; purl:identifier "ID".
+"#;
+
+fn service() -> Service {
+    Service::new(ConversionOptions::default(), None)
+}
+
+fn validating_service() -> Service {
+    let shapes = parse_turtle_to_graph(SHAPES).unwrap();
+    let validator = GraphValidator::new(Box::new(NativeShaclEngine::new()), Some(shapes));
+    Service::new(ConversionOptions::default(), Some(validator))
+}
+
+fn request(
+    service: &Service,
+    method: Method,
+    path: &str,
+    headers: &[(header::HeaderName, &str)],
+    body: &str,
+) -> Response<String> {
+    let mut header_map = HeaderMap::new();
+    for (name, value) in headers {
+        header_map.insert(name, value.parse().unwrap());
+    }
+    service.handle(&method, path, &header_map, body.as_bytes())
+}
+
+fn content_type(response: &Response<String>) -> &str {
+    response.headers()[header::CONTENT_TYPE].to_str().unwrap()
+}
+
+fn json_body(response: &Response<String>) -> Value {
+    assert_eq!(content_type(response), "application/json");
+    serde_json::from_str(response.body()).unwrap()
+}
+
+#[test]
+fn test_health() {
+    let response = request(&service(), Method::GET, "/health", &[], "");
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = json_body(&response);
+    assert_eq!(body["status"], "ok");
+    assert_eq!(body["validation"], false);
+}
+
+#[test]
+fn test_convert_negotiation() {
+    let service = service();
+    let synth = SYNTH.replace("UNIT", "bar");
+
+    let response = request(&service, Method::POST, "/convert/synth", &[], &synth);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(content_type(&response), "text/turtle");
+    assert!(response.body().contains("unit:Bar"), "{}", response.body());
+
+    let accept = [(header::ACCEPT, "application/n-triples")];
+    let response = request(&service, Method::POST, "/convert/auto", &accept, &synth);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(content_type(&response), "application/n-triples");
+    assert!(response.body().contains("<http://qudt.org/vocab/unit/Bar>"), "{}", response.body());
+
+    let accept = [(header::ACCEPT, "text/html, application/ld+json;q=0.5")];
+    let response = request(&service, Method::POST, "/convert/synth", &accept, &synth);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(content_type(&response), "application/ld+json");
+    serde_json::from_str::<Value>(response.body()).unwrap();
+
+    let accept = [(header::ACCEPT, "text/html")];
+    let response = request(&service, Method::POST, "/convert/synth", &accept, &synth);
+    assert_eq!(response.status(), StatusCode::NOT_ACCEPTABLE);
+    assert_eq!(json_body(&response)["error"], "not_acceptable");
+}
+
+#[test]
+fn test_convert_errors() {
+    let service = service();
+
+    let furlong = SYNTH.replace("UNIT", "furlong");
+    let response = request(&service, Method::POST, "/convert/synth", &[], &furlong);
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body = json_body(&response);
+    assert_eq!(body["status"], 422);
+    assert_eq!(body["error"], "unknown_unit");
+    assert_eq!(body["location"]["pointer"], "/Actions/0/pressureMeasurement/unit");
+
+    for path in ["/convert/synth", "/convert/auto"] {
+        let response = request(&service, Method::POST, path, &[], "{ \"batchID\": ");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = json_body(&response);
+        assert_eq!(body["error"], "syntax");
+        assert_eq!(body["location"]["line"], 1);
+    }
+
+    let response = request(&service, Method::POST, "/convert/cobol", &[], "{}");
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_eq!(json_body(&response)["error"], "not_found");
+
+    let response = request(&service, Method::GET, "/convert/synth", &[], "");
+    assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(json_body(&response)["error"], "method_not_allowed");
+
+    let response = request(&service, Method::GET, "/", &[], "");
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[test]
+fn test_validate() {
+    let response = request(&service(), Method::POST, "/validate", &[], BATCH);
+    assert_eq!(response.status(), StatusCode::NOT_IMPLEMENTED);
+    assert_eq!(json_body(&response)["error"], "no_validation");
+
+    let service = validating_service();
+    let turtle = [(header::CONTENT_TYPE, "text/turtle")];
+    let response = request(&service, Method::POST, "/validate", &turtle, BATCH);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(json_body(&response)["conforms"], false);
+
+    let conforming = BATCH.replace("\"ID\"", "\"23\"");
+    let response = request(&service, Method::POST, "/validate", &turtle, &conforming);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(json_body(&response)["conforms"], true);
+
+    // Without Content-Type, the format is detected from the body.
+    let ntriples = "<urn:a> <urn:b> <urn:c> .\n";
+    let response = request(&service, Method::POST, "/validate", &[], ntriples);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(json_body(&response)["conforms"], true);
+
+    let headers = [turtle[0].clone(), (header::ACCEPT, "text/plain")];
+    let response = request(&service, Method::POST, "/validate", &headers, BATCH);
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(content_type(&response), "text/plain");
+
+    let headers = [(header::CONTENT_TYPE, "image/png")];
+    let response = request(&service, Method::POST, "/validate", &headers, BATCH);
+    assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    assert_eq!(json_body(&response)["error"], "unsupported_media_type");
+
+    let headers = [(header::CONTENT_TYPE, "text/turtle; charset=utf-8")];
+    let response = request(&service, Method::POST, "/validate", &headers, "not RDF at all {");
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(json_body(&response)["error"], "invalid_rdf");
+}
+
+#[test]
+fn test_serve() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let listener = runtime.block_on(tokio::net::TcpListener::bind("127.0.0.1:0")).unwrap();
+    let address = listener.local_addr().unwrap();
+    runtime.spawn(serve(listener, Arc::new(service().with_max_body_size(4096))));
+
+    let client = reqwest::blocking::Client::new();
+    let response = client.get(format!("http://{}/health", address)).send().unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+
+    let response = client
+        .post(format!("http://{}/convert/synth", address))
+        .header("Accept", "application/n-triples")
+        .body(SYNTH.replace("UNIT", "bar"))
+        .send()
+        .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "application/n-triples");
+    assert!(response.text().unwrap().contains("<http://qudt.org/vocab/unit/Bar>"));
+
+    let response = client
+        .post(format!("http://{}/convert/synth", address))
+        .body(" ".repeat(8192))
+        .send()
+        .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::PAYLOAD_TOO_LARGE);
+    let body: Value = serde_json::from_str(&response.text().unwrap()).unwrap();
+    assert_eq!(body["error"], "payload_too_large");
+}
+
+/// Serves `SHAPES` in answer to any request, as a remote shapes file or a SHACL API.
+fn serve_shapes() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            let mut request = Vec::new();
+            let mut buffer = [0; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                match stream.read(&mut buffer) {
+                    Ok(0) | Err(_) => break,
+                    Ok(read) => request.extend_from_slice(&buffer[..read]),
+                }
+            }
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/turtle\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                SHAPES.len(),
+                SHAPES
+            );
+            let _ = stream.write_all(response.as_bytes());
+        }
+    });
+    format!("http://{}", address)
+}
+
+/// Starts the server binary with `args`, and returns it with the URL it listens on.
+fn start_server(args: &[&str], cache_dir: &Path) -> (Child, String) {
+    let mut server = Command::new(env!("CARGO_BIN_EXE_server"))
+        .args(["--address", "127.0.0.1:0"])
+        .args(args)
+        .env("CATPLUS_CACHE_DIR", cache_dir)
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stderr = BufReader::new(server.stderr.take().unwrap()).lines();
+    let url = loop {
+        match stderr.next() {
+            Some(line) => match line.unwrap().strip_prefix("Listening on ") {
+                Some(url) => break url.to_string(),
+                None => continue,
+            },
+            None => panic!("The server stopped before listening: {:?}", server.wait()),
+        }
+    };
+    // Drained so that the server can go on logging its requests.
+    thread::spawn(move || stderr.for_each(drop));
+    (server, url)
+}
+
+#[test]
+fn test_start_with_remote_validation() {
+    let shapes_url = serve_shapes();
+    let cache_dir = std::env::temp_dir().join(format!("catplus-server-{}", uuid::Uuid::new_v4()));
+    for args in
+        [["--shapes", &format!("{}/shapes.ttl", shapes_url)], ["--shacl-endpoint", &shapes_url]]
+    {
+        let (mut server, url) = start_server(&args, &cache_dir);
+        let response = reqwest::blocking::get(format!("{}/health", url)).and_then(|r| r.text());
+        server.kill().unwrap();
+        let body: Value = serde_json::from_str(&response.unwrap()).unwrap();
+        assert_eq!(body["validation"], true, "{:?}", args);
+    }
+    std::fs::remove_dir_all(cache_dir).unwrap();
+}
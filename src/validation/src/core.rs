@@ -1,32 +1,204 @@
 /// Interface for validating an RDF graph.
-use sophia::inmem::graph::LightGraph;
-use sophia_api::prelude::*;
-use std::error::Error;
+use catplus_common::graph::{
+    namespaces::{prov, schema, sh},
+    utils::generate_bnode_term,
+};
+use serde::Serialize;
+use sophia::{api::ns::rdf, inmem::graph::LightGraph};
+use sophia_api::{
+    ns::NsTerm,
+    prelude::*,
+    term::{SimpleTerm, TermKind},
+};
+use std::{collections::HashMap, error::Error};
+
+/// Severity of a validation result (sh:resultSeverity), from the most to the least severe.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, clap::ValueEnum)]
+pub enum Severity {
+    Violation,
+    Warning,
+    Info,
+}
+
+impl Severity {
+    /// Whether `self` is at least as severe as `threshold`.
+    pub fn is_at_least(self, threshold: Severity) -> bool {
+        self <= threshold
+    }
+
+    /// Reads a severity IRI, unknown severities are considered violations.
+    fn from_term(term: &SimpleTerm) -> Self {
+        if Term::eq(term, sh::Warning) {
+            Severity::Warning
+        } else if Term::eq(term, sh::Info) {
+            Severity::Info
+        } else {
+            Severity::Violation
+        }
+    }
+}
+
+/// A result of a SHACL validation report (sh:ValidationResult).
+///
+/// Terms are given as IRIs, the lexical form of literals, or `_:id` for blank nodes.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ValidationResult {
+    pub focus_node: String,
+    pub result_path: Option<String>,
+    pub value: Option<String>,
+    pub source_shape: Option<String>,
+    pub constraint_component: Option<String>,
+    pub severity: Severity,
+    pub messages: Vec<String>,
+}
+
+/// The shapes a graph was validated against.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ShapesInfo {
+    /// Path or URL the shapes were read from.
+    pub source: String,
+    /// Release tag of the shapes, if they come from an ontology release.
+    pub version: Option<String>,
+    /// SHA-256 checksum of the shapes file.
+    pub sha256: String,
+}
 
 /// A SHACL validation report for an RDF graph.
 #[derive(Clone, Debug)]
 pub struct ValidationReport {
     pub conforms: bool,
     pub graph: LightGraph,
-    //TODO: Summary of severity counts.
-    //pub summary: HashMap<Severity, u32>,
+    /// The results of the report, sorted by severity and focus node.
+    pub results: Vec<ValidationResult>,
+    /// Number of results per severity.
+    pub summary: HashMap<Severity, u32>,
+    /// The shapes used for the validation, if known.
+    pub shapes: Option<ShapesInfo>,
 }
 
 impl ValidationReport {
     pub fn new(conforms: bool, graph: LightGraph) -> Self {
-        ValidationReport { conforms, graph }
+        let results = parse_results(&graph);
+        let summary = summarize(&results);
+        ValidationReport { conforms, graph, results, summary, shapes: None }
     }
 
     pub fn from_graph(graph: LightGraph) -> Self {
         // NOTE: Only looks at value of the first sh:conforms triple
         // not found -> not conform
         let conforms = graph
-            .triples_matching(Any, ["http://www.w3.org/ns/shacl#conforms".as_simple()], Any)
+            .triples_matching(Any, [sh::conforms], Any)
             .map(|t| t.map_or(false, |t| t[2].lexical_form().unwrap() == "true"))
             .next()
             .unwrap_or(false);
 
-        ValidationReport { conforms, graph }
+        ValidationReport::new(conforms, graph)
+    }
+
+    /// Records the shapes used for the validation, in the report graph as a `prov:Entity`
+    /// used by the sh:ValidationReport.
+    pub fn with_shapes(mut self, shapes: ShapesInfo) -> Result<Self, Box<dyn Error>> {
+        let report_node = self
+            .graph
+            .triples_matching(Any, [rdf::type_], [sh::ValidationReport])
+            .filter_map(Result::ok)
+            .map(|[s, _, _]| s.into_term::<SimpleTerm<'static>>())
+            .next();
+        let report_node = match report_node {
+            Some(node) => node,
+            None => {
+                let node = generate_bnode_term();
+                self.graph.insert(&node, rdf::type_, sh::ValidationReport)?;
+                node
+            }
+        };
+
+        let shapes_node = generate_bnode_term();
+        self.graph.insert(&report_node, prov::used, &shapes_node)?;
+        self.graph.insert(&shapes_node, rdf::type_, prov::Entity)?;
+        self.graph.insert(&shapes_node, prov::atLocation, shapes.source.as_str())?;
+        self.graph.insert(&shapes_node, schema::sha256, shapes.sha256.as_str())?;
+        if let Some(version) = &shapes.version {
+            self.graph.insert(&shapes_node, schema::version, version.as_str())?;
+        }
+
+        self.shapes = Some(shapes);
+        Ok(self)
+    }
+
+    /// Whether the report has results of at least the given severity.
+    pub fn fails_at(&self, threshold: Severity) -> bool {
+        self.results.iter().any(|result| result.severity.is_at_least(threshold))
+    }
+
+    /// Number of results with the given severity.
+    pub fn count(&self, severity: Severity) -> u32 {
+        self.summary.get(&severity).copied().unwrap_or(0)
+    }
+}
+
+/// Reads the sh:result entries of a report graph, results without a focus node are skipped.
+fn parse_results(graph: &LightGraph) -> Vec<ValidationResult> {
+    let object = |subject: &SimpleTerm, predicate: NsTerm| -> Option<SimpleTerm<'static>> {
+        graph
+            .triples_matching([subject], [predicate], Any)
+            .filter_map(Result::ok)
+            .map(|[_, _, o]| o.into_term())
+            .next()
+    };
+
+    let mut results: Vec<ValidationResult> = graph
+        .triples_matching(Any, [sh::result], Any)
+        .filter_map(Result::ok)
+        .filter_map(|[_, _, result]| {
+            let focus_node = object(result, sh::focusNode)?;
+            let mut messages: Vec<String> = graph
+                .triples_matching([result], [sh::resultMessage], Any)
+                .filter_map(Result::ok)
+                .map(|[_, _, message]| display(message))
+                .collect();
+            messages.sort();
+            Some(ValidationResult {
+                focus_node: display(&focus_node),
+                result_path: object(result, sh::resultPath).as_ref().map(display),
+                value: object(result, sh::value).as_ref().map(display),
+                source_shape: object(result, sh::sourceShape).as_ref().map(display),
+                constraint_component: object(result, sh::sourceConstraintComponent)
+                    .as_ref()
+                    .map(display),
+                severity: object(result, sh::resultSeverity)
+                    .map_or(Severity::Violation, |s| Severity::from_term(&s)),
+                messages,
+            })
+        })
+        .collect();
+
+    results.sort_by(|a, b| {
+        (a.severity, &a.focus_node, &a.result_path, &a.constraint_component, &a.value).cmp(&(
+            b.severity,
+            &b.focus_node,
+            &b.result_path,
+            &b.constraint_component,
+            &b.value,
+        ))
+    });
+    results
+}
+
+fn summarize(results: &[ValidationResult]) -> HashMap<Severity, u32> {
+    let mut summary = HashMap::new();
+    for result in results {
+        *summary.entry(result.severity).or_insert(0) += 1;
+    }
+    summary
+}
+
+fn display(term: &SimpleTerm) -> String {
+    match term.kind() {
+        TermKind::Iri => term.iri().unwrap().as_str().to_string(),
+        TermKind::Literal => term.lexical_form().unwrap().to_string(),
+        TermKind::BlankNode => format!("_:{}", term.bnode_id().unwrap().as_str()),
+        _ => format!("{:?}", term),
     }
 }
 
@@ -0,0 +1,1280 @@
+/// A native implementation of a SHACL Core validation engine.
+/// See: https://www.w3.org/TR/shacl/#core-components
+///
+/// The engine runs entirely in-process and does not need any external service.
+/// SHACL-SPARQL constraints are not supported and are ignored.
+use regex::RegexBuilder;
+use sophia::{
+    api::ns::{rdf, rdfs, xsd},
+    inmem::graph::{FastGraph, LightGraph},
+};
+use sophia_api::{prelude::*, term::SimpleTerm};
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    error::Error,
+};
+
+use crate::core::{ShaclEngine, ValidationReport};
+use catplus_common::graph::{namespaces::sh, utils::generate_bnode_term};
+
+type Node = SimpleTerm<'static>;
+
+/// Nested shapes (sh:node, sh:not, ...) deeper than this are considered recursive.
+const MAX_DEPTH: usize = 64;
+
+#[derive(Default)]
+pub struct NativeShaclEngine {
+    shapes: Option<LightGraph>,
+}
+
+impl NativeShaclEngine {
+    pub fn new() -> Self {
+        NativeShaclEngine { shapes: None }
+    }
+
+    /// Uses `shapes` when no shapes graph is given to [ShaclEngine::validate].
+    pub fn with_shapes(shapes: LightGraph) -> Self {
+        NativeShaclEngine { shapes: Some(shapes) }
+    }
+}
+
+impl ShaclEngine for NativeShaclEngine {
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    fn validate(
+        &self,
+        data: &LightGraph,
+        shapes: Option<&LightGraph>,
+    ) -> Result<ValidationReport, Box<dyn Error>> {
+        let shapes = shapes
+            .or(self.shapes.as_ref())
+            .ok_or("the native SHACL engine has no default shapes, a shapes graph is required")?;
+
+        // Index both graphs for fast lookups by subject or object.
+        let data: FastGraph = data.triples().collect_triples()?;
+        let shapes: FastGraph = shapes.triples().collect_triples()?;
+
+        let validator = Validator::new(&data, &shapes);
+        let results = validator.validate_all()?;
+
+        Ok(ValidationReport::from_graph(build_report(&shapes, &results)?))
+    }
+}
+
+/// One entry of the validation report (sh:ValidationResult).
+struct ResultEntry {
+    focus_node: Node,
+    path: Option<Node>,
+    value: Option<Node>,
+    source_shape: Node,
+    component: Node,
+    severity: Node,
+    messages: Vec<Node>,
+}
+
+/// The shape and focus node currently being validated, used to build results.
+struct ShapeContext<'a> {
+    focus: &'a Node,
+    path: Option<&'a Node>,
+    shape: &'a Node,
+    severity: &'a Node,
+    messages: &'a [Node],
+}
+
+impl ShapeContext<'_> {
+    /// Creates a result, using the messages of the shape if it declares any.
+    fn result(&self, component: Node, value: Option<Node>, default_message: String) -> ResultEntry {
+        let messages = match self.messages {
+            [] => vec![default_message.as_str().as_simple().into_term()],
+            messages => messages.to_vec(),
+        };
+        ResultEntry {
+            focus_node: self.focus.clone(),
+            path: self.path.cloned(),
+            value,
+            source_shape: self.shape.clone(),
+            component,
+            severity: self.severity.clone(),
+            messages,
+        }
+    }
+}
+
+/// How many members of sh:and, sh:or and sh:xone a value must conform to.
+enum Quantifier {
+    All,
+    Any,
+    ExactlyOne,
+}
+
+impl Quantifier {
+    fn accepts(&self, conforming: usize, members: usize) -> bool {
+        match self {
+            Quantifier::All => conforming == members,
+            Quantifier::Any => conforming > 0,
+            Quantifier::ExactlyOne => conforming == 1,
+        }
+    }
+}
+
+struct Validator<'a> {
+    data: &'a FastGraph,
+    shapes: &'a FastGraph,
+}
+
+fn term<T: Term>(t: T) -> Node {
+    t.into_term()
+}
+
+fn objects<G: Graph>(graph: &G, subject: &Node, predicate: impl Term) -> Vec<Node> {
+    graph
+        .triples_matching([subject], [predicate], Any)
+        .filter_map(Result::ok)
+        .map(|t| term(t.o()))
+        .collect()
+}
+
+fn object<G: Graph>(graph: &G, subject: &Node, predicate: impl Term) -> Option<Node> {
+    objects(graph, subject, predicate).into_iter().next()
+}
+
+fn subjects<G: Graph>(graph: &G, predicate: impl Term, object: &Node) -> Vec<Node> {
+    graph
+        .triples_matching(Any, [predicate], [object])
+        .filter_map(Result::ok)
+        .map(|t| term(t.s()))
+        .collect()
+}
+
+/// Reads the members of an RDF list.
+fn list<G: Graph>(graph: &G, head: &Node) -> Vec<Node> {
+    let nil = term(rdf::nil);
+    let mut members = Vec::new();
+    let mut current = head.clone();
+    let mut seen = HashSet::new();
+    while current != nil && seen.insert(current.clone()) {
+        match object(graph, &current, rdf::first) {
+            Some(first) => members.push(first),
+            None => break,
+        }
+        match object(graph, &current, rdf::rest) {
+            Some(rest) => current = rest,
+            None => break,
+        }
+    }
+    members
+}
+
+fn is_true(node: &Option<Node>) -> bool {
+    node.as_ref().and_then(|n| n.lexical_form().map(|l| &l[..] == "true")).unwrap_or(false)
+}
+
+fn lexical(node: &Node) -> Option<String> {
+    match node.kind() {
+        TermKind::Iri => node.iri().map(|i| i.as_str().to_string()),
+        TermKind::Literal => node.lexical_form().map(|l| l.to_string()),
+        _ => None,
+    }
+}
+
+fn is_numeric(node: &Node) -> bool {
+    const NUMERIC: [&str; 16] = [
+        "decimal",
+        "integer",
+        "double",
+        "float",
+        "long",
+        "int",
+        "short",
+        "byte",
+        "nonNegativeInteger",
+        "nonPositiveInteger",
+        "positiveInteger",
+        "negativeInteger",
+        "unsignedLong",
+        "unsignedInt",
+        "unsignedShort",
+        "unsignedByte",
+    ];
+    node.datatype().is_some_and(|dt| {
+        let dt = dt.as_str();
+        dt.strip_prefix(xsd::PREFIX.as_str()).is_some_and(|local| NUMERIC.contains(&local))
+    })
+}
+
+/// Compares two literals, numerically if both are numbers, lexically if they share a datatype.
+fn compare(left: &Node, right: &Node) -> Option<Ordering> {
+    if left.kind() != TermKind::Literal || right.kind() != TermKind::Literal {
+        return None;
+    }
+    let (l, r) = (left.lexical_form()?, right.lexical_form()?);
+    if is_numeric(left) && is_numeric(right) {
+        let (l, r) = (l.trim().parse::<f64>().ok()?, r.trim().parse::<f64>().ok()?);
+        return l.partial_cmp(&r);
+    }
+    match (left.datatype(), right.datatype()) {
+        (Some(ld), Some(rd)) if ld.as_str() == rd.as_str() => Some(Ord::cmp(&l[..], &r[..])),
+        _ => None,
+    }
+}
+
+/// Checks basic well-formedness of literals with common XSD datatypes.
+fn is_well_formed(node: &Node) -> bool {
+    let (Some(datatype), Some(lexical)) = (node.datatype(), node.lexical_form()) else {
+        return true;
+    };
+    let Some(local) = datatype.as_str().strip_prefix(xsd::PREFIX.as_str()) else {
+        return true;
+    };
+    match local {
+        "boolean" => matches!(&lexical[..], "true" | "false" | "1" | "0"),
+        "double" | "float" => {
+            matches!(&lexical[..], "INF" | "-INF" | "NaN") || lexical.parse::<f64>().is_ok()
+        }
+        "decimal" => is_decimal(&lexical),
+        "integer" | "long" | "int" | "short" | "byte" => lexical.parse::<i128>().is_ok(),
+        _ => true,
+    }
+}
+
+/// Whether `lexical` is in the lexical space of `xsd:decimal`: an optional sign and digits with
+/// an optional decimal point, without exponent or special values (`1e3`, `INF`, `NaN`).
+fn is_decimal(lexical: &str) -> bool {
+    let unsigned = lexical.strip_prefix(['+', '-']).unwrap_or(lexical);
+    let (integer, fraction) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+    !(integer.is_empty() && fraction.is_empty())
+        && integer.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit())
+}
+
+impl<'a> Validator<'a> {
+    fn new(data: &'a FastGraph, shapes: &'a FastGraph) -> Self {
+        Validator { data, shapes }
+    }
+
+    /// Validates all shapes with targets against the data graph.
+    fn validate_all(&self) -> Result<Vec<ResultEntry>, Box<dyn Error>> {
+        let mut results = Vec::new();
+        for shape in self.shapes_with_targets() {
+            for focus in self.focus_nodes(&shape) {
+                self.validate_shape(&shape, &focus, 0, &mut results)?;
+            }
+        }
+        Ok(results)
+    }
+
+    /// Collects all shapes that declare a target, explicit or implicit.
+    fn shapes_with_targets(&self) -> Vec<Node> {
+        let mut shapes: Vec<Node> = Vec::new();
+        let mut seen = HashSet::new();
+        for predicate in
+            [sh::targetClass, sh::targetNode, sh::targetObjectsOf, sh::targetSubjectsOf]
+        {
+            for t in self.shapes.triples_matching(Any, [predicate], Any).filter_map(Result::ok) {
+                let shape = term(t.s());
+                if seen.insert(shape.clone()) {
+                    shapes.push(shape);
+                }
+            }
+        }
+        // Implicit class targets: shapes that are also classes.
+        for shape in subjects(self.shapes, rdf::type_, &term(rdfs::Class)) {
+            let is_shape = [sh::NodeShape, sh::PropertyShape].into_iter().any(|kind| {
+                self.shapes.triples_matching([&shape], [rdf::type_], [kind]).next().is_some()
+            });
+            if is_shape && seen.insert(shape.clone()) {
+                shapes.push(shape);
+            }
+        }
+        shapes
+    }
+
+    /// Computes the focus nodes of a shape from its targets.
+    fn focus_nodes(&self, shape: &Node) -> Vec<Node> {
+        let mut nodes = Vec::new();
+        let mut seen = HashSet::new();
+        let mut push = |node: Node, nodes: &mut Vec<Node>| {
+            if seen.insert(node.clone()) {
+                nodes.push(node);
+            }
+        };
+
+        for node in objects(self.shapes, shape, sh::targetNode) {
+            push(node, &mut nodes);
+        }
+
+        let mut classes = objects(self.shapes, shape, sh::targetClass);
+        if self.shapes.triples_matching([shape], [rdf::type_], [rdfs::Class]).next().is_some() {
+            classes.push(shape.clone());
+        }
+        for class in classes {
+            for node in self.instances_of(&class) {
+                push(node, &mut nodes);
+            }
+        }
+
+        for predicate in objects(self.shapes, shape, sh::targetSubjectsOf) {
+            for t in self.data.triples_matching(Any, [&predicate], Any).filter_map(Result::ok) {
+                push(term(t.s()), &mut nodes);
+            }
+        }
+        for predicate in objects(self.shapes, shape, sh::targetObjectsOf) {
+            for t in self.data.triples_matching(Any, [&predicate], Any).filter_map(Result::ok) {
+                push(term(t.o()), &mut nodes);
+            }
+        }
+        nodes
+    }
+
+    /// Returns the class and all its subclasses in the data graph.
+    fn subclasses(&self, class: &Node) -> HashSet<Node> {
+        let mut classes = HashSet::from([class.clone()]);
+        let mut stack = vec![class.clone()];
+        while let Some(current) = stack.pop() {
+            for sub in subjects(self.data, rdfs::subClassOf, &current) {
+                if classes.insert(sub.clone()) {
+                    stack.push(sub);
+                }
+            }
+        }
+        classes
+    }
+
+    fn instances_of(&self, class: &Node) -> Vec<Node> {
+        let mut instances = Vec::new();
+        let mut seen = HashSet::new();
+        for c in self.subclasses(class) {
+            for node in subjects(self.data, rdf::type_, &c) {
+                if seen.insert(node.clone()) {
+                    instances.push(node);
+                }
+            }
+        }
+        instances
+    }
+
+    fn is_instance_of(&self, node: &Node, class: &Node) -> bool {
+        let classes = self.subclasses(class);
+        objects(self.data, node, rdf::type_).iter().any(|t| classes.contains(t))
+    }
+
+    /// Evaluates a SHACL property path starting at `focus`.
+    fn value_nodes(&self, focus: &Node, path: &Node) -> Vec<Node> {
+        let mut values = Vec::new();
+        let mut seen = HashSet::new();
+        for value in self.eval_path(std::slice::from_ref(focus), path) {
+            if seen.insert(value.clone()) {
+                values.push(value);
+            }
+        }
+        values
+    }
+
+    fn eval_path(&self, start: &[Node], path: &Node) -> Vec<Node> {
+        if path.kind() == TermKind::Iri {
+            return start.iter().flat_map(|node| objects(self.data, node, path)).collect();
+        }
+        if let Some(inverse) = object(self.shapes, path, sh::inversePath) {
+            if inverse.kind() == TermKind::Iri {
+                return start.iter().flat_map(|node| subjects(self.data, &inverse, node)).collect();
+            }
+            // Inverse of a complex path: keep the nodes from which `start` can be reached.
+            let candidates: Vec<Node> = self
+                .data
+                .triples()
+                .filter_map(Result::ok)
+                .flat_map(|t| [term(t.s()), term(t.o())])
+                .collect::<HashSet<_>>()
+                .into_iter()
+                .collect();
+            return candidates
+                .into_iter()
+                .filter(|c| {
+                    self.eval_path(std::slice::from_ref(c), &inverse)
+                        .iter()
+                        .any(|v| start.contains(v))
+                })
+                .collect();
+        }
+        if let Some(alternatives) = object(self.shapes, path, sh::alternativePath) {
+            return list(self.shapes, &alternatives)
+                .iter()
+                .flat_map(|alternative| self.eval_path(start, alternative))
+                .collect();
+        }
+        if let Some(inner) = object(self.shapes, path, sh::zeroOrMorePath) {
+            return self.closure(start, &inner, true);
+        }
+        if let Some(inner) = object(self.shapes, path, sh::oneOrMorePath) {
+            return self.closure(start, &inner, false);
+        }
+        if let Some(inner) = object(self.shapes, path, sh::zeroOrOnePath) {
+            let mut values = start.to_vec();
+            values.extend(self.eval_path(start, &inner));
+            return values;
+        }
+        // Otherwise, the path is a sequence given as an RDF list.
+        list(self.shapes, path)
+            .iter()
+            .fold(start.to_vec(), |nodes, step| self.eval_path(&nodes, step))
+    }
+
+    fn closure(&self, start: &[Node], path: &Node, include_start: bool) -> Vec<Node> {
+        let mut reached: HashSet<Node> = HashSet::new();
+        let mut values = Vec::new();
+        if include_start {
+            for node in start {
+                if reached.insert(node.clone()) {
+                    values.push(node.clone());
+                }
+            }
+        }
+        let mut frontier = start.to_vec();
+        while !frontier.is_empty() {
+            let next = self.eval_path(&frontier, path);
+            frontier = next.into_iter().filter(|n| reached.insert(n.clone())).collect();
+            values.extend(frontier.iter().cloned());
+        }
+        values
+    }
+
+    /// Checks whether `focus` conforms to `shape`, without reporting results.
+    fn conforms(&self, shape: &Node, focus: &Node, depth: usize) -> Result<bool, Box<dyn Error>> {
+        let mut results = Vec::new();
+        self.validate_shape(shape, focus, depth + 1, &mut results)?;
+        Ok(results.is_empty())
+    }
+
+    /// Validates `focus` against `shape` and appends the violations to `results`.
+    fn validate_shape(
+        &self,
+        shape: &Node,
+        focus: &Node,
+        depth: usize,
+        results: &mut Vec<ResultEntry>,
+    ) -> Result<(), Box<dyn Error>> {
+        if depth > MAX_DEPTH {
+            return Err(format!("recursive shape definition detected at {:?}", shape).into());
+        }
+        if is_true(&object(self.shapes, shape, sh::deactivated)) {
+            return Ok(());
+        }
+
+        let path = object(self.shapes, shape, sh::path);
+        let values = match &path {
+            Some(path) => self.value_nodes(focus, path),
+            None => vec![focus.clone()],
+        };
+        let severity =
+            object(self.shapes, shape, sh::severity).unwrap_or_else(|| term(sh::Violation));
+        let messages = objects(self.shapes, shape, sh::message);
+
+        let context = ShapeContext {
+            focus,
+            path: path.as_ref(),
+            shape,
+            severity: &severity,
+            messages: &messages,
+        };
+
+        // Value type constraints
+        for class in objects(self.shapes, shape, sh::class) {
+            for value in values.iter().filter(|v| !self.is_instance_of(v, &class)) {
+                results.push(context.result(
+                    term(sh::ClassConstraintComponent),
+                    Some(value.clone()),
+                    format!("Value is not an instance of {}", display(&class)),
+                ));
+            }
+        }
+        for datatype in objects(self.shapes, shape, sh::datatype) {
+            let datatype_iri = lexical(&datatype);
+            for value in values.iter() {
+                let matches = value.kind() == TermKind::Literal
+                    && value.datatype().map(|dt| dt.as_str().to_string()) == datatype_iri
+                    && is_well_formed(value);
+                if !matches {
+                    results.push(context.result(
+                        term(sh::DatatypeConstraintComponent),
+                        Some(value.clone()),
+                        format!("Value does not have datatype {}", display(&datatype)),
+                    ));
+                }
+            }
+        }
+        for kind in objects(self.shapes, shape, sh::nodeKind) {
+            for value in values.iter().filter(|v| !matches_node_kind(v, &kind)) {
+                results.push(context.result(
+                    term(sh::NodeKindConstraintComponent),
+                    Some(value.clone()),
+                    format!("Value does not have node kind {}", display(&kind)),
+                ));
+            }
+        }
+
+        // Cardinality constraints (property shapes only)
+        if path.is_some() {
+            if let Some(min) = object(self.shapes, shape, sh::minCount).and_then(|n| integer(&n)) {
+                if (values.len() as i64) < min {
+                    results.push(context.result(
+                        term(sh::MinCountConstraintComponent),
+                        None,
+                        format!("Less than {} values", min),
+                    ));
+                }
+            }
+            if let Some(max) = object(self.shapes, shape, sh::maxCount).and_then(|n| integer(&n)) {
+                if (values.len() as i64) > max {
+                    results.push(context.result(
+                        term(sh::MaxCountConstraintComponent),
+                        None,
+                        format!("More than {} values", max),
+                    ));
+                }
+            }
+        }
+
+        // Value range constraints
+        for (predicate, component, accepted, label) in [
+            (
+                sh::minExclusive,
+                sh::MinExclusiveConstraintComponent,
+                &[Ordering::Greater][..],
+                "greater than",
+            ),
+            (
+                sh::minInclusive,
+                sh::MinInclusiveConstraintComponent,
+                &[Ordering::Greater, Ordering::Equal][..],
+                "greater than or equal to",
+            ),
+            (
+                sh::maxExclusive,
+                sh::MaxExclusiveConstraintComponent,
+                &[Ordering::Less][..],
+                "less than",
+            ),
+            (
+                sh::maxInclusive,
+                sh::MaxInclusiveConstraintComponent,
+                &[Ordering::Less, Ordering::Equal][..],
+                "less than or equal to",
+            ),
+        ] {
+            for bound in objects(self.shapes, shape, predicate) {
+                for value in values.iter() {
+                    if !compare(value, &bound).is_some_and(|o| accepted.contains(&o)) {
+                        results.push(context.result(
+                            term(component),
+                            Some(value.clone()),
+                            format!("Value is not {} {}", label, display(&bound)),
+                        ));
+                    }
+                }
+            }
+        }
+
+        // String-based constraints
+        if let Some(min) = object(self.shapes, shape, sh::minLength).and_then(|n| integer(&n)) {
+            for value in values.iter() {
+                if lexical(value).is_none_or(|s| (s.chars().count() as i64) < min) {
+                    results.push(context.result(
+                        term(sh::MinLengthConstraintComponent),
+                        Some(value.clone()),
+                        format!("Value is shorter than {} characters", min),
+                    ));
+                }
+            }
+        }
+        if let Some(max) = object(self.shapes, shape, sh::maxLength).and_then(|n| integer(&n)) {
+            for value in values.iter() {
+                if lexical(value).is_none_or(|s| (s.chars().count() as i64) > max) {
+                    results.push(context.result(
+                        term(sh::MaxLengthConstraintComponent),
+                        Some(value.clone()),
+                        format!("Value is longer than {} characters", max),
+                    ));
+                }
+            }
+        }
+        for pattern in objects(self.shapes, shape, sh::pattern) {
+            let flags = object(self.shapes, shape, sh::flags).and_then(|f| lexical(&f));
+            let regex = build_regex(&lexical(&pattern).unwrap_or_default(), flags.as_deref())?;
+            for value in values.iter() {
+                if !lexical(value).is_some_and(|s| regex.is_match(&s)) {
+                    results.push(context.result(
+                        term(sh::PatternConstraintComponent),
+                        Some(value.clone()),
+                        format!("Value does not match pattern \"{}\"", regex.as_str()),
+                    ));
+                }
+            }
+        }
+        if let Some(languages) = object(self.shapes, shape, sh::languageIn) {
+            let languages: Vec<String> =
+                list(self.shapes, &languages).iter().filter_map(lexical).collect();
+            for value in values.iter() {
+                let matches = value.language_tag().is_some_and(|tag| {
+                    let tag = tag.as_str().to_lowercase();
+                    languages.iter().any(|l| {
+                        let l = l.to_lowercase();
+                        tag == l || tag.starts_with(&format!("{}-", l))
+                    })
+                });
+                if !matches {
+                    results.push(context.result(
+                        term(sh::LanguageInConstraintComponent),
+                        Some(value.clone()),
+                        format!("Language tag is not one of {}", languages.join(", ")),
+                    ));
+                }
+            }
+        }
+        if path.is_some() && is_true(&object(self.shapes, shape, sh::uniqueLang)) {
+            let mut tags: HashMap<String, usize> = HashMap::new();
+            for value in values.iter() {
+                if let Some(tag) = value.language_tag() {
+                    *tags.entry(tag.as_str().to_lowercase()).or_default() += 1;
+                }
+            }
+            for (tag, _) in tags.into_iter().filter(|(_, count)| *count > 1) {
+                results.push(context.result(
+                    term(sh::UniqueLangConstraintComponent),
+                    None,
+                    format!("Language \"{}\" is used by more than one value", tag),
+                ));
+            }
+        }
+
+        // Property pair constraints
+        for other in objects(self.shapes, shape, sh::equals) {
+            let others = objects(self.data, focus, &other);
+            for value in values.iter().filter(|v| !others.contains(v)) {
+                results.push(context.result(
+                    term(sh::EqualsConstraintComponent),
+                    Some(value.clone()),
+                    format!("Value is not a value of {}", display(&other)),
+                ));
+            }
+            for value in others.iter().filter(|v| !values.contains(v)) {
+                results.push(context.result(
+                    term(sh::EqualsConstraintComponent),
+                    Some(value.clone()),
+                    format!("Value of {} is missing", display(&other)),
+                ));
+            }
+        }
+        for other in objects(self.shapes, shape, sh::disjoint) {
+            let others = objects(self.data, focus, &other);
+            for value in values.iter().filter(|v| others.contains(v)) {
+                results.push(context.result(
+                    term(sh::DisjointConstraintComponent),
+                    Some(value.clone()),
+                    format!("Value is also a value of {}", display(&other)),
+                ));
+            }
+        }
+        for (predicate, component, accepted, label) in [
+            (sh::lessThan, sh::LessThanConstraintComponent, &[Ordering::Less][..], "less than"),
+            (
+                sh::lessThanOrEquals,
+                sh::LessThanOrEqualsConstraintComponent,
+                &[Ordering::Less, Ordering::Equal][..],
+                "less than or equal to",
+            ),
+        ] {
+            for other in objects(self.shapes, shape, predicate) {
+                let others = objects(self.data, focus, &other);
+                for value in values.iter() {
+                    for o in others.iter() {
+                        if !compare(value, o).is_some_and(|ord| accepted.contains(&ord)) {
+                            results.push(context.result(
+                                term(component),
+                                Some(value.clone()),
+                                format!("Value is not {} {}", label, display(o)),
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+
+        // Logical constraints
+        for negated in objects(self.shapes, shape, sh::not) {
+            for value in values.iter() {
+                if self.conforms(&negated, value, depth)? {
+                    results.push(context.result(
+                        term(sh::NotConstraintComponent),
+                        Some(value.clone()),
+                        format!("Value conforms to {}", display(&negated)),
+                    ));
+                }
+            }
+        }
+        for (predicate, component, quantifier) in [
+            (sh::and, sh::AndConstraintComponent, Quantifier::All),
+            (sh::or, sh::OrConstraintComponent, Quantifier::Any),
+            (sh::xone, sh::XoneConstraintComponent, Quantifier::ExactlyOne),
+        ] {
+            for members in objects(self.shapes, shape, predicate) {
+                let members = list(self.shapes, &members);
+                for value in values.iter() {
+                    let mut conforming = 0;
+                    for member in members.iter() {
+                        if self.conforms(member, value, depth)? {
+                            conforming += 1;
+                        }
+                    }
+                    if !quantifier.accepts(conforming, members.len()) {
+                        results.push(context.result(
+                            term(component),
+                            Some(value.clone()),
+                            format!("Value does not satisfy {}", display(&term(predicate))),
+                        ));
+                    }
+                }
+            }
+        }
+
+        // Shape-based constraints
+        for node_shape in objects(self.shapes, shape, sh::node) {
+            for value in values.iter() {
+                if !self.conforms(&node_shape, value, depth)? {
+                    results.push(context.result(
+                        term(sh::NodeConstraintComponent),
+                        Some(value.clone()),
+                        format!("Value does not conform to {}", display(&node_shape)),
+                    ));
+                }
+            }
+        }
+        if let Some(qualified) = object(self.shapes, shape, sh::qualifiedValueShape) {
+            let mut conforming = 0;
+            for value in values.iter() {
+                if self.conforms(&qualified, value, depth)? {
+                    conforming += 1;
+                }
+            }
+            if let Some(min) =
+                object(self.shapes, shape, sh::qualifiedMinCount).and_then(|n| integer(&n))
+            {
+                if conforming < min {
+                    results.push(context.result(
+                        term(sh::QualifiedMinCountConstraintComponent),
+                        None,
+                        format!("Less than {} values conform to {}", min, display(&qualified)),
+                    ));
+                }
+            }
+            if let Some(max) =
+                object(self.shapes, shape, sh::qualifiedMaxCount).and_then(|n| integer(&n))
+            {
+                if conforming > max {
+                    results.push(context.result(
+                        term(sh::QualifiedMaxCountConstraintComponent),
+                        None,
+                        format!("More than {} values conform to {}", max, display(&qualified)),
+                    ));
+                }
+            }
+        }
+
+        // Other constraints
+        if is_true(&object(self.shapes, shape, sh::closed)) {
+            let mut allowed: HashSet<Node> = objects(self.shapes, shape, sh::property)
+                .iter()
+                .filter_map(|p| object(self.shapes, p, sh::path))
+                .filter(|p| p.kind() == TermKind::Iri)
+                .collect();
+            if let Some(ignored) = object(self.shapes, shape, sh::ignoredProperties) {
+                allowed.extend(list(self.shapes, &ignored));
+            }
+            for value in values.iter() {
+                for t in self.data.triples_matching([value], Any, Any).filter_map(Result::ok) {
+                    let predicate = term(t.p());
+                    if !allowed.contains(&predicate) {
+                        let mut result = context.result(
+                            term(sh::ClosedConstraintComponent),
+                            Some(term(t.o())),
+                            format!("Predicate {} is not allowed", display(&predicate)),
+                        );
+                        result.path = Some(predicate);
+                        results.push(result);
+                    }
+                }
+            }
+        }
+        for expected in objects(self.shapes, shape, sh::hasValue) {
+            if !values.contains(&expected) {
+                results.push(context.result(
+                    term(sh::HasValueConstraintComponent),
+                    None,
+                    format!("Missing expected value {}", display(&expected)),
+                ));
+            }
+        }
+        if let Some(members) = object(self.shapes, shape, sh::in_) {
+            let members = list(self.shapes, &members);
+            for value in values.iter().filter(|v| !members.contains(v)) {
+                results.push(context.result(
+                    term(sh::InConstraintComponent),
+                    Some(value.clone()),
+                    "Value is not in the list of allowed values".to_string(),
+                ));
+            }
+        }
+
+        // Nested property shapes report their own results.
+        for property in objects(self.shapes, shape, sh::property) {
+            for value in values.iter() {
+                self.validate_shape(&property, value, depth + 1, results)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn integer(node: &Node) -> Option<i64> {
+    node.lexical_form().and_then(|l| l.trim().parse::<i64>().ok())
+}
+
+fn matches_node_kind(value: &Node, kind: &Node) -> bool {
+    let kind = kind.iri().map(|i| i.as_str().to_string()).unwrap_or_default();
+    let allowed: &[TermKind] = match kind.strip_prefix(sh::PREFIX.as_str()) {
+        Some("IRI") => &[TermKind::Iri],
+        Some("BlankNode") => &[TermKind::BlankNode],
+        Some("Literal") => &[TermKind::Literal],
+        Some("BlankNodeOrIRI") => &[TermKind::BlankNode, TermKind::Iri],
+        Some("BlankNodeOrLiteral") => &[TermKind::BlankNode, TermKind::Literal],
+        Some("IRIOrLiteral") => &[TermKind::Iri, TermKind::Literal],
+        _ => &[],
+    };
+    allowed.contains(&value.kind())
+}
+
+fn build_regex(pattern: &str, flags: Option<&str>) -> Result<regex::Regex, Box<dyn Error>> {
+    let flags = flags.unwrap_or_default();
+    Ok(RegexBuilder::new(pattern)
+        .case_insensitive(flags.contains('i'))
+        .multi_line(flags.contains('m'))
+        .dot_matches_new_line(flags.contains('s'))
+        .ignore_whitespace(flags.contains('x'))
+        .build()?)
+}
+
+/// Short representation of a term for result messages.
+fn display(node: &Node) -> String {
+    match node.kind() {
+        TermKind::Iri => format!("<{}>", lexical(node).unwrap_or_default()),
+        TermKind::Literal => format!("\"{}\"", lexical(node).unwrap_or_default()),
+        _ => "a blank node".to_string(),
+    }
+}
+
+/// Copies the triples describing a blank node (e.g. a complex path) from `source` into `target`.
+fn copy_blank_node<G: Graph>(
+    source: &G,
+    node: &Node,
+    target: &mut LightGraph,
+) -> Result<(), Box<dyn Error>> {
+    let mut stack = vec![node.clone()];
+    let mut seen = HashSet::new();
+    while let Some(current) = stack.pop() {
+        if current.kind() != TermKind::BlankNode || !seen.insert(current.clone()) {
+            continue;
+        }
+        for t in source.triples_matching([&current], Any, Any).filter_map(Result::ok) {
+            let (p, o) = (term(t.p()), term(t.o()));
+            target.insert(&current, &p, &o)?;
+            stack.push(o);
+        }
+    }
+    Ok(())
+}
+
+/// Builds the sh:ValidationReport graph from the validation results.
+fn build_report(shapes: &FastGraph, results: &[ResultEntry]) -> Result<LightGraph, Box<dyn Error>> {
+    let mut report = LightGraph::new();
+    let report_node = generate_bnode_term();
+    let conforms = if results.is_empty() { "true" } else { "false" };
+
+    report.insert(&report_node, rdf::type_, sh::ValidationReport)?;
+    report.insert(&report_node, sh::conforms, (conforms * xsd::boolean).as_simple())?;
+
+    for result in results {
+        let result_node = generate_bnode_term();
+        report.insert(&report_node, sh::result, &result_node)?;
+        report.insert(&result_node, rdf::type_, sh::ValidationResult)?;
+        report.insert(&result_node, sh::focusNode, &result.focus_node)?;
+        if let Some(path) = &result.path {
+            report.insert(&result_node, sh::resultPath, path)?;
+            copy_blank_node(shapes, path, &mut report)?;
+        }
+        if let Some(value) = &result.value {
+            report.insert(&result_node, sh::value, value)?;
+        }
+        report.insert(&result_node, sh::sourceShape, &result.source_shape)?;
+        report.insert(&result_node, sh::sourceConstraintComponent, &result.component)?;
+        report.insert(&result_node, sh::resultSeverity, &result.severity)?;
+        for message in &result.messages {
+            report.insert(&result_node, sh::resultMessage, message)?;
+        }
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::Severity;
+    use catplus_common::rdf::rdf_parser::parse_turtle_to_graph;
+
+    const SHAPES: &str = r#"
+        PREFIX sh: <http://www.w3.org/ns/shacl#>
+        PREFIX xsd: <http://www.w3.org/2001/XMLSchema#>
+        PREFIX cat: <http://example.org/cat#>
+        PREFIX purl: <http://purl.allotrope.org/ontologies/>
+
+        cat:BatchShape a sh:NodeShape;
+            sh:targetClass cat:Batch;
+            sh:property [
+                sh:path purl:identifier;
+                sh:minCount 1;
+                sh:maxCount 1;
+                sh:datatype xsd:string;
+                sh:pattern "^[0-9]+$";
+            ].
+    "#;
+
+    #[test]
+    fn test_conforming_data() {
+        let data = parse_turtle_to_graph(
+            r#"
+            PREFIX cat: <http://example.org/cat#>
+            PREFIX purl: <http://purl.allotrope.org/ontologies/>
+            [] a cat:Batch; purl:identifier "23".
+            "#,
+        )
+        .unwrap();
+        let shapes = parse_turtle_to_graph(SHAPES).unwrap();
+
+        let report = NativeShaclEngine::new().validate(&data, Some(&shapes)).unwrap();
+        assert!(report.conforms, "valid batch should conform");
+    }
+
+    #[test]
+    fn test_violations_are_reported() {
+        let data = parse_turtle_to_graph(
+            r#"
+            PREFIX cat: <http://example.org/cat#>
+            PREFIX purl: <http://purl.allotrope.org/ontologies/>
+            <http://example.org/batch/1> a cat:Batch; purl:identifier "A1", "B2".
+            <http://example.org/batch/2> a cat:Batch.
+            "#,
+        )
+        .unwrap();
+        let shapes = parse_turtle_to_graph(SHAPES).unwrap();
+
+        let report = NativeShaclEngine::new().validate(&data, Some(&shapes)).unwrap();
+        assert!(!report.conforms, "invalid batches should not conform");
+
+        // maxCount and two pattern violations for batch 1, minCount for batch 2
+        let results = report.graph.triples_matching(Any, [sh::result], Any).count();
+        assert_eq!(results, 4);
+    }
+
+    #[test]
+    fn test_results_are_parsed() {
+        let data = parse_turtle_to_graph(
+            r#"
+            PREFIX cat: <http://example.org/cat#>
+            <http://example.org/batch/2> a cat:Batch.
+            "#,
+        )
+        .unwrap();
+        let shapes = parse_turtle_to_graph(SHAPES).unwrap();
+
+        let report = NativeShaclEngine::new().validate(&data, Some(&shapes)).unwrap();
+        assert_eq!(report.count(Severity::Violation), 1);
+        assert_eq!(report.count(Severity::Warning), 0);
+
+        let result = &report.results[0];
+        assert_eq!(result.focus_node, "http://example.org/batch/2");
+        assert_eq!(
+            result.result_path.as_deref(),
+            Some("http://purl.allotrope.org/ontologies/identifier")
+        );
+        assert_eq!(result.value, None);
+        assert_eq!(
+            result.constraint_component.as_deref(),
+            Some("http://www.w3.org/ns/shacl#MinCountConstraintComponent")
+        );
+        assert_eq!(result.severity, Severity::Violation);
+        assert!(result.source_shape.as_ref().unwrap().starts_with("_:"));
+        assert_eq!(result.messages.len(), 1);
+    }
+
+    #[test]
+    fn test_missing_shapes() {
+        let data = LightGraph::new();
+        assert!(NativeShaclEngine::new().validate(&data, None).is_err());
+    }
+
+    const PREFIXES: &str = r#"
+        PREFIX sh: <http://www.w3.org/ns/shacl#>
+        PREFIX rdf: <http://www.w3.org/1999/02/22-rdf-syntax-ns#>
+        PREFIX rdfs: <http://www.w3.org/2000/01/rdf-schema#>
+        PREFIX xsd: <http://www.w3.org/2001/XMLSchema#>
+        PREFIX ex: <http://example.org/>
+    "#;
+
+    fn validate(data: &str, shapes: &str) -> Result<ValidationReport, Box<dyn Error>> {
+        let data = parse_turtle_to_graph(&format!("{}{}", PREFIXES, data)).unwrap();
+        let shapes = parse_turtle_to_graph(&format!("{}{}", PREFIXES, shapes)).unwrap();
+        NativeShaclEngine::new().validate(&data, Some(&shapes))
+    }
+
+    /// The focus node and constraint component of each result, without their namespaces.
+    fn results(report: &ValidationReport) -> Vec<(String, String)> {
+        let mut results: Vec<(String, String)> = report
+            .results
+            .iter()
+            .map(|result| {
+                let component = result.constraint_component.as_deref().unwrap_or_default();
+                (
+                    result.focus_node.trim_start_matches("http://example.org/").to_string(),
+                    component.trim_start_matches(sh::PREFIX.as_str()).to_string(),
+                )
+            })
+            .collect();
+        results.sort();
+        results
+    }
+
+    fn result(focus: &str, component: &str) -> (String, String) {
+        (focus.to_string(), component.to_string())
+    }
+
+    #[test]
+    fn test_class() {
+        let report = validate(
+            r#"
+            ex:Solvent rdfs:subClassOf ex:Chemical.
+            ex:b1 a ex:Batch; ex:product ex:c1, ex:c2.
+            ex:b2 a ex:Batch; ex:product ex:x.
+            ex:c1 a ex:Chemical.
+            ex:c2 a ex:Solvent.
+            ex:x a ex:Container.
+            "#,
+            r#"
+            ex:BatchShape a sh:NodeShape;
+                sh:targetClass ex:Batch;
+                sh:property [ sh:path ex:product; sh:class ex:Chemical ].
+            "#,
+        )
+        .unwrap();
+        assert_eq!(results(&report), [result("b2", "ClassConstraintComponent")]);
+        assert_eq!(report.results[0].value.as_deref(), Some("http://example.org/x"));
+    }
+
+    #[test]
+    fn test_node() {
+        let report = validate(
+            r#"
+            ex:b1 a ex:Batch; ex:product ex:c1, ex:c2.
+            ex:c1 ex:casNumber "64-17-5".
+            "#,
+            r#"
+            ex:BatchShape a sh:NodeShape;
+                sh:targetClass ex:Batch;
+                sh:property [ sh:path ex:product; sh:node ex:ChemicalShape ].
+            ex:ChemicalShape a sh:NodeShape;
+                sh:property [ sh:path ex:casNumber; sh:minCount 1 ].
+            "#,
+        )
+        .unwrap();
+        // The nested shape only fails the sh:node constraint, it has no target of its own.
+        assert_eq!(results(&report), [result("b1", "NodeConstraintComponent")]);
+        assert_eq!(report.results[0].value.as_deref(), Some("http://example.org/c2"));
+    }
+
+    #[test]
+    fn test_logical_constraints() {
+        let report = validate(
+            r#"
+            ex:both ex:x 1; ex:y 2.
+            ex:onlyX ex:x 1.
+            ex:none ex:z 3.
+            "#,
+            r#"
+            ex:HasX sh:path ex:x; sh:minCount 1.
+            ex:HasY sh:path ex:y; sh:minCount 1.
+
+            ex:AndShape sh:targetNode ex:both, ex:onlyX; sh:and (ex:HasX ex:HasY).
+            ex:OrShape sh:targetNode ex:onlyX, ex:none; sh:or (ex:HasX ex:HasY).
+            ex:XoneShape sh:targetNode ex:both, ex:onlyX; sh:xone (ex:HasX ex:HasY).
+            ex:NotShape sh:targetNode ex:both, ex:none; sh:not ex:HasX.
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            results(&report),
+            [
+                result("both", "NotConstraintComponent"),
+                result("both", "XoneConstraintComponent"),
+                result("none", "OrConstraintComponent"),
+                result("onlyX", "AndConstraintComponent"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_paths() {
+        let data = r#"
+            ex:a ex:p ex:b; ex:r ex:f.
+            ex:b ex:q ex:c.
+            ex:c ex:p ex:d.
+            ex:d ex:q ex:g.
+            ex:e ex:r ex:a.
+        "#;
+        let report = validate(
+            data,
+            r#"
+            ex:PathShape sh:targetNode ex:a;
+                sh:property [ sh:path (ex:p ex:q); sh:in (ex:c); sh:minCount 1 ],
+                    [ sh:path [ sh:inversePath ex:r ]; sh:in (ex:e); sh:minCount 1 ],
+                    [ sh:path [ sh:alternativePath (ex:p ex:r) ]; sh:in (ex:b ex:f); sh:minCount 2 ],
+                    [ sh:path [ sh:zeroOrMorePath ex:p ]; sh:in (ex:a ex:b); sh:minCount 2 ],
+                    [ sh:path [ sh:oneOrMorePath (ex:p ex:q) ]; sh:in (ex:c ex:g); sh:minCount 2 ],
+                    [ sh:path [ sh:zeroOrOnePath ex:p ]; sh:in (ex:a ex:b); sh:minCount 2 ].
+            ex:InverseSequenceShape sh:targetNode ex:c;
+                sh:property [ sh:path [ sh:inversePath (ex:p ex:q) ]; sh:in (ex:a); sh:minCount 1 ].
+            "#,
+        )
+        .unwrap();
+        assert!(report.conforms, "{:?}", results(&report));
+
+        let report = validate(
+            data,
+            r#"
+            ex:PathShape sh:targetNode ex:a;
+                sh:property [ sh:path (ex:p ex:q); sh:hasValue ex:b ].
+            "#,
+        )
+        .unwrap();
+        assert_eq!(results(&report), [result("a", "HasValueConstraintComponent")]);
+        // Complex paths are reported as a copy of their blank node.
+        assert!(report.results[0].result_path.as_ref().unwrap().starts_with("_:"));
+    }
+
+    #[test]
+    fn test_closed() {
+        let report = validate(
+            r#"
+            ex:b1 a ex:Batch; ex:id "1".
+            ex:b2 a ex:Batch; ex:id "2"; ex:comment "extra".
+            "#,
+            r#"
+            ex:BatchShape a sh:NodeShape;
+                sh:targetClass ex:Batch;
+                sh:closed true;
+                sh:ignoredProperties (rdf:type);
+                sh:property [ sh:path ex:id ].
+            "#,
+        )
+        .unwrap();
+        assert_eq!(results(&report), [result("b2", "ClosedConstraintComponent")]);
+        assert_eq!(report.results[0].result_path.as_deref(), Some("http://example.org/comment"));
+    }
+
+    #[test]
+    fn test_in() {
+        let report = validate(
+            r#"
+            ex:b1 a ex:Batch; ex:status "done".
+            ex:b2 a ex:Batch; ex:status "failed".
+            ex:b3 a ex:Batch; ex:status ex:done.
+            "#,
+            r#"
+            ex:BatchShape a sh:NodeShape;
+                sh:targetClass ex:Batch;
+                sh:property [ sh:path ex:status; sh:in ("done" "running") ].
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            results(&report),
+            [result("b2", "InConstraintComponent"), result("b3", "InConstraintComponent")]
+        );
+    }
+
+    #[test]
+    fn test_qualified_value_shape() {
+        let report = validate(
+            r#"
+            ex:b1 a ex:Batch; ex:product ex:c1, ex:x.
+            ex:b2 a ex:Batch; ex:product ex:x.
+            ex:b3 a ex:Batch; ex:product ex:c1, ex:c2.
+            ex:c1 a ex:Chemical.
+            ex:c2 a ex:Chemical.
+            "#,
+            r#"
+            ex:BatchShape a sh:NodeShape;
+                sh:targetClass ex:Batch;
+                sh:property [
+                    sh:path ex:product;
+                    sh:qualifiedValueShape [ sh:class ex:Chemical ];
+                    sh:qualifiedMinCount 1;
+                    sh:qualifiedMaxCount 1;
+                ].
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            results(&report),
+            [
+                result("b2", "QualifiedMinCountConstraintComponent"),
+                result("b3", "QualifiedMaxCountConstraintComponent"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_recursive_shapes() {
+        let report = validate(
+            "ex:a ex:next ex:a.",
+            r#"
+            ex:LoopShape sh:targetNode ex:a;
+                sh:property [ sh:path ex:next; sh:node ex:LoopShape ].
+            "#,
+        );
+        assert!(report.is_err(), "recursive shapes are not supported");
+    }
+
+    #[test]
+    fn test_decimal_datatype() {
+        let report = validate(
+            r#"
+            ex:m1 ex:value "12"^^xsd:decimal, "-1.50"^^xsd:decimal,
+                "+.5"^^xsd:decimal, "3."^^xsd:decimal.
+            ex:m2 ex:value "1e3"^^xsd:decimal.
+            ex:m3 ex:value "INF"^^xsd:decimal.
+            ex:m4 ex:value "NaN"^^xsd:decimal.
+            ex:m5 ex:value "."^^xsd:decimal.
+            "#,
+            r#"
+            ex:ValueShape sh:targetSubjectsOf ex:value;
+                sh:property [ sh:path ex:value; sh:datatype xsd:decimal ].
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            results(&report),
+            ["m2", "m3", "m4", "m5"].map(|focus| result(focus, "DatatypeConstraintComponent"))
+        );
+    }
+}
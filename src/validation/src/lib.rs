@@ -1,2 +1,4 @@
 pub mod core;
 pub mod engines;
+pub mod report;
+pub mod shapes;
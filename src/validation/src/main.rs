@@ -1,18 +1,29 @@
 use anyhow::{Context, Result};
-use catplus_common::rdf::{
-    rdf_parser::parse_turtle_to_graph, rdf_serializers::serialize_graph_to_turtle,
-};
+use catplus_common::rdf::{rdf_format::RdfFormat, rdf_parser::parse_rdf_to_graph};
 use clap::Parser;
 use std::{
-    fs,
     fs::File,
-    io::{stdin, stdout, BufReader, BufWriter, Read, Write},
+    io::{stdin, stdout, BufReader, BufWriter, IsTerminal, Read, Write},
     path::{Path, PathBuf},
+    process::ExitCode,
+};
+use validation::{
+    core::*,
+    engines::{native::NativeShaclEngine, shacl_api::*},
+    report::{render_report, ReportFormat},
+    shapes::{Shapes, ShapesCache, ShapesSource},
 };
-use validation::{core::*, engines::shacl_api::*};
+
+#[derive(Debug, clap::ValueEnum, Clone)]
+enum Engine {
+    /// In-process SHACL Core validation, requires `--shapes`.
+    Native,
+    /// Validation through a shacl-api server, requires `--endpoint`.
+    ShaclApi,
+}
 
 // Validates an RDF file
-// Only turtle format is supported
+// The format of the input and shapes is detected from their extension or content
 #[derive(Parser, Debug)]
 struct Args {
     /// Path to the input RDF data.
@@ -25,22 +36,102 @@ struct Args {
     #[arg(short, long, default_value = "-")]
     output: PathBuf,
 
-    /// Path to the shapes file.
+    /// Format of the input RDF data, detected from its extension or content by default.
+    #[arg(short, long, value_enum, default_value = None)]
+    input_format: Option<RdfFormat>,
+
+    /// Shapes to validate against: a file path, an HTTP(S) URL, or a catplus-ontology
+    /// release tag (e.g. v0.1.0). Downloaded shapes are cached.
     /// If not provided, the default shapes of the validation engine will be used.
     #[arg(short, long, default_value=None)]
-    shapes: Option<PathBuf>,
+    shapes: Option<ShapesSource>,
+
+    /// Directory in which downloaded shapes are cached, one directory per version.
+    /// Defaults to $CATPLUS_CACHE_DIR, or catplus/shapes in $XDG_CACHE_HOME or ~/.cache.
+    #[arg(long, default_value = None)]
+    cache_dir: Option<PathBuf>,
+
+    /// Only use cached shapes, never download them.
+    #[arg(long, default_value_t = false)]
+    offline: bool,
+
+    /// Download the shapes again even if they are cached.
+    #[arg(long, default_value_t = false, conflicts_with = "offline")]
+    refresh_shapes: bool,
+
+    /// Validation engine to use.
+    #[arg(long, value_enum, default_value = "shacl-api")]
+    engine: Engine,
 
     /// Endpoint of the SHACL API server.
     #[arg(short, long)]
-    endpoint: String,
+    endpoint: Option<String>,
+
+    /// Format of the validation report.
+    #[arg(short, long, value_enum, default_value = "turtle")]
+    format: ReportFormat,
+
+    /// Lowest severity that fails the validation.
+    /// By default, the validation fails when the data does not conform.
+    #[arg(long, value_enum, default_value = None)]
+    fail_on: Option<Severity>,
+}
+
+/// Exit code when the data fails the validation, errors exit with code 2.
+const VALIDATION_FAILED: u8 = 1;
+
+fn main() -> ExitCode {
+    match run(Args::parse()) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(VALIDATION_FAILED),
+        Err(error) => {
+            eprintln!("Error: {:?}", error);
+            ExitCode::from(2)
+        }
+    }
 }
 
-fn main() -> Result<()> {
-    let args = Args::parse();
+/// Validates the input and writes the report, returns whether the validation passed.
+fn run(args: Args) -> Result<bool> {
+    let engine: Box<dyn ShaclEngine> = match args.engine {
+        Engine::Native => Box::new(NativeShaclEngine::new()),
+        Engine::ShaclApi => {
+            let endpoint =
+                args.endpoint.context("An endpoint is required by the shacl-api engine")?;
+            let shacl_api = ShaclApiEndpoint::new(endpoint.clone());
+            // Check if the endpoint is reachable
+            if !shacl_api.is_available() {
+                return Err(anyhow::anyhow!("SHACL API is not available at {}", endpoint));
+            }
+            Box::new(shacl_api)
+        }
+    };
+
+    // Colours are only written to a terminal, see https://no-color.org
+    let color = args.output.to_str() == Some("-")
+        && stdout().is_terminal()
+        && std::env::var_os("NO_COLOR").is_none();
+
+    let cache = ShapesCache::new(args.cache_dir.unwrap_or_else(ShapesCache::default_dir))
+        .with_offline(args.offline)
+        .with_refresh(args.refresh_shapes);
+    let shapes = args.shapes.map(|source| cache.resolve(&source)).transpose()?;
+
+    let report = validate_graph(args.input, args.input_format, shapes, engine.as_ref())?;
 
-    validate_graph(args.input, args.output, args.shapes, args.endpoint)?;
+    let passed = match args.fail_on {
+        Some(threshold) => !report.fails_at(threshold),
+        None => report.conforms,
+    };
+    // Any result makes the data non-conforming, i.e. fails at the Info level.
+    let fail_on = args.fail_on.unwrap_or(Severity::Info);
+
+    let mut sink = get_writer(&args.output)?;
+    sink.write_all(render_report(&report, args.format, fail_on, color)?.as_bytes())
+        .context("Failed to write to output file")?;
+    sink.flush().context("Failed to write to output file")?;
 
-    Ok(())
+    Ok(passed)
 }
 
 // Get a reader based on input path, either from stdin or a file.
@@ -61,38 +152,30 @@ pub fn get_writer(path: &Path) -> Result<Box<dyn Write>> {
 
 fn validate_graph(
     input: PathBuf,
-    output: PathBuf,
-    shapes: Option<PathBuf>,
-    endpoint: String,
-) -> Result<()> {
-    // Check if the endpoint is reachable
-    let shacl_api = ShaclApiEndpoint::new(endpoint.clone());
-    if !shacl_api.is_available() {
-        return Err(anyhow::anyhow!("SHACL API is not available at {}", endpoint));
-    }
-
-    // Parse I/O paths
+    input_format: Option<RdfFormat>,
+    shapes: Option<Shapes>,
+    engine: &dyn ShaclEngine,
+) -> Result<ValidationReport> {
+    // Parse input path
     let mut source = get_reader(&input)?;
-    let mut sink = get_writer(&output)?;
 
     // Read whole files as strings
     let mut input_data = String::new();
     source.read_to_string(&mut input_data).context("Failed to read input data")?;
 
-    let shapes_data =
-        shapes.map(|path| fs::read_to_string(path).expect("Failed to read shapes file"));
-
-    // Parse into triple graphs
-    let data_graph =
-        parse_turtle_to_graph(&input_data).context("Failed to parse input RDF data")?;
+    // Parse into triple graphs, the format is detected if it is not given
+    let input_format = input_format.or(RdfFormat::from_path(&input));
+    let data_graph = parse_rdf_to_graph(&input_data, input_format.as_ref())
+        .context("Failed to parse input RDF data")?;
 
-    let shapes_graph =
-        shapes_data.map(|data| parse_turtle_to_graph(&data).expect("Failed to parse shapes data"));
+    let report = engine
+        .validate(&data_graph, shapes.as_ref().map(|shapes| &shapes.graph))
+        .map_err(|e| anyhow::anyhow!("Validation failed: {}", e))?;
 
-    let report = shacl_api.validate(&data_graph, shapes_graph.as_ref()).unwrap();
-
-    // Write the validation report to the output
-    sink.write_all(serialize_graph_to_turtle(&report.graph).unwrap().as_bytes())
-        .context("Failed to write to output file")?;
-    Ok(())
+    match shapes {
+        Some(shapes) => report
+            .with_shapes(shapes.info)
+            .map_err(|e| anyhow::anyhow!("Failed to record the shapes in the report: {}", e)),
+        None => Ok(report),
+    }
 }
@@ -0,0 +1,308 @@
+/// Renders validation reports for humans and for CI tools.
+use crate::core::{Severity, ShapesInfo, ValidationReport, ValidationResult};
+use anyhow::{Context, Result};
+use catplus_common::rdf::rdf_serializers::serialize_graph_to_turtle;
+use serde_json::json;
+use std::{collections::BTreeMap, fmt::Write};
+
+/// Output format of a validation report.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum ReportFormat {
+    /// The SHACL report graph as Turtle.
+    Turtle,
+    /// A summary grouped by focus node and shape.
+    Human,
+    /// The parsed results as JSON, e.g. for CI annotations.
+    Json,
+    /// JUnit XML, one test case per focus node and shape.
+    Junit,
+}
+
+/// Renders `report` in the given format.
+///
+/// `fail_on` is the lowest severity counted as a failure (in JUnit XML and in the human
+/// summary), and `color` enables ANSI colours in the human summary.
+pub fn render_report(
+    report: &ValidationReport,
+    format: ReportFormat,
+    fail_on: Severity,
+    color: bool,
+) -> Result<String> {
+    match format {
+        ReportFormat::Turtle => serialize_graph_to_turtle(&report.graph),
+        ReportFormat::Human => Ok(render_human(report, fail_on, color)),
+        ReportFormat::Json => render_json(report),
+        ReportFormat::Junit => Ok(render_junit(report, fail_on)),
+    }
+}
+
+/// Results grouped by focus node, then by source shape, in the order of the report.
+fn group_results(
+    report: &ValidationReport,
+) -> BTreeMap<&str, BTreeMap<&str, Vec<&ValidationResult>>> {
+    let mut groups: BTreeMap<&str, BTreeMap<&str, Vec<&ValidationResult>>> = BTreeMap::new();
+    for result in &report.results {
+        let shape = result.source_shape.as_deref().unwrap_or("unknown shape");
+        groups.entry(&result.focus_node).or_default().entry(shape).or_default().push(result);
+    }
+    groups
+}
+
+fn summary_line(report: &ValidationReport) -> String {
+    [Severity::Violation, Severity::Warning, Severity::Info]
+        .iter()
+        .map(|severity| format!("{} {:?}", report.count(*severity), severity))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// A short description of a result: its path, value and messages.
+fn describe(result: &ValidationResult) -> String {
+    let mut description = String::new();
+    if let Some(path) = &result.result_path {
+        write!(description, "{}: ", path).unwrap();
+    }
+    match result.messages.as_slice() {
+        [] => description.push_str(result.constraint_component.as_deref().unwrap_or("failed")),
+        messages => description.push_str(&messages.join("; ")),
+    }
+    if let Some(value) = &result.value {
+        write!(description, " (value: {})", value).unwrap();
+    }
+    description
+}
+
+/// The source of the shapes, with their release tag or checksum.
+fn describe_shapes(shapes: &ShapesInfo) -> String {
+    match &shapes.version {
+        Some(version) => format!("{} ({})", shapes.source, version),
+        None => {
+            format!("{} (sha256 {})", shapes.source, &shapes.sha256[..12.min(shapes.sha256.len())])
+        }
+    }
+}
+
+fn render_human(report: &ValidationReport, fail_on: Severity, color: bool) -> String {
+    let paint = |code: &str, text: &str| -> String {
+        if color {
+            format!("\x1b[{}m{}\x1b[0m", code, text)
+        } else {
+            text.to_string()
+        }
+    };
+
+    let mut output = String::new();
+    if report.fails_at(fail_on) {
+        writeln!(output, "{} ({})", paint("1;31", "Validation failed"), summary_line(report))
+    } else {
+        writeln!(output, "{} ({})", paint("1;32", "Validation passed"), summary_line(report))
+    }
+    .unwrap();
+    if let Some(shapes) = &report.shapes {
+        writeln!(output, "Shapes: {}", describe_shapes(shapes)).unwrap();
+    }
+
+    for (focus_node, shapes) in group_results(report) {
+        writeln!(output, "\n{}", paint("1", focus_node)).unwrap();
+        for (shape, results) in shapes {
+            writeln!(output, "  shape {}", shape).unwrap();
+            for result in results {
+                let code = match result.severity {
+                    Severity::Violation => "31",
+                    Severity::Warning => "33",
+                    Severity::Info => "34",
+                };
+                let severity = paint(code, &format!("[{:?}]", result.severity));
+                writeln!(output, "    {} {}", severity, describe(result)).unwrap();
+            }
+        }
+    }
+    output
+}
+
+fn render_json(report: &ValidationReport) -> Result<String> {
+    let summary: BTreeMap<_, _> = report.summary.iter().collect();
+    let json = json!({
+        "conforms": report.conforms,
+        "summary": summary,
+        "shapes": report.shapes,
+        "results": report.results,
+    });
+    let json =
+        serde_json::to_string_pretty(&json).context("Failed to serialize the report to JSON")?;
+    Ok(json + "\n")
+}
+
+fn render_junit(report: &ValidationReport, fail_on: Severity) -> String {
+    let mut cases = String::new();
+    let (mut tests, mut failures) = (0, 0);
+
+    for (focus_node, shapes) in group_results(report) {
+        for (shape, results) in shapes {
+            tests += 1;
+            writeln!(
+                cases,
+                "    <testcase classname=\"{}\" name=\"{}\">",
+                escape_xml(shape),
+                escape_xml(focus_node)
+            )
+            .unwrap();
+            let (failed, other): (Vec<&ValidationResult>, Vec<&ValidationResult>) =
+                results.into_iter().partition(|r| r.severity.is_at_least(fail_on));
+            if !failed.is_empty() {
+                failures += 1;
+                let details: Vec<String> = failed.iter().map(|r| describe(r)).collect();
+                writeln!(
+                    cases,
+                    "      <failure type=\"{:?}\" message=\"{}\">{}</failure>",
+                    failed[0].severity,
+                    escape_xml(&details[0]),
+                    escape_xml(&details.join("\n"))
+                )
+                .unwrap();
+            }
+            if !other.is_empty() {
+                let details: Vec<String> =
+                    other.iter().map(|r| format!("[{:?}] {}", r.severity, describe(r))).collect();
+                writeln!(
+                    cases,
+                    "      <system-out>{}</system-out>",
+                    escape_xml(&details.join("\n"))
+                )
+                .unwrap();
+            }
+            cases.push_str("    </testcase>\n");
+        }
+    }
+    if tests == 0 {
+        tests = 1;
+        cases.push_str("    <testcase classname=\"shacl\" name=\"conforms\"/>\n");
+    }
+
+    let properties = match &report.shapes {
+        Some(shapes) => format!(
+            "    <properties>\n      <property name=\"shapes\" value=\"{}\"/>\n    </properties>\n",
+            escape_xml(&describe_shapes(shapes))
+        ),
+        None => String::new(),
+    };
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <testsuites name=\"shacl-validation\" tests=\"{tests}\" failures=\"{failures}\">\n  \
+         <testsuite name=\"shacl-validation\" tests=\"{tests}\" failures=\"{failures}\">\n\
+         {properties}{cases}  </testsuite>\n</testsuites>\n"
+    )
+}
+
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use catplus_common::rdf::rdf_parser::parse_turtle_to_graph;
+
+    const REPORT: &str = r#"
+        PREFIX sh: <http://www.w3.org/ns/shacl#>
+        PREFIX ex: <http://example.org/>
+
+        [] a sh:ValidationReport;
+            sh:conforms false;
+            sh:result [ a sh:ValidationResult;
+                sh:focusNode ex:batch1;
+                sh:resultPath ex:identifier;
+                sh:sourceShape ex:BatchShape;
+                sh:sourceConstraintComponent sh:MinCountConstraintComponent;
+                sh:resultSeverity sh:Violation;
+                sh:resultMessage "Missing <identifier>"
+            ], [ a sh:ValidationResult;
+                sh:focusNode ex:batch2;
+                sh:resultPath ex:identifier;
+                sh:value "A";
+                sh:sourceShape ex:BatchShape;
+                sh:sourceConstraintComponent sh:PatternConstraintComponent;
+                sh:resultSeverity sh:Warning
+            ].
+    "#;
+
+    fn report() -> ValidationReport {
+        ValidationReport::from_graph(parse_turtle_to_graph(REPORT).unwrap())
+    }
+
+    #[test]
+    fn test_fails_at() {
+        let report = report();
+        assert!(report.fails_at(Severity::Violation));
+        assert!(report.fails_at(Severity::Info));
+        assert_eq!(report.count(Severity::Warning), 1);
+    }
+
+    #[test]
+    fn test_render_human() {
+        let output = render_report(&report(), ReportFormat::Human, Severity::Info, false).unwrap();
+        let expected = "Validation failed (1 Violation, 1 Warning, 0 Info)\n\
+            \nhttp://example.org/batch1\n  shape http://example.org/BatchShape\n    \
+            [Violation] http://example.org/identifier: Missing <identifier>\n\
+            \nhttp://example.org/batch2\n  shape http://example.org/BatchShape\n    \
+            [Warning] http://example.org/identifier: \
+            http://www.w3.org/ns/shacl#PatternConstraintComponent (value: A)\n";
+        assert_eq!(output, expected);
+
+        let colored = render_report(&report(), ReportFormat::Human, Severity::Info, true).unwrap();
+        assert!(colored.contains("\x1b[31m[Violation]\x1b[0m"));
+    }
+
+    #[test]
+    fn test_render_json() {
+        let output = render_report(&report(), ReportFormat::Json, Severity::Info, false).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+
+        assert_eq!(json["conforms"], false);
+        assert_eq!(json["summary"], json!({ "Violation": 1, "Warning": 1 }));
+        assert_eq!(json["results"][0]["focus_node"], "http://example.org/batch1");
+        assert_eq!(json["results"][1]["value"], "A");
+    }
+
+    #[test]
+    fn test_render_junit() {
+        let output =
+            render_report(&report(), ReportFormat::Junit, Severity::Violation, false).unwrap();
+
+        assert!(output.contains(r#"<testsuite name="shacl-validation" tests="2" failures="1">"#));
+        assert!(output.contains(
+            r#"<failure type="Violation" message="http://example.org/identifier: Missing &lt;identifier&gt;">"#
+        ));
+        // Warnings are below the threshold and do not fail.
+        assert!(output.contains("<system-out>[Warning] http://example.org/identifier: "));
+    }
+
+    #[test]
+    fn test_shapes_are_recorded() {
+        let shapes = ShapesInfo {
+            source: "https://example.org/shapes.ttl".to_string(),
+            version: Some("v0.1.0".to_string()),
+            sha256: "0123456789abcdef".to_string(),
+        };
+        let report = report().with_shapes(shapes).unwrap();
+
+        let human = render_report(&report, ReportFormat::Human, Severity::Info, false).unwrap();
+        assert!(human.contains("Shapes: https://example.org/shapes.ttl (v0.1.0)\n"));
+        let json = render_report(&report, ReportFormat::Json, Severity::Info, false).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(json["shapes"]["version"], "v0.1.0");
+        let junit = render_report(&report, ReportFormat::Junit, Severity::Info, false).unwrap();
+        assert!(junit.contains(
+            r#"<property name="shapes" value="https://example.org/shapes.ttl (v0.1.0)"/>"#
+        ));
+        let turtle = render_report(&report, ReportFormat::Turtle, Severity::Info, false).unwrap();
+        assert!(turtle.contains("schema:version \"v0.1.0\""));
+        assert!(turtle.contains("prov:atLocation \"https://example.org/shapes.ttl\""));
+    }
+}
@@ -0,0 +1,264 @@
+/// Resolution of SHACL shapes from files, URLs and catplus-ontology releases, with a disk cache.
+use crate::core::ShapesInfo;
+use anyhow::{Context, Result};
+use catplus_common::rdf::{rdf_format::RdfFormat, rdf_parser::parse_rdf_to_graph};
+use sha2::{Digest, Sha256};
+use sophia::inmem::graph::LightGraph;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// Repository of the CAT+ ontology, which publishes the shapes with each release.
+pub const ONTOLOGY_REPOSITORY: &str = "https://github.com/sdsc-ordes/catplus-ontology";
+
+/// Name of the shapes file in the ontology releases.
+pub const SHAPES_FILE: &str = "catplus_ontology.ttl";
+
+/// Where to find SHACL shapes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ShapesSource {
+    /// A local file.
+    File(PathBuf),
+    /// An HTTP(S) URL.
+    Url(String),
+    /// A release tag of the catplus-ontology, e.g. `v0.1.0`.
+    Release(String),
+}
+
+impl FromStr for ShapesSource {
+    type Err = anyhow::Error;
+
+    /// Reads a URL, an existing file, or a release tag (`v` followed by a version number).
+    fn from_str(source: &str) -> Result<Self> {
+        if source.starts_with("http://") || source.starts_with("https://") {
+            return Ok(match release_tag_of_url(source) {
+                Some(tag) => ShapesSource::Release(tag.to_string()),
+                None => ShapesSource::Url(source.to_string()),
+            });
+        }
+        if !Path::new(source).exists() && is_release_tag(source) {
+            return Ok(ShapesSource::Release(source.to_string()));
+        }
+        Ok(ShapesSource::File(PathBuf::from(source)))
+    }
+}
+
+/// Whether `tag` looks like a release tag, e.g. `v0.1.0` or `v1.0.0-rc.1`.
+fn is_release_tag(tag: &str) -> bool {
+    let Some(version) = tag.strip_prefix('v') else {
+        return false;
+    };
+    let (number, suffix) = version.split_once('-').unwrap_or((version, ""));
+    !number.is_empty()
+        && number
+            .split('.')
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+        && suffix.chars().all(|c| c.is_ascii_alphanumeric() || c == '.')
+}
+
+/// The release tag of a shapes file URL of the ontology repository, if any.
+fn release_tag_of_url(url: &str) -> Option<&str> {
+    let path = url.strip_prefix(ONTOLOGY_REPOSITORY)?.strip_prefix("/releases/download/")?;
+    match path.split_once('/') {
+        Some((tag, SHAPES_FILE)) if is_release_tag(tag) => Some(tag),
+        _ => None,
+    }
+}
+
+impl ShapesSource {
+    /// URL to download the shapes from, `None` for local files.
+    pub fn url(&self) -> Option<String> {
+        match self {
+            ShapesSource::File(_) => None,
+            ShapesSource::Url(url) => Some(url.clone()),
+            ShapesSource::Release(tag) => {
+                Some(format!("{}/releases/download/{}/{}", ONTOLOGY_REPOSITORY, tag, SHAPES_FILE))
+            }
+        }
+    }
+
+    /// Location of the shapes in a cache directory, `None` for local files.
+    ///
+    /// Releases are stored under their tag, e.g. `v0.1.0/catplus_ontology.ttl`, other URLs
+    /// under a hash of the URL.
+    fn cache_path(&self) -> Option<PathBuf> {
+        match self {
+            ShapesSource::File(_) => None,
+            ShapesSource::Url(url) => {
+                let hash = format!("{:x}", Sha256::digest(url.as_bytes()));
+                let name = url.rsplit('/').next().filter(|name| !name.is_empty());
+                Some(Path::new("url").join(&hash[..16]).join(name.unwrap_or(SHAPES_FILE)))
+            }
+            ShapesSource::Release(tag) => Some(Path::new(tag).join(SHAPES_FILE)),
+        }
+    }
+}
+
+/// Parsed shapes and where they come from.
+pub struct Shapes {
+    pub graph: LightGraph,
+    pub info: ShapesInfo,
+}
+
+/// Resolves shapes, and keeps the downloaded ones in a cache directory.
+///
+/// Cached shapes are used without checking for updates unless `refresh` is set, and
+/// nothing is downloaded when `offline` is set.
+pub struct ShapesCache {
+    dir: PathBuf,
+    offline: bool,
+    refresh: bool,
+}
+
+impl ShapesCache {
+    pub fn new(dir: PathBuf) -> Self {
+        ShapesCache { dir, offline: false, refresh: false }
+    }
+
+    /// Fails instead of downloading shapes that are not cached.
+    pub fn with_offline(self, offline: bool) -> Self {
+        ShapesCache { offline, ..self }
+    }
+
+    /// Downloads the shapes again even if they are cached.
+    pub fn with_refresh(self, refresh: bool) -> Self {
+        ShapesCache { refresh, ..self }
+    }
+
+    /// The default cache directory: `$CATPLUS_CACHE_DIR`, or `catplus/shapes` in
+    /// `$XDG_CACHE_HOME` or `~/.cache`.
+    pub fn default_dir() -> PathBuf {
+        if let Some(dir) = std::env::var_os("CATPLUS_CACHE_DIR") {
+            return PathBuf::from(dir);
+        }
+        let cache_home = std::env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))
+            .unwrap_or_else(std::env::temp_dir);
+        cache_home.join("catplus").join("shapes")
+    }
+
+    /// Reads and parses the shapes of `source`, from the cache if they were downloaded before.
+    pub fn resolve(&self, source: &ShapesSource) -> Result<Shapes> {
+        let (content, location) = match (source, source.url(), source.cache_path()) {
+            (ShapesSource::File(path), _, _) => {
+                let content = fs::read_to_string(path)
+                    .with_context(|| format!("Failed to read shapes file '{}'", path.display()))?;
+                (content, path.clone())
+            }
+            (_, Some(url), Some(cache_path)) => {
+                let cache_path = self.dir.join(cache_path);
+                (self.fetch(&url, &cache_path)?, PathBuf::from(url))
+            }
+            _ => unreachable!("Remote shapes have a URL and a cache path"),
+        };
+
+        let format = RdfFormat::from_path(&location);
+        let graph = parse_rdf_to_graph(&content, format.as_ref())
+            .with_context(|| format!("Failed to parse shapes from '{}'", location.display()))?;
+        let info = ShapesInfo {
+            source: location.display().to_string(),
+            version: match source {
+                ShapesSource::Release(tag) => Some(tag.clone()),
+                _ => None,
+            },
+            sha256: format!("{:x}", Sha256::digest(content.as_bytes())),
+        };
+
+        Ok(Shapes { graph, info })
+    }
+
+    /// Returns the cached content of `url`, downloading it into the cache if needed.
+    fn fetch(&self, url: &str, cache_path: &Path) -> Result<String> {
+        if cache_path.is_file() && !self.refresh {
+            return fs::read_to_string(cache_path).with_context(|| {
+                format!("Failed to read cached shapes '{}'", cache_path.display())
+            });
+        }
+        if self.offline {
+            anyhow::bail!(
+                "The shapes at '{}' are not cached in '{}' and downloads are disabled",
+                url,
+                self.dir.display()
+            );
+        }
+
+        let content = reqwest::blocking::get(url)
+            .and_then(|response| response.error_for_status())
+            .and_then(|response| response.text())
+            .with_context(|| format!("Failed to download shapes from '{}'", url))?;
+
+        // Write to a temporary file first so that an interrupted download is not cached.
+        let parent = cache_path.parent().unwrap_or(&self.dir);
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create cache directory '{}'", parent.display()))?;
+        let partial = cache_path.with_extension("part");
+        fs::write(&partial, &content)
+            .and_then(|_| fs::rename(&partial, cache_path))
+            .with_context(|| format!("Failed to cache shapes in '{}'", cache_path.display()))?;
+
+        Ok(content)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sophia_api::prelude::*;
+
+    const SHAPES: &str = r#"
+        @prefix sh: <http://www.w3.org/ns/shacl#> .
+        @prefix ex: <http://example.org/> .
+
+        ex:PlateShape a sh:NodeShape ;
+            sh:targetClass ex:Plate .
+    "#;
+
+    #[test]
+    fn test_parse_source() {
+        assert_eq!(
+            "v0.1.0".parse::<ShapesSource>().unwrap(),
+            ShapesSource::Release("v0.1.0".to_string())
+        );
+        assert_eq!(
+            "https://github.com/sdsc-ordes/catplus-ontology/releases/download/v0.2.0/catplus_ontology.ttl"
+                .parse::<ShapesSource>()
+                .unwrap(),
+            ShapesSource::Release("v0.2.0".to_string())
+        );
+        assert_eq!(
+            "https://example.org/shapes.ttl".parse::<ShapesSource>().unwrap(),
+            ShapesSource::Url("https://example.org/shapes.ttl".to_string())
+        );
+        assert_eq!(
+            "shapes/catplus.ttl".parse::<ShapesSource>().unwrap(),
+            ShapesSource::File(PathBuf::from("shapes/catplus.ttl"))
+        );
+        assert!(!is_release_tag("v1..0"));
+        assert!(is_release_tag("v1.0.0-rc.1"));
+    }
+
+    #[test]
+    fn test_offline_uses_cache() {
+        let dir = std::env::temp_dir().join(format!("catplus-shapes-{}", std::process::id()));
+        let cache = ShapesCache::new(dir.clone()).with_offline(true);
+        let source = ShapesSource::Release("v0.1.0".to_string());
+
+        let error = cache.resolve(&source).err().unwrap();
+        assert!(error.to_string().contains("not cached"));
+
+        let cached = dir.join("v0.1.0").join(SHAPES_FILE);
+        fs::create_dir_all(cached.parent().unwrap()).unwrap();
+        fs::write(&cached, SHAPES).unwrap();
+
+        let shapes = cache.resolve(&source).unwrap();
+        assert_eq!(shapes.info.version.as_deref(), Some("v0.1.0"));
+        assert_eq!(shapes.info.source, source.url().unwrap());
+        assert_eq!(shapes.info.sha256.len(), 64);
+        assert!(shapes.graph.triples().count() > 0);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}